@@ -11,7 +11,7 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
         .add_plugin(RapierDebugRenderPlugin::default())
-        .add_plugin(WanderlustPlugin)
+        .add_plugin(WanderlustPlugin::default())
         .insert_resource(Sensitivity(0.15))
         .add_startup_system(setup)
         // Add to PreUpdate to ensure updated before movement is calculated
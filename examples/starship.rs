@@ -27,7 +27,7 @@ fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
         .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
-        .add_plugin(WanderlustPlugin)
+        .add_plugin(WanderlustPlugin::default())
         // .add_plugin(EditorPlugin)
         // .insert_resource(bindings)
         .add_startup_system(setup)
@@ -0,0 +1,142 @@
+use crate::components::{ControllerSettings, ControllerState, SurfaceMaterial};
+use crate::events::{LandedEvent, SlidingChangedEvent, UngroundedCause, UngroundedEvent};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_kira_audio::{Audio, AudioControl, AudioSource};
+
+/// Plays footstep, landing, jumping and sliding sounds for every character controller through `bevy_kira_audio`,
+/// picked by the [`SurfaceMaterial`] of the ground. Configure the sounds with the [`MovementSounds`] resource.
+///
+/// Requires the `kira` feature, and `bevy_kira_audio`'s `AudioPlugin`.
+pub struct WanderlustAudioPlugin;
+
+impl Plugin for WanderlustAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovementSounds>()
+            .add_system_to_stage(CoreStage::PostUpdate, play_movement_sounds);
+    }
+}
+
+/// Which sounds [`WanderlustAudioPlugin`] plays, and how.
+pub struct MovementSounds {
+    /// The sounds of each [`SurfaceMaterial`], by name.
+    pub surfaces: HashMap<String, SurfaceSounds>,
+    /// The sounds of ground without a [`SurfaceMaterial`], or with one that isn't in [`surfaces`](MovementSounds::surfaces).
+    pub default: SurfaceSounds,
+    /// How far a grounded character moves between footsteps.
+    pub stride: f32,
+    /// The volume to play sounds at.
+    pub volume: f64,
+    /// How much the volume of each sound is randomly raised or lowered by, as a fraction of [`volume`](MovementSounds::volume).
+    pub volume_variation: f64,
+    /// How much the pitch of each sound is randomly raised or lowered by, as a fraction of its normal pitch.
+    pub pitch_variation: f64,
+}
+
+impl Default for MovementSounds {
+    fn default() -> Self {
+        Self {
+            surfaces: default(),
+            default: default(),
+            stride: 1.5,
+            volume: 1.0,
+            volume_variation: 0.1,
+            pitch_variation: 0.1,
+        }
+    }
+}
+
+impl MovementSounds {
+    /// The sounds of the given ground.
+    pub fn surface(&self, material: Option<&SurfaceMaterial>) -> &SurfaceSounds {
+        material
+            .and_then(|material| self.surfaces.get(&material.0))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// The sounds of moving on one [`SurfaceMaterial`]. Each time a sound is needed, one of the handles in its list is picked at
+/// random, so several variations can be given to avoid repetition. Leave a list empty for silence.
+#[derive(Default, Clone)]
+pub struct SurfaceSounds {
+    /// Played every [`stride`](MovementSounds::stride) a character walks.
+    pub footsteps: Vec<Handle<AudioSource>>,
+    /// Played when a character lands.
+    pub land: Vec<Handle<AudioSource>>,
+    /// Played when a character jumps off of the ground.
+    pub jump: Vec<Handle<AudioSource>>,
+    /// Played when a character starts sliding. See [`ControllerState::sliding`].
+    pub slide: Vec<Handle<AudioSource>>,
+}
+
+/// What [`play_movement_sounds`] remembers about a character from the previous frame.
+struct SoundTracker {
+    walked: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_movement_sounds(
+    sounds: Res<MovementSounds>,
+    audio: Res<Audio>,
+    characters: Query<(Entity, &ControllerState, &ControllerSettings)>,
+    time: Res<Time>,
+    surfaces: Query<&SurfaceMaterial>,
+    mut ungrounded_events: EventReader<UngroundedEvent>,
+    mut landed_events: EventReader<LandedEvent>,
+    mut sliding_events: EventReader<SlidingChangedEvent>,
+    mut trackers: Local<HashMap<Entity, SoundTracker>>,
+) {
+    let play = |handles: &[Handle<AudioSource>]| {
+        if handles.is_empty() {
+            return;
+        }
+
+        let handle = handles[fastrand::usize(..handles.len())].clone();
+        let vary = |variation: f64| 1.0 + variation * (fastrand::f64() * 2.0 - 1.0);
+        audio
+            .play(handle)
+            .with_volume(sounds.volume * vary(sounds.volume_variation))
+            .with_playback_rate(vary(sounds.pitch_variation));
+    };
+    let surface = |ground: Option<Entity>| {
+        sounds.surface(ground.and_then(|ground| surfaces.get(ground).ok()))
+    };
+
+    for event in ungrounded_events.iter() {
+        if event.cause == UngroundedCause::Jumped {
+            play(&surface(event.ground).jump);
+        }
+    }
+    for event in landed_events.iter() {
+        play(&surface(event.ground).land);
+        if let Some(tracker) = trackers.get_mut(&event.entity) {
+            tracker.walked = 0.0;
+        }
+    }
+    for event in sliding_events.iter() {
+        if event.sliding {
+            if let Ok((_, controller, _)) = characters.get(event.entity) {
+                play(&surface(controller.last_ground).slide);
+            }
+        }
+    }
+
+    trackers.retain(|entity, _| characters.contains(*entity));
+
+    for (entity, controller, settings) in characters.iter() {
+        let tracker = trackers
+            .entry(entity)
+            .or_insert_with(|| SoundTracker { walked: 0.0 });
+
+        // Only count walking relative to the ground, so riding a platform doesn't make footsteps.
+        if controller.is_grounded && !controller.sliding {
+            let velocity = controller.ground_relative_velocity;
+            let speed = velocity.reject_from(settings.up_vector).length();
+            tracker.walked += speed * time.delta_seconds();
+            if tracker.walked >= sounds.stride {
+                play(&surface(controller.last_ground).footsteps);
+                tracker.walked %= sounds.stride;
+            }
+        }
+    }
+}
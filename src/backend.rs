@@ -0,0 +1,521 @@
+use crate::components::ControllerBody;
+use crate::diagnostics::{count, Counter};
+use bevy::{ecs::schedule::StageLabelId, prelude::*};
+use bevy_rapier3d::{
+    plugin::PhysicsStages,
+    prelude::*,
+    rapier::{dynamics::RigidBodyHandle, math::Vector},
+};
+use std::sync::Arc;
+
+/// The physics engine the character controllers run on. [`WanderlustPlugin`](crate::WanderlustPlugin) and every system it
+/// adds are generic over it, and reach the engine's world, configuration and components only through it, so another
+/// engine can be plugged in by implementing it and adding `WanderlustPlugin::<MyBackend>::new()`.
+///
+/// Shapes are described with `bevy_rapier3d`'s [`Collider`], which the settings use, such as
+/// [`GroundCastSettings::collider`](crate::GroundCastSettings::collider), and are converted by the backend as needed.
+/// [`RapierBackend`] is the default, and the only one built in.
+pub trait PhysicsBackend: Send + Sync + 'static {
+    /// The resource holding the physics world to query.
+    type Context: Send + Sync + 'static;
+    /// The resource holding the physics settings, such as the timestep.
+    type Config: Send + Sync + 'static;
+    /// The resource tracking how far rendering is ahead of the simulation, see [`PhysicsBackend::render_offset`].
+    type RenderTime: Send + Sync + 'static;
+    /// The component giving an entity its collider.
+    type Collider: Component;
+    /// The component making an entity a rigid body, and setting what kind.
+    type Body: Component;
+    /// The component holding a body's velocity.
+    type Velocity: Component;
+    /// The component that impulses are applied to a body through.
+    type Impulse: Component;
+    /// The component that makes a collider detect overlaps without colliding.
+    type Sensor: Component + Default;
+    /// The component putting a body to sleep.
+    type Sleeping: Component;
+    /// The component that makes a body's transform be interpolated between physics steps.
+    type Interpolation: Component;
+
+    /// Casts `shape` from `position` along `direction` for up to `max_distance`, returning the first collider hit for which
+    /// `filter` returns `true`. Sensors are never hit.
+    #[allow(clippy::too_many_arguments)]
+    fn cast_shape(
+        ctx: &Self::Context,
+        position: Vec3,
+        rotation: Quat,
+        direction: Vec3,
+        shape: &Collider,
+        max_distance: f32,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> Option<ShapeHit>;
+
+    /// Casts a ray from `origin` along `direction` for up to `max_distance`, returning the first collider hit for which
+    /// `filter` returns `true`. A `solid` ray starting inside a collider hits it right away, at a distance of zero, while
+    /// other rays hit its boundary from the inside. Sensors are never hit.
+    fn cast_ray(
+        ctx: &Self::Context,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        solid: bool,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> Option<RayHit>;
+
+    /// Whether `shape` at `position` overlaps any collider for which `filter` returns `true`. Sensors are ignored.
+    fn overlaps(
+        ctx: &Self::Context,
+        position: Vec3,
+        rotation: Quat,
+        shape: &Collider,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> bool;
+
+    /// Calls `callback` with every collider containing `point` for which `filter` returns `true`, sensors included, until
+    /// it returns `false`.
+    fn intersections_with_point(
+        ctx: &Self::Context,
+        point: Vec3,
+        filter: &dyn Fn(Entity) -> bool,
+        callback: &mut dyn FnMut(Entity) -> bool,
+    );
+
+    /// Calls `callback` with every collider overlapping `shape` at `position` for which `filter` returns `true`, sensors
+    /// included, until it returns `false`.
+    fn intersections_with_shape(
+        ctx: &Self::Context,
+        position: Vec3,
+        rotation: Quat,
+        shape: &Collider,
+        filter: &dyn Fn(Entity) -> bool,
+        callback: &mut dyn FnMut(Entity) -> bool,
+    );
+
+    /// Collects the colliders `entity` is touching into `contacts`, sorted.
+    fn touching(ctx: &Self::Context, entity: Entity, contacts: &mut Vec<Entity>);
+
+    /// Collects the points where `entity` touches other colliders into `contacts`.
+    fn contacts(ctx: &Self::Context, entity: Entity, contacts: &mut Vec<ContactPoint>);
+
+    /// The pose of the collider of `entity` in the physics world.
+    fn collider_pose(ctx: &Self::Context, entity: Entity) -> Option<(Vec3, Quat)>;
+
+    /// The pose of the body of `entity` as of the last physics step.
+    fn body_pose(ctx: &Self::Context, entity: Entity) -> Option<(Vec3, Quat)>;
+
+    /// The center of mass of the body the collider of `entity` is attached to, which the body turns around.
+    fn center_of_mass(ctx: &Self::Context, entity: Entity) -> Option<Vec3>;
+
+    /// The entity of the body the collider of `entity` is attached to.
+    fn body_entity(ctx: &Self::Context, entity: Entity) -> Option<Entity>;
+
+    /// The mass of the body the collider of `entity` is attached to.
+    fn mass(ctx: &Self::Context, entity: Entity) -> Option<f32>;
+
+    /// The stage where changes to transforms are sent to the physics world, which
+    /// [`WanderlustStage::AfterPhysicsSync`](crate::WanderlustStage::AfterPhysicsSync) runs right after.
+    fn sync_stage() -> StageLabelId;
+
+    /// Brings the physics world's queries up to date with where its bodies are now.
+    fn sync_queries(ctx: &mut Self::Context);
+
+    /// Moves every body and collider in the physics world by `offset`, and brings its queries up to date.
+    fn shift_origin(ctx: &mut Self::Context, offset: Vec3);
+
+    /// Tweaks the physics settings to make the character controller behave better, see [`WanderlustPhysicsTweaks`](crate::WanderlustPhysicsTweaks).
+    fn tweak(ctx: &mut Self::Context);
+
+    /// How much time the physics simulation moves forward this frame, see [`ControllerSettings::clock`](crate::ControllerSettings::clock).
+    fn simulated_seconds(config: &Self::Config, time: &Time) -> f32;
+
+    /// The length of a physics step, if bodies with [`PhysicsBackend::Interpolation`] are interpolated between steps.
+    fn interpolation_step(config: &Self::Config) -> Option<f32>;
+
+    /// How far rendering is ahead of the simulation: each frame's time is added to it, and the length of each physics step
+    /// taken off, so that it is between minus one step and zero while interpolating.
+    fn render_offset(time: &Self::RenderTime) -> f32;
+
+    /// The shape of a collider.
+    fn collider_shape(collider: &Self::Collider) -> Collider;
+
+    /// Changes the shape of a collider.
+    fn set_collider_shape(collider: &mut Self::Collider, shape: Collider);
+
+    /// The kind of body that moves a character the way `body` says.
+    fn body(body: ControllerBody) -> Self::Body;
+
+    /// Whether `body` is moved by the simulation.
+    fn is_dynamic(body: &Self::Body) -> bool;
+
+    /// Whether `body` never moves.
+    fn is_fixed(body: &Self::Body) -> bool;
+
+    /// The linear velocity of a body.
+    fn linear_velocity(velocity: &Self::Velocity) -> Vec3;
+
+    /// The angular velocity of a body.
+    fn angular_velocity(velocity: &Self::Velocity) -> Vec3;
+
+    /// Sets the `linear` and `angular` velocity of a body.
+    fn set_velocity(velocity: &mut Self::Velocity, linear: Vec3, angular: Vec3);
+
+    /// An impulse of `linear` and `angular`, for a body that doesn't have one yet.
+    fn impulse(linear: Vec3, angular: Vec3) -> Self::Impulse;
+
+    /// Applies `linear` and `angular` impulses to a body this frame, replacing the ones applied last frame.
+    fn apply_impulse(impulse: &mut Self::Impulse, linear: Vec3, angular: Vec3);
+
+    /// Adds `linear` and `angular` impulses to the ones applied to a body this frame.
+    fn add_impulse(impulse: &mut Self::Impulse, linear: Vec3, angular: Vec3);
+
+    /// The component putting a body that doesn't have one yet to sleep, or keeping it awake.
+    fn sleeping(asleep: bool) -> Self::Sleeping;
+
+    /// Puts a body that can sleep to sleep, or wakes it up.
+    fn set_sleeping(sleeping: &mut Self::Sleeping, asleep: bool);
+}
+
+/// A collider hit by [`PhysicsBackend::cast_shape`].
+#[derive(Clone, Copy, Debug)]
+pub struct ShapeHit {
+    /// The entity of the collider that was hit.
+    pub entity: Entity,
+    /// How far the shape travelled before hitting it.
+    pub distance: f32,
+    /// Where the shape touched it.
+    pub point: Vec3,
+    /// The normal of its surface where the shape touched it.
+    pub normal: Vec3,
+    /// Whether the shape already overlapped it at the start of the cast.
+    pub penetrating: bool,
+}
+
+/// A collider hit by [`PhysicsBackend::cast_ray`].
+#[derive(Clone, Copy, Debug)]
+pub struct RayHit {
+    /// The entity of the collider that was hit.
+    pub entity: Entity,
+    /// How far the ray travelled before hitting it.
+    pub distance: f32,
+    /// Where the ray hit it.
+    pub point: Vec3,
+    /// The normal of its surface where the ray hit it.
+    pub normal: Vec3,
+}
+
+/// A point where two colliders touch, found by [`PhysicsBackend::contacts`].
+#[derive(Clone, Copy, Debug)]
+pub struct ContactPoint {
+    /// The entity of the other collider.
+    pub entity: Entity,
+    /// Where they touch.
+    pub point: Vec3,
+    /// The normal of the other collider's surface, pointing away from it.
+    pub normal: Vec3,
+    /// How deep the colliders overlap, negative if they are still apart.
+    pub depth: f32,
+}
+
+/// Whether `a` and `b` are known to be the same shape. May return `false` for equal shapes that were built separately.
+pub(crate) fn same_shape(a: &Collider, b: &Collider) -> bool {
+    // Clones of a collider share its shape.
+    std::ptr::addr_eq(Arc::as_ptr(&a.raw.0), Arc::as_ptr(&b.raw.0))
+}
+
+/// The [`PhysicsBackend`] for `bevy_rapier3d`.
+pub struct RapierBackend;
+
+impl PhysicsBackend for RapierBackend {
+    type Context = RapierContext;
+    type Config = RapierConfiguration;
+    type RenderTime = SimulationToRenderTime;
+    type Collider = Collider;
+    type Body = RigidBody;
+    type Velocity = Velocity;
+    type Impulse = ExternalImpulse;
+    type Sensor = Sensor;
+    type Sleeping = Sleeping;
+    type Interpolation = TransformInterpolation;
+
+    fn cast_shape(
+        ctx: &RapierContext,
+        position: Vec3,
+        rotation: Quat,
+        direction: Vec3,
+        shape: &Collider,
+        max_distance: f32,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> Option<ShapeHit> {
+        count(Counter::ShapeCasts, 1);
+        let predicate = |entity| filter(entity);
+        let filter = QueryFilter::new().predicate(&predicate).exclude_sensors();
+        ctx.cast_shape(position, rotation, direction, shape, max_distance, filter)
+            .map(|(entity, toi)| ShapeHit {
+                entity,
+                distance: toi.toi,
+                point: toi.witness1,
+                normal: toi.normal1,
+                penetrating: toi.status == TOIStatus::Penetrating,
+            })
+    }
+
+    fn cast_ray(
+        ctx: &RapierContext,
+        origin: Vec3,
+        direction: Vec3,
+        max_distance: f32,
+        solid: bool,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> Option<RayHit> {
+        count(Counter::Raycasts, 1);
+        let predicate = |entity| filter(entity);
+        let filter = QueryFilter::new().predicate(&predicate).exclude_sensors();
+        ctx.cast_ray_and_get_normal(origin, direction, max_distance, solid, filter)
+            .map(|(entity, hit)| RayHit {
+                entity,
+                distance: hit.toi,
+                point: hit.point,
+                normal: hit.normal,
+            })
+    }
+
+    fn overlaps(
+        ctx: &RapierContext,
+        position: Vec3,
+        rotation: Quat,
+        shape: &Collider,
+        filter: &dyn Fn(Entity) -> bool,
+    ) -> bool {
+        let predicate = |entity| filter(entity);
+        let filter = QueryFilter::new().predicate(&predicate).exclude_sensors();
+        ctx.intersection_with_shape(position, rotation, shape, filter)
+            .is_some()
+    }
+
+    fn intersections_with_point(
+        ctx: &RapierContext,
+        point: Vec3,
+        filter: &dyn Fn(Entity) -> bool,
+        callback: &mut dyn FnMut(Entity) -> bool,
+    ) {
+        let predicate = |entity| filter(entity);
+        ctx.intersections_with_point(point, QueryFilter::new().predicate(&predicate), callback);
+    }
+
+    fn intersections_with_shape(
+        ctx: &RapierContext,
+        position: Vec3,
+        rotation: Quat,
+        shape: &Collider,
+        filter: &dyn Fn(Entity) -> bool,
+        callback: &mut dyn FnMut(Entity) -> bool,
+    ) {
+        let predicate = |entity| filter(entity);
+        ctx.intersections_with_shape(
+            position,
+            rotation,
+            shape,
+            QueryFilter::new().predicate(&predicate),
+            callback,
+        );
+    }
+
+    fn touching(ctx: &RapierContext, entity: Entity, contacts: &mut Vec<Entity>) {
+        contacts.clear();
+        contacts.extend(
+            ctx.contacts_with(entity)
+                .filter(|pair| pair.has_any_active_contacts())
+                .map(|pair| {
+                    if pair.collider1() == entity {
+                        pair.collider2()
+                    } else {
+                        pair.collider1()
+                    }
+                }),
+        );
+        contacts.sort();
+    }
+
+    fn contacts(ctx: &RapierContext, entity: Entity, contacts: &mut Vec<ContactPoint>) {
+        contacts.clear();
+        for pair in ctx.contacts_with(entity) {
+            if !pair.has_any_active_contacts() {
+                continue;
+            }
+            // Rapier's normals point from the first collider to the second.
+            let (other, flip) = if pair.collider1() == entity {
+                (pair.collider2(), -1.0)
+            } else {
+                (pair.collider1(), 1.0)
+            };
+            for manifold in pair.manifolds() {
+                let normal = manifold.normal() * flip;
+                contacts.extend(manifold.solver_contacts().map(|contact| ContactPoint {
+                    entity: other,
+                    point: contact.point(),
+                    normal,
+                    depth: -contact.dist(),
+                }));
+            }
+        }
+    }
+
+    fn collider_pose(ctx: &RapierContext, entity: Entity) -> Option<(Vec3, Quat)> {
+        let collider = ctx.colliders.get(*ctx.entity2collider().get(&entity)?)?;
+        let position = collider.position();
+        Some((position.translation.vector.into(), position.rotation.into()))
+    }
+
+    fn body_pose(ctx: &RapierContext, entity: Entity) -> Option<(Vec3, Quat)> {
+        let body = ctx.bodies.get(*ctx.entity2body().get(&entity)?)?;
+        let position = body.position();
+        Some((position.translation.vector.into(), position.rotation.into()))
+    }
+
+    fn center_of_mass(ctx: &RapierContext, entity: Entity) -> Option<Vec3> {
+        let body = ctx.bodies.get(rapier_body(ctx, entity)?)?;
+        Some(
+            body.mass_properties()
+                .world_com(body.position())
+                .coords
+                .into(),
+        )
+    }
+
+    fn body_entity(ctx: &RapierContext, entity: Entity) -> Option<Entity> {
+        ctx.rigid_body_entity(rapier_body(ctx, entity)?)
+    }
+
+    fn mass(ctx: &RapierContext, entity: Entity) -> Option<f32> {
+        Some(ctx.bodies.get(rapier_body(ctx, entity)?)?.mass())
+    }
+
+    fn sync_stage() -> StageLabelId {
+        PhysicsStages::SyncBackend.as_label()
+    }
+
+    fn sync_queries(ctx: &mut RapierContext) {
+        ctx.propagate_modified_body_positions_to_colliders();
+        ctx.update_query_pipeline();
+    }
+
+    fn shift_origin(ctx: &mut RapierContext, offset: Vec3) {
+        let offset = Vector::from(offset);
+        for (_, body) in ctx.bodies.iter_mut() {
+            let translation = body.translation() + offset;
+            body.set_translation(translation, false);
+        }
+        for (_, collider) in ctx.colliders.iter_mut() {
+            if collider.parent().is_none() {
+                let translation = collider.translation() + offset;
+                collider.set_translation(translation);
+            }
+        }
+        Self::sync_queries(ctx);
+    }
+
+    fn tweak(ctx: &mut RapierContext) {
+        let params = &mut ctx.integration_parameters;
+        // This prevents any noticeable jitter when running facefirst into a wall.
+        params.erp = 0.99;
+        // This prevents (most) noticeable jitter when running facefirst into an inverted corner.
+        params.max_velocity_iterations = 16;
+        // TODO: Fix jitter that occurs when running facefirst into a normal corner.
+    }
+
+    fn simulated_seconds(config: &RapierConfiguration, time: &Time) -> f32 {
+        match config.timestep_mode {
+            TimestepMode::Fixed { dt, .. } => dt,
+            TimestepMode::Variable {
+                max_dt, time_scale, ..
+            } => (time.delta_seconds() * time_scale).min(max_dt),
+            TimestepMode::Interpolated { time_scale, .. } => time.delta_seconds() * time_scale,
+        }
+    }
+
+    fn interpolation_step(config: &RapierConfiguration) -> Option<f32> {
+        match config.timestep_mode {
+            TimestepMode::Interpolated { dt, .. } => Some(dt),
+            _ => None,
+        }
+    }
+
+    fn render_offset(time: &SimulationToRenderTime) -> f32 {
+        time.diff
+    }
+
+    fn collider_shape(collider: &Collider) -> Collider {
+        collider.clone()
+    }
+
+    fn set_collider_shape(collider: &mut Collider, shape: Collider) {
+        *collider = shape;
+    }
+
+    fn body(body: ControllerBody) -> RigidBody {
+        match body {
+            ControllerBody::Dynamic => RigidBody::Dynamic,
+            ControllerBody::Kinematic => RigidBody::KinematicPositionBased,
+        }
+    }
+
+    fn is_dynamic(body: &RigidBody) -> bool {
+        *body == RigidBody::Dynamic
+    }
+
+    fn is_fixed(body: &RigidBody) -> bool {
+        *body == RigidBody::Fixed
+    }
+
+    fn linear_velocity(velocity: &Velocity) -> Vec3 {
+        velocity.linvel
+    }
+
+    fn angular_velocity(velocity: &Velocity) -> Vec3 {
+        velocity.angvel
+    }
+
+    fn set_velocity(velocity: &mut Velocity, linear: Vec3, angular: Vec3) {
+        velocity.linvel = linear;
+        velocity.angvel = angular;
+    }
+
+    fn impulse(linear: Vec3, angular: Vec3) -> ExternalImpulse {
+        ExternalImpulse {
+            impulse: linear,
+            torque_impulse: angular,
+        }
+    }
+
+    fn apply_impulse(impulse: &mut ExternalImpulse, linear: Vec3, angular: Vec3) {
+        impulse.impulse = linear;
+        impulse.torque_impulse = angular;
+    }
+
+    fn add_impulse(impulse: &mut ExternalImpulse, linear: Vec3, angular: Vec3) {
+        impulse.impulse += linear;
+        impulse.torque_impulse += angular;
+    }
+
+    fn sleeping(asleep: bool) -> Sleeping {
+        Sleeping {
+            sleeping: asleep,
+            ..default()
+        }
+    }
+
+    fn set_sleeping(sleeping: &mut Sleeping, asleep: bool) {
+        sleeping.sleeping = asleep;
+    }
+}
+
+/// The handle of the body `entity` is, or that its collider is attached to.
+fn rapier_body(ctx: &RapierContext, entity: Entity) -> Option<RigidBodyHandle> {
+    match ctx.entity2body().get(&entity) {
+        Some(body) => Some(*body),
+        None => ctx
+            .colliders
+            .get(*ctx.entity2collider().get(&entity)?)?
+            .parent(),
+    }
+}
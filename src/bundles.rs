@@ -1,4 +1,7 @@
-use crate::{ControllerInput, ControllerSettings, ControllerState};
+use crate::{
+    ControllerForces, ControllerFrame, ControllerInput, ControllerSettings, ControllerState,
+    GroundCastInfo,
+};
 
 use bevy::{math::*, prelude::*};
 use bevy_rapier3d::prelude::*;
@@ -28,6 +31,8 @@ pub struct ControllerPhysicsBundle {
     pub damping: Damping,
     /// See [`Restitution`].
     pub restitution: Restitution,
+    /// See [`TransformInterpolation`]. Only has an effect with [`TimestepMode::Interpolated`].
+    pub interpolation: TransformInterpolation,
 }
 
 impl Default for ControllerPhysicsBundle {
@@ -53,6 +58,7 @@ impl Default for ControllerPhysicsBundle {
                 coefficient: 0.0,
                 combine_rule: CoefficientCombineRule::Min,
             },
+            interpolation: default(),
         }
     }
 }
@@ -68,6 +74,12 @@ pub struct CharacterControllerBundle {
     pub settings: ControllerSettings,
     /// See [`ControllerInput`].
     pub input: ControllerInput,
+    /// See [`GroundCastInfo`].
+    pub ground_cast: GroundCastInfo,
+    /// See [`ControllerFrame`].
+    pub frame: ControllerFrame,
+    /// See [`ControllerForces`].
+    pub forces: ControllerForces,
     /// See [`ControllerPhysicsBundle`]
     #[bundle]
     pub physics: ControllerPhysicsBundle,
@@ -76,8 +88,10 @@ pub struct CharacterControllerBundle {
     /// See [`GlobalTransform`]
     pub global_transform: GlobalTransform,
     /// See [`Visibility`]
+    #[cfg(feature = "render")]
     pub visibility: Visibility,
     /// See [`ComputedVisibility`]
+    #[cfg(feature = "render")]
     pub computed_visibility: ComputedVisibility,
 }
 
@@ -87,10 +101,15 @@ impl Default for CharacterControllerBundle {
             controller: default(),
             settings: ControllerSettings::character(),
             input: default(),
+            ground_cast: default(),
+            frame: default(),
+            forces: default(),
             physics: default(),
             transform: default(),
             global_transform: default(),
+            #[cfg(feature = "render")]
             visibility: default(),
+            #[cfg(feature = "render")]
             computed_visibility: default(),
         }
     }
@@ -105,6 +124,12 @@ pub struct StarshipControllerBundle {
     pub settings: ControllerSettings,
     /// See [`ControllerInput`].
     pub input: ControllerInput,
+    /// See [`GroundCastInfo`].
+    pub ground_cast: GroundCastInfo,
+    /// See [`ControllerFrame`].
+    pub frame: ControllerFrame,
+    /// See [`ControllerForces`].
+    pub forces: ControllerForces,
     /// See [`ControllerPhysicsBundle`].
     #[bundle]
     pub physics: ControllerPhysicsBundle,
@@ -113,8 +138,10 @@ pub struct StarshipControllerBundle {
     /// See [`GlobalTransform`]
     pub global_transform: GlobalTransform,
     /// See [`Visibility`]
+    #[cfg(feature = "render")]
     pub visibility: Visibility,
     /// See [`ComputedVisibility`]
+    #[cfg(feature = "render")]
     pub computed_visibility: ComputedVisibility,
 }
 
@@ -124,10 +151,15 @@ impl Default for StarshipControllerBundle {
             controller: default(),
             settings: ControllerSettings::starship(),
             input: default(),
+            ground_cast: default(),
+            frame: default(),
+            forces: default(),
             physics: default(),
             transform: default(),
             global_transform: default(),
+            #[cfg(feature = "render")]
             visibility: default(),
+            #[cfg(feature = "render")]
             computed_visibility: default(),
         }
     }
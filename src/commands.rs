@@ -0,0 +1,314 @@
+use crate::backend::{PhysicsBackend, RapierBackend, ShapeHit};
+use crate::components::{
+    AimTransform, BoundsShape, ContactTransform, ControllerBounds, ControllerCommand,
+    ControllerCommands, ControllerFrame, ControllerInput, ControllerSettings, ControllerState,
+    FootPlacement, GrappleState, GroundCastInfo, InteractionRay, LandingPrediction, LedgeHang,
+    LedgeHangState, PathFollow, PatrolRoute, RenderInterpolation, Vault, WallContact,
+};
+use bevy::ecs::system::{Command, EntityCommands};
+use bevy::ecs::world::EntityMut;
+use bevy::prelude::*;
+
+/// The parts of [`TeleportController`] and [`ShiftControllerOrigin`] that depend on the physics engine. Inserted by
+/// [`WanderlustPlugin`](crate::WanderlustPlugin) for its [`PhysicsBackend`], and [`RapierBackend`]'s are used without it.
+#[derive(Clone, Copy)]
+pub(crate) struct BackendCommands {
+    /// Stops an entity's body.
+    stop: fn(&mut EntityMut),
+    /// Moves everything in the physics world by an offset.
+    shift_origin: fn(&mut World, Vec3),
+}
+
+impl BackendCommands {
+    pub(crate) fn new<B: PhysicsBackend>() -> Self {
+        Self {
+            stop: |entity| {
+                if let Some(mut velocity) = entity.get_mut::<B::Velocity>() {
+                    B::set_velocity(&mut velocity, Vec3::ZERO, Vec3::ZERO);
+                }
+            },
+            shift_origin: |world, offset| {
+                if let Some(mut ctx) = world.get_resource_mut::<B::Context>() {
+                    B::shift_origin(&mut ctx, offset);
+                }
+            },
+        }
+    }
+
+    fn get(world: &World) -> Self {
+        world
+            .get_resource::<BackendCommands>()
+            .copied()
+            .unwrap_or_else(BackendCommands::new::<RapierBackend>)
+    }
+}
+
+/// A [`Command`] which moves a character controller to `position` instantly, stopping it and resetting its
+/// [`ControllerState`] so no jump, coyote time, etc. carries over from where it was.
+///
+/// `position` is relative to the character's parent, if it has one.
+pub struct TeleportController {
+    /// The character to move.
+    pub entity: Entity,
+    /// Where to move the character to.
+    pub position: Vec3,
+}
+
+impl Command for TeleportController {
+    fn write(self, world: &mut World) {
+        let backend = BackendCommands::get(world);
+        let mut entity = match world.get_entity_mut(self.entity) {
+            Some(entity) => entity,
+            None => {
+                warn!("Cannot teleport {:?}, which does not exist", self.entity);
+                return;
+            }
+        };
+
+        if let Some(mut tf) = entity.get_mut::<Transform>() {
+            tf.translation = self.position;
+        }
+        (backend.stop)(&mut entity);
+        if let Some(mut state) = entity.get_mut::<ControllerState>() {
+            // The parent is still in place, so keep track of it to be unparented later.
+            *state = ControllerState {
+                carry_parent: state.carry_parent,
+                body: state.body,
+                ..default()
+            };
+        }
+    }
+}
+
+/// Extension methods on [`EntityCommands`] for moving character controllers.
+pub trait TeleportEntityCommandsExt {
+    /// Move this character to `position` instantly. See [`TeleportController`].
+    fn teleport(&mut self, position: Vec3) -> &mut Self;
+}
+
+impl TeleportEntityCommandsExt for EntityCommands<'_, '_, '_> {
+    fn teleport(&mut self, position: Vec3) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(TeleportController { entity, position });
+        self
+    }
+}
+
+/// Implemented by the components that keep track of world-space positions, so that floating origin systems can move them
+/// along with the rest of the world. See [`ShiftControllerOrigin`] for shifting every character at once.
+pub trait ShiftOrigin {
+    /// Move every world-space position kept track of by `offset`.
+    fn shift_origin(&mut self, offset: Vec3);
+}
+
+/// A [`Command`] which moves every world-space position that character controllers keep track of by `offset`, such as
+/// where they last touched the ground, the ledges they hang from and the paths they follow, for floating origin systems
+/// that move the whole world back towards the origin.
+///
+/// The characters' own transforms are not moved, since they should be moved along with all the others. Queue this from
+/// the same system that moves them. The physics engine's bodies and colliders are moved right away as well, see
+/// [`PhysicsBackend::shift_origin`], so the controller never sees the world and its own state out of step, whatever stage
+/// it runs in. Rapier sets their positions from the moved transforms again on its next sync rather than moving them by as
+/// much, so they are not moved twice.
+pub struct ShiftControllerOrigin {
+    /// How far the world moved.
+    pub offset: Vec3,
+}
+
+impl Command for ShiftControllerOrigin {
+    fn write(self, world: &mut World) {
+        fn shift<T: Component + ShiftOrigin>(world: &mut World, offset: Vec3) {
+            for mut component in world.query::<&mut T>().iter_mut(world) {
+                component.shift_origin(offset);
+            }
+        }
+
+        shift::<ControllerState>(world, self.offset);
+        shift::<ControllerFrame>(world, self.offset);
+        shift::<ControllerInput>(world, self.offset);
+        shift::<ControllerCommands>(world, self.offset);
+        shift::<GroundCastInfo>(world, self.offset);
+        shift::<LandingPrediction>(world, self.offset);
+        shift::<FootPlacement>(world, self.offset);
+        shift::<AimTransform>(world, self.offset);
+        shift::<InteractionRay>(world, self.offset);
+        shift::<WallContact>(world, self.offset);
+        shift::<PathFollow>(world, self.offset);
+        shift::<PatrolRoute>(world, self.offset);
+        shift::<GrappleState>(world, self.offset);
+        shift::<Vault>(world, self.offset);
+        shift::<LedgeHang>(world, self.offset);
+        shift::<RenderInterpolation>(world, self.offset);
+
+        (BackendCommands::get(world).shift_origin)(world, self.offset);
+
+        // Heights are measured along each character's own up vector.
+        for (mut bounds, settings) in world
+            .query::<(&mut ControllerBounds, &ControllerSettings)>()
+            .iter_mut(world)
+        {
+            match &mut bounds.shape {
+                BoundsShape::Height { min, max } => {
+                    *min += self.offset.dot(settings.up_vector);
+                    *max += self.offset.dot(settings.up_vector);
+                }
+                BoundsShape::Aabb { min, max } => {
+                    *min += self.offset;
+                    *max += self.offset;
+                }
+            }
+            if let Some(respawn) = &mut bounds.respawn {
+                *respawn += self.offset;
+            }
+        }
+    }
+}
+
+impl ShiftOrigin for ContactTransform {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.position += offset;
+    }
+}
+
+impl ShiftOrigin for ShapeHit {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.point += offset;
+    }
+}
+
+impl ShiftOrigin for ControllerState {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.last_ground_contact.shift_origin(offset);
+        self.swim_surface.shift_origin(offset);
+        for position in &mut self.safe_positions {
+            *position += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for ControllerFrame {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.position += offset;
+        for hit in self.ground.iter_mut().chain(&mut self.steep_slope) {
+            hit.shift_origin(offset);
+        }
+    }
+}
+
+impl ShiftOrigin for ControllerInput {
+    fn shift_origin(&mut self, offset: Vec3) {
+        if let Some(target) = &mut self.look_at {
+            *target += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for ControllerCommands {
+    fn shift_origin(&mut self, offset: Vec3) {
+        for command in &mut self.queue {
+            match command {
+                ControllerCommand::MoveToward { target, .. }
+                | ControllerCommand::JumpTo { target, .. } => *target += offset,
+                _ => {}
+            }
+        }
+        if let Some(arc) = &mut self.jump_arc {
+            arc.start += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for GroundCastInfo {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.point += offset;
+    }
+}
+
+impl ShiftOrigin for LandingPrediction {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.position += offset;
+    }
+}
+
+impl ShiftOrigin for FootPlacement {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.left.position += offset;
+        self.right.position += offset;
+    }
+}
+
+impl ShiftOrigin for AimTransform {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.transform.translation += offset;
+    }
+}
+
+impl ShiftOrigin for InteractionRay {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.point += offset;
+    }
+}
+
+impl ShiftOrigin for WallContact {
+    fn shift_origin(&mut self, offset: Vec3) {
+        self.point += offset;
+    }
+}
+
+impl ShiftOrigin for PathFollow {
+    fn shift_origin(&mut self, offset: Vec3) {
+        for waypoint in &mut self.waypoints {
+            *waypoint += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for PatrolRoute {
+    fn shift_origin(&mut self, offset: Vec3) {
+        for waypoint in &mut self.waypoints {
+            *waypoint += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for GrappleState {
+    fn shift_origin(&mut self, offset: Vec3) {
+        if let Some(anchor) = &mut self.anchor {
+            *anchor += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for Vault {
+    fn shift_origin(&mut self, offset: Vec3) {
+        if let Some(arc) = &mut self.arc {
+            arc.start += offset;
+            arc.end += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for LedgeHang {
+    fn shift_origin(&mut self, offset: Vec3) {
+        match &mut self.state {
+            LedgeHangState::Free => {}
+            LedgeHangState::Hanging(ledge) => ledge.anchor += offset,
+            LedgeHangState::Climbing(path) => {
+                path.start += offset;
+                path.end += offset;
+            }
+        }
+        if let Some(ledge) = &mut self.detected {
+            ledge.anchor += offset;
+        }
+    }
+}
+
+impl ShiftOrigin for RenderInterpolation {
+    fn shift_origin(&mut self, offset: Vec3) {
+        if let Some((before, after)) = &mut self.steps {
+            before.translation += offset;
+            after.translation += offset;
+        }
+    }
+}
@@ -1,11 +1,14 @@
-use bevy::{math::*, prelude::*};
+use bevy::{math::*, prelude::*, reflect::FromReflect, utils::HashMap};
 use bevy_rapier3d::prelude::*;
+use std::collections::VecDeque;
 
+use crate::backend::{PhysicsBackend, ShapeHit};
 use crate::{CharacterControllerPreset, StarshipControllerPreset};
 
 /// The character controller's state.
 /// This is the component responsible for adding controls to an entity.
-/// Requires [`ControllerSettings`], [`ControllerInput`], [`GlobalTransform`], and [`ExternalImpulse`](bevy_rapier3d::prelude::ExternalImpulse) to work.
+/// Requires [`ControllerSettings`], [`ControllerInput`], [`ControllerFrame`], [`ControllerForces`], [`GlobalTransform`], and
+/// [`ExternalImpulse`](bevy_rapier3d::prelude::ExternalImpulse) to work.
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct ControllerState {
@@ -13,27 +16,447 @@ pub struct ControllerState {
     /// The goal velocity represents the input after being directly translated to a desired final motion.
     /// This field represents the goal velocity that was calculated last frame.
     pub last_goal_velocity: Vec3,
-    /// A timer to track how long to skip the ground check for (see [`jump_skip_ground_check_duration`](ControllerSettings::jump_skip_ground_check_duration)).
-    pub skip_ground_check_timer: f32,
+    /// A timer to track how long to skip the ground check for (see [`skip_ground_check_duration`](JumpSettings::skip_ground_check_duration)).
+    pub(crate) skip_ground_check_timer: Countdown,
     /// A timer to track how long to jump for.
-    pub jump_timer: f32,
+    pub(crate) jump_timer: Countdown,
     /// Was [`ControllerInput::jumping`] pressed last frame.
     pub jump_pressed_last_frame: bool,
-    /// A timer to track coyote time. See [`coyote_time_duration`](ControllerSettings::coyote_time_duration)
-    pub coyote_timer: f32,
-    /// A timer to track jump buffering. See [`jump_buffer_duration`](ControllerSettings::jump_buffer_duration)
-    pub jump_buffer_timer: f32,
+    /// A timer to track coyote time. See [`coyote_time_duration`](JumpSettings::coyote_time_duration)
+    pub(crate) coyote_timer: Countdown,
+    /// A timer to track jump buffering. See [`buffer_duration`](JumpSettings::buffer_duration)
+    pub(crate) jump_buffer_timer: Countdown,
     /// How many extra jumps are remaining
     pub remaining_jumps: u32,
+    /// Was the character grounded at the end of last frame.
+    pub is_grounded: bool,
+    /// The ground entity the character was standing on last frame, if any.
+    pub last_ground: Option<Entity>,
+    /// The velocity of [`last_ground`](ControllerState::last_ground) last frame.
+    /// Used to estimate the acceleration of moving platforms.
+    pub last_ground_velocity: Vec3,
+    /// The velocity of the ground along [`up_vector`](ControllerSettings::up_vector) this frame, such as that of an elevator.
+    /// Zero if not standing on anything.
+    pub ground_vertical_velocity: f32,
+    /// An estimate of the acceleration of the ground along [`up_vector`](ControllerSettings::up_vector), smoothed over the last few frames.
+    /// Zero if not standing on anything.
+    pub ground_vertical_acceleration: f32,
+    /// The character's velocity relative to the ground it stands on, where it stands on it, as of the last physics step.
+    /// Use this rather than the world velocity for animation speed, footsteps and effects, so that riding a platform,
+    /// boat or train doesn't look like running. The same as the world velocity while airborne, unless the character is
+    /// inside a [`VehicleInterior`].
+    pub ground_relative_velocity: Vec3,
+    /// The [`VehicleInterior`] the character is inside of, if any. Kept while the character is airborne inside it. Always
+    /// the body of the character's [`ReferenceFrame`], if it has one.
+    pub vehicle: Option<Entity>,
+    /// The velocity of [`vehicle`](ControllerState::vehicle) last frame, where the character was in it.
+    pub(crate) vehicle_velocity: Vec3,
+    /// The platform this character has been parented to by [`PlatformCarry::Parent`], if any.
+    pub carry_parent: Option<Entity>,
+    /// How long the character has been grounded for. While airborne, how long it was grounded before it left the ground.
+    pub grounded_time: f32,
+    /// How long the character has been airborne for. While grounded, how long its last time in the air lasted,
+    /// which is useful for scaling landing effects.
+    pub airborne_time: f32,
+    /// How long the character has been squeezed between colliders. See [`CrushSettings`].
+    pub crush_timer: f32,
+    /// Where the character last touched the ground, while it was grounded.
+    pub last_ground_contact: ContactTransform,
+    /// Recent places the character stood safely, newest first. See [`safe_position`](ControllerState::safe_position).
+    #[reflect(ignore)]
+    pub(crate) safe_positions: VecDeque<Vec3>,
+    /// Whether the character is sliding off of another character's head, see [`GroundCastSettings::stand_on_characters`],
+    /// or down a slope too steep to stand on, see [`GroundCastSettings::slope_slide_acceleration`].
+    pub sliding: bool,
+    /// How far the character is floating above (or, if negative, below) [`FloatSettings::distance`] while grounded,
+    /// i.e. how far the float spring has displaced it. Zero while airborne.
+    pub float_offset: f32,
+    /// Whether the character has broken its [`FacingMode::Locked`] to pivot towards its movement.
+    /// See [`FacingSettings::release_lock_angle`].
+    pub facing_lock_released: bool,
+    /// How far the character has fallen along [`up_vector`](ControllerSettings::up_vector) since it left the ground or reached
+    /// the apex of a jump. Still holds the distance of the fall on the frame the character lands, and is zero otherwise while grounded.
+    pub fall_distance: f32,
+    /// The velocity of the character while it is [`ControllerBody::Kinematic`], which its body doesn't keep track of.
+    pub kinematic_velocity: Velocity,
+    /// Whether the character is crouched. See [`CrouchSettings`].
+    pub crouched: bool,
+    /// Whether the character is sprinting. See [`SprintSettings`].
+    pub sprinting: bool,
+    /// Whether the character is skidding. See [`TractionSettings`].
+    pub skidding: bool,
+    pub(crate) skid_timer: Countdown,
+    /// Whether the character is turning in place. See [`FacingSettings::turn_in_place_angle`].
+    pub turning_in_place: bool,
+    pub(crate) turn_in_place_timer: Countdown,
+    /// Whether the character is sliding down a wall. See [`WallSettings::slide_speed`].
+    pub wall_sliding: bool,
+    /// Whether the character is gliding. See [`GlideSettings`].
+    pub gliding: bool,
+    /// The [`FluidVolume`] the character is swimming in, if any. See [`SwimSettings`].
+    pub swimming: Option<Entity>,
+    /// How much of the character is in a [`FluidVolume`], from 0.0 to 1.0, whether or not it's swimming. See
+    /// [`SwimSettings::height`].
+    pub submersion: f32,
+    /// How far below the surface of the fluid the top of the character is. Negative if it sticks out.
+    pub(crate) head_depth: f32,
+    /// The point on the surface of the fluid right above the character.
+    pub(crate) swim_surface: ContactTransform,
+    /// The [`LadderVolume`] the character is climbing, if any. See [`ClimbSettings`].
+    pub climbing: Option<Entity>,
+    /// The direction the ladder being climbed faces, in world space.
+    pub(crate) climb_normal: Vec3,
+    /// The direction up the ladder being climbed, in world space.
+    pub(crate) climb_axis: Vec3,
+    /// The [`ControllerSettings::body`] the character's [`RigidBody`] was last set up for. The rigidbody is only switched
+    /// when the setting changes, so user code can still switch it, e.g. to kinematic for a cutscene.
+    #[reflect(ignore)]
+    pub(crate) body: Option<ControllerBody>,
+    /// Whether the character's collider has been made a [`Sensor`] for [`FlySettings::noclip`].
+    pub(crate) noclip: bool,
+    /// How long the character's movement can't steer it towards or away from the wall it jumped off of. See
+    /// [`WallSettings::jump_lockout`].
+    pub(crate) wall_jump_lockout: Countdown,
+    /// The normal of the wall the character last jumped off of.
+    pub(crate) wall_jump_normal: Vec3,
+    /// Was [`ControllerInput::dashing`] pressed last frame.
+    pub dash_pressed_last_frame: bool,
+    /// A timer to track how long the current dash lasts for. See [`DashSettings::duration`].
+    pub(crate) dash_timer: Countdown,
+    /// A timer to track how long until the character can dash again. See [`DashSettings::cooldown`].
+    pub(crate) dash_cooldown: Countdown,
+    /// A timer to track dash buffering. See [`ActionSettings::buffer_durations`].
+    pub(crate) dash_buffer_timer: Countdown,
+    /// The direction of the current dash, perpendicular to [`up_vector`](ControllerSettings::up_vector).
+    pub(crate) dash_direction: Vec3,
+    /// A timer to track how long the character's movement is turned off for after a knockback. See
+    /// [`KnockbackEvent::lockout`](crate::KnockbackEvent::lockout).
+    pub(crate) knockback_lockout: Countdown,
+    /// The collider to restore once the character stands back up.
+    #[reflect(ignore)]
+    pub(crate) standing_collider: Option<Collider>,
+}
+
+impl ControllerState {
+    /// How long to skip the ground check for. See [`skip_ground_check_duration`](JumpSettings::skip_ground_check_duration).
+    pub fn skip_ground_check_timer(&self) -> Countdown {
+        self.skip_ground_check_timer
+    }
+
+    /// How long the current jump can still be held for. See [`time`](JumpSettings::time).
+    pub fn jump_timer(&self) -> Countdown {
+        self.jump_timer
+    }
+
+    /// How long the character can still jump after leaving the ground. See [`coyote_time_duration`](JumpSettings::coyote_time_duration).
+    pub fn coyote_timer(&self) -> Countdown {
+        self.coyote_timer
+    }
+
+    /// How long a jump pressed in the air stays buffered. See [`buffer_duration`](JumpSettings::buffer_duration).
+    pub fn jump_buffer_timer(&self) -> Countdown {
+        self.jump_buffer_timer
+    }
+
+    /// How long the current dash lasts for. See [`DashSettings::duration`].
+    pub fn dash_timer(&self) -> Countdown {
+        self.dash_timer
+    }
+
+    /// How long until the character can dash again. See [`DashSettings::cooldown`].
+    pub fn dash_cooldown(&self) -> Countdown {
+        self.dash_cooldown
+    }
+
+    /// How long a dash pressed while the character couldn't dash stays buffered. See [`ActionSettings::buffer_durations`].
+    pub fn dash_buffer_timer(&self) -> Countdown {
+        self.dash_buffer_timer
+    }
+
+    /// Where the character stood safely `back` places ago, 0 being the most recent, or the oldest place kept if there are
+    /// fewer. E.g. for respawning a character that fell into a pit a little way back from its edge. `None` if the
+    /// character hasn't stood anywhere safe since it was spawned or teleported.
+    ///
+    /// A place is safe if the character stood on static ground there without sliding. Places are the translation of the
+    /// character's transform, kept at least a unit apart, and only while it isn't carried by a parent, see
+    /// [`PlatformCarry::Parent`]. The last few are kept.
+    pub fn safe_position(&self, back: usize) -> Option<Vec3> {
+        self.safe_positions
+            .get(back)
+            .or_else(|| self.safe_positions.back())
+            .copied()
+    }
+
+    /// How long the character's movement stays turned off after a knockback. See
+    /// [`KnockbackEvent::lockout`](crate::KnockbackEvent::lockout).
+    pub fn knockback_lockout(&self) -> Countdown {
+        self.knockback_lockout
+    }
+}
+
+/// A timer counting down to zero, as used by [`ControllerState`].
+///
+/// Countdowns only advance while the controller runs, by the time of its [`ControllerClock`], so they stop while the game is
+/// paused (e.g. with Rapier's `time_scale` set to zero) and follow slow motion.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Countdown {
+    remaining: f32,
+}
+
+impl Countdown {
+    /// How much time is left, in seconds.
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+
+    /// Whether there is any time left.
+    pub fn is_running(&self) -> bool {
+        self.remaining > 0.0
+    }
+
+    /// Start counting down from `duration` seconds.
+    pub(crate) fn start(&mut self, duration: f32) {
+        self.remaining = duration;
+    }
+
+    /// Stop counting down, as if the time ran out.
+    pub(crate) fn stop(&mut self) {
+        self.remaining = 0.0;
+    }
+
+    /// Count down by `dt` seconds.
+    pub(crate) fn tick(&mut self, dt: f32) {
+        self.remaining = (self.remaining - dt).max(0.0);
+    }
 }
 
 /// The settings of a character controller. See each individual field for more description.
 ///
+/// Settings are grouped by the part of the controller they affect, so presets can override one group while keeping the rest.
+///
 /// The [`Default::default()`] of this type is not well configured; it is not a good reference for any character controller, and will not do much.
 /// See bundles like [`CharacterControllerBundle`](super::bundles::CharacterControllerBundle) for well-config
-#[derive(Component, Reflect)]
+#[derive(Clone, Component, Default, Reflect)]
 #[reflect(Component)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ControllerSettings {
+    /// The direction to jump, which is also the direction that gravity is opposite to. Kept up to date by
+    /// [`DynamicGravity`] and [`ReferenceFrame`] on characters that have them.
+    pub up_vector: Vec3,
+    /// The strength of gravity.
+    pub gravity: f32,
+    /// See [`ControllerClock`].
+    pub clock: ControllerClock,
+    /// See [`ControllerBody`].
+    pub body: ControllerBody,
+    /// See [`LocomotionSettings`].
+    pub locomotion: LocomotionSettings,
+    /// See [`JumpSettings`].
+    pub jump: JumpSettings,
+    /// See [`FloatSettings`].
+    pub float: FloatSettings,
+    /// See [`GroundCastSettings`].
+    pub ground_cast: GroundCastSettings,
+    /// See [`UprightSettings`].
+    pub upright: UprightSettings,
+    /// See [`FacingSettings`].
+    pub facing: FacingSettings,
+    /// Where the character's eyes are, relative to its origin and rotation. Aiming and interaction start from here, see
+    /// [`AimTransform`] and [`InteractionRay`].
+    pub eye: Vec3,
+    /// See [`ActionSettings`].
+    pub actions: ActionSettings,
+    /// See [`CrushSettings`].
+    pub crush: CrushSettings,
+    /// See [`RecoverySettings`].
+    pub recovery: RecoverySettings,
+    /// See [`CrouchSettings`].
+    pub crouch: CrouchSettings,
+    /// See [`SprintSettings`].
+    pub sprint: SprintSettings,
+    /// See [`DashSettings`].
+    pub dash: DashSettings,
+    /// See [`TractionSettings`].
+    pub traction: TractionSettings,
+    /// See [`WallSettings`].
+    pub wall: WallSettings,
+    /// See [`SwimSettings`].
+    pub swim: SwimSettings,
+    /// See [`ClimbSettings`].
+    pub climb: ClimbSettings,
+    /// See [`FlySettings`].
+    pub fly: FlySettings,
+    /// See [`GlideSettings`].
+    pub glide: GlideSettings,
+}
+
+impl ControllerSettings {
+    /// See [`CharacterControllerPreset`].
+    pub fn character() -> Self {
+        CharacterControllerPreset.into()
+    }
+
+    /// See [`StarshipControllerPreset`].
+    pub fn starship() -> Self {
+        StarshipControllerPreset.into()
+    }
+
+    /// How far above the ground to float, depending on whether the character is crouched.
+    pub fn float_distance(&self, crouched: bool) -> f32 {
+        if crouched {
+            self.crouch.float_distance
+        } else {
+            self.float.distance
+        }
+    }
+
+    /// The fastest the character moves by itself, depending on whether it is crouched or sprinting.
+    pub fn max_speed(&self, crouched: bool, sprinting: bool) -> f32 {
+        if crouched {
+            self.crouch.max_speed
+        } else if sprinting {
+            self.sprint.max_speed
+        } else {
+            self.locomotion.max_speed
+        }
+    }
+
+    /// How quickly the character reaches its [`max_speed`](ControllerSettings::max_speed), depending on whether it is
+    /// sprinting.
+    pub fn acceleration(&self, sprinting: bool) -> f32 {
+        if sprinting {
+            self.sprint.acceleration
+        } else {
+            self.locomotion.acceleration
+        }
+    }
+}
+
+/// Which clock a character controller's forces and timers (coyote time, jump buffering, etc.) follow.
+/// Part of [`ControllerSettings`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum ControllerClock {
+    /// The time simulated by the physics engine this frame, see [`PhysicsBackend::simulated_seconds`]. With Rapier, this
+    /// follows the `time_scale` of its `TimestepMode`, so slowing Rapier down for bullet time slows the controller down with it.
+    #[default]
+    Virtual,
+    /// The real time this frame took, regardless of how fast Rapier is simulating.
+    /// Note that the physics simulation still moves the character at Rapier's speed.
+    Real,
+    /// A constant time step, such as when running the controller on a fixed timestep.
+    Fixed(f32),
+}
+
+impl ControllerClock {
+    /// How much time passed this frame according to this clock, with `config` the physics settings of backend `B`.
+    pub fn delta_seconds<B: PhysicsBackend>(&self, time: &Time, config: &B::Config) -> f32 {
+        match *self {
+            ControllerClock::Virtual => B::simulated_seconds(config, time),
+            ControllerClock::Real => time.delta_seconds(),
+            ControllerClock::Fixed(dt) => dt,
+        }
+    }
+}
+
+/// How a character controller moves its body. Part of [`ControllerSettings`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum ControllerBody {
+    /// The character is a dynamic rigidbody pushed around by impulses, so it interacts with other bodies physically.
+    #[default]
+    Dynamic,
+    /// The character is a `RigidBody::KinematicPositionBased` moved directly by the controller, which slides it along
+    /// whatever it runs into. It isn't pushed by other bodies, which makes it more predictable.
+    ///
+    /// The controller's impulses are applied as changes to [`ControllerState::kinematic_velocity`], as if the character
+    /// had a mass of 1.0. The character's [`RigidBody`] is switched to match whenever this setting changes, and left alone
+    /// otherwise. It needs a [`Collider`] to slide with.
+    Kinematic,
+}
+
+/// Settings for crouching while [`ControllerInput::crouching`] is held. Part of [`ControllerSettings`].
+///
+/// Crouching shortens the character's capsule [`Collider`] from the top, and lowers it to float at
+/// [`float_distance`](CrouchSettings::float_distance) with its feet where they were. The character only stands back up once
+/// there's room above it. For crouching with other colliders, blending or crouching automatically, see
+/// [`ControllerStances`] and [`AutoCrouch`] instead.
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct CrouchSettings {
+    /// How tall the character's capsule is while crouched, from the bottom of one cap to the top of the other. Zero
+    /// disables crouching, as do colliders other than capsules.
+    pub height: f32,
+    /// How far above the ground the character floats while crouched, in place of [`FloatSettings::distance`].
+    pub float_distance: f32,
+    /// The fastest the character moves by itself while crouched, in place of [`LocomotionSettings::max_speed`].
+    pub max_speed: f32,
+}
+
+/// Settings for sprinting while [`ControllerInput::sprinting`] is held. Part of [`ControllerSettings`].
+///
+/// Characters only sprint while moving and not crouched. Give the character a [`SprintStamina`] to limit how long it can
+/// sprint for.
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SprintSettings {
+    /// The fastest the character moves by itself while sprinting, in place of [`LocomotionSettings::max_speed`]. Zero
+    /// disables sprinting.
+    pub max_speed: f32,
+    /// How quickly to reach [`max_speed`](SprintSettings::max_speed) while sprinting, in place of
+    /// [`LocomotionSettings::acceleration`].
+    pub acceleration: f32,
+}
+
+/// Settings for dashing, a short burst of speed started by pressing [`ControllerInput::dashing`]. Part of
+/// [`ControllerSettings`].
+///
+/// A dash moves the character at [`speed`](DashSettings::speed) in the direction of its movement, or forward if it isn't
+/// moving, without the limits of [`LocomotionSettings::acceleration`] and
+/// [`max_acceleration_force`](LocomotionSettings::max_acceleration_force). Afterwards it slows down as it normally would. See
+/// [`DashEvent`](crate::DashEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct DashSettings {
+    /// How fast the character dashes. Zero disables dashing.
+    pub speed: f32,
+    /// How long a dash lasts, in seconds.
+    pub duration: f32,
+    /// How long after a dash ends before the character can dash again, in seconds.
+    pub cooldown: f32,
+    /// Whether the character neither falls nor rises while dashing.
+    pub ignore_gravity: bool,
+    /// Whether the character can only start dashing while grounded.
+    pub ground_only: bool,
+}
+
+/// Settings for how movement input is turned into motion. Part of [`ControllerSettings`].
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct LocomotionSettings {
     /// How quickly to interpolate from `last_goal_velocity` to the new `input_goal_velocity`.
     /// In other words, how quickly to go from "not moving" to "moving at max speed".
     pub acceleration: f32,
@@ -42,45 +465,67 @@ pub struct ControllerSettings {
     ///
     /// Keys are generally either not saturated or fully saturated, while analog controls like a joystick can be partially saturated (half tilt).
     pub max_speed: f32,
-    /// The maximum amount of force that can be applied to fulfill [`acceleration`](ControllerSettings::acceleration).
+    /// The maximum amount of force that can be applied to fulfill [`acceleration`](LocomotionSettings::acceleration).
     pub max_acceleration_force: f32,
-    /// The direction to jump, which is also the direction that gravity is opposite to.
-    pub up_vector: Vec3,
-    /// The strength of gravity.
-    pub gravity: f32,
-    /// The maximum angle that the ground can be, in radians, before it is no longer considered suitable for being "grounded" on.
-    ///
-    /// For example, if this is set to `π/4` (45 degrees), then a player standing on a slope steeper than 45 degrees will slip and fall, and will not have
-    /// their jump refreshed by landing on that surface.
-    pub max_ground_angle: f32,
-    /// While floating, the character can be floating at a different exact distance than [`float_distance`] depending on other forces acting on them.
-    /// This field controls how much lower than [`float_distance`] they can be and still be considered grounded.
+    /// Scales movement force. This is useful to ensure movement does not affect vertical velocity (by setting it to e.g. `Vec3(1.0, 0.0, 1.0)`).
+    /// `Y` scales the part of the force along [`up_vector`](ControllerSettings::up_vector), so this keeps working when the
+    /// up vector changes, e.g. with [`DynamicGravity`].
+    pub force_scale: Vec3,
+    /// How much of the ground's change in velocity since last frame is fed directly into the goal velocity, from 0.0 to 1.0.
     ///
-    /// This helps keep jumps more consistent when the ground cast length is longer than the float distance.
-    pub min_float_offset: f32,
-    /// While floating, the character can be floating at a different exact distance than [`float_distance`] depending on other forces acting on them.
-    /// This field controls how much higher than [`float_distance`] they can be and still be considered grounded.
+    /// Without this, speed changes of a moving platform are only followed as fast as [`acceleration`](LocomotionSettings::acceleration)
+    /// allows, so the character lags behind or overshoots when the platform starts or stops. This part of the force is not limited
+    /// by [`max_acceleration_force`](LocomotionSettings::max_acceleration_force).
+    pub ground_acceleration_feed_forward: f32,
+    /// How the character is carried along by moving platforms. See [`PlatformCarry`].
+    pub platform_carry: PlatformCarry,
+    /// How strongly to push a character sideways around an obstacle, such as a door frame, that blocks its movement but
+    /// ends just off to the side. Zero disables it.
+    pub corner_nudge: f32,
+    /// How far to the side of where the character is blocked the edge of the obstacle can be for
+    /// [`corner_nudge`](LocomotionSettings::corner_nudge) to apply.
+    pub corner_nudge_width: f32,
+    /// The highest step, such as a stair or a curb, that a grounded character rises onto by itself as it moves into it,
+    /// instead of being stopped by it or popped up by the float spring once it's underneath. Zero disables it.
     ///
-    /// This helps keep jumps more consistent when the ground cast length is longer than the float distance.
-    pub max_float_offset: f32,
+    /// Usually the same as [`GroundCastSettings::step_height`], so that the character can stand on the edge of the step
+    /// too.
+    pub max_step_height: f32,
+    /// Whether to move along the ground the character stands on rather than horizontally, so it keeps the same speed up
+    /// and down slopes and doesn't fight the float spring or fly off of crests. The movement force is tilted onto the
+    /// ground after being scaled by [`force_scale`](LocomotionSettings::force_scale).
+    pub project_on_ground: bool,
+    /// If set, movement doesn't brake velocity beyond what the input asks for, such as from explosions, jump pads or
+    /// boosters. That velocity only slows down by this much per second, while the character can still steer. `None`
+    /// brakes all horizontal velocity towards the input's goal velocity.
+    pub external_momentum_drag: Option<f32>,
+}
+
+/// Settings for jumping. Part of [`ControllerSettings`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct JumpSettings {
     /// The amount of force to apply on the first frame when a jump begins.
-    pub jump_initial_force: f32,
+    pub initial_force: f32,
     /// The amount of force to continuously apply every second during a jump.
-    pub jump_force: f32,
+    pub force: f32,
     /// The amount of force to apply downwards when the jump control is released prior to a jump expiring.
     /// This allows analog jumping by cutting the jump short when the control is released.
-    pub jump_stop_force: f32,
+    pub stop_force: f32,
     /// How long a jump can last.
-    pub jump_time: f32,
+    pub time: f32,
     /// A function taking the current progress of a jump, from 0.0 to 1.0, with 0.0 indicating a jump has just begun and 1.0 indicating the jump has ended,
-    /// which returns a modifier (usually from 0.0 to 1.0, but not necessarily) to multiply [`jump_force`](ControllerSettings::jump_force) by.
+    /// which returns a modifier (usually from 0.0 to 1.0, but not necessarily) to multiply [`force`](JumpSettings::force) by.
     #[reflect(ignore)]
-    pub jump_decay_function: fn(f32) -> f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub decay_function: fn(f32) -> f32,
     /// How long to skip ground checks after jumping. Usually this should be set just high enough that the character is out of range of the ground
     /// just before the timer elapses.
-    pub jump_skip_ground_check_duration: f32,
-    /// Override skip ground check. If true, never checks for the ground.
-    pub skip_ground_check_override: bool,
+    pub skip_ground_check_duration: f32,
     /// How many extra times the character can jump after leaving the ground. 0 is normal, 1 corresponds to double jump, etc.
     pub extra_jumps: u32,
     /// How long should the character still be able to jump after leaving the ground, in seconds.
@@ -88,75 +533,2461 @@ pub struct ControllerSettings {
     pub coyote_time_duration: f32,
     /// If the jump input is pressed before landing, how long will the jump be buffered for?
     /// In other words, if this is 0.5, the character can input jump up to 0.5 seconds before landing and the jump will occur when they land.
-    pub jump_buffer_duration: f32,
-    /// Scales movement force. This is useful to ensure movement does not affect vertical velocity (by setting it to e.g. `Vec3(1.0, 0.0, 1.0)`).
-    pub force_scale: Vec3,
+    pub buffer_duration: f32,
+    /// How far below the top of a ledge the character can catch on it while airborne, and still be lifted over it.
+    /// Helps with jumps that barely clip a ledge's lip. Zero disables it.
+    pub ledge_forgiveness: f32,
+}
+
+impl Default for JumpSettings {
+    fn default() -> Self {
+        Self {
+            initial_force: default(),
+            force: default(),
+            stop_force: default(),
+            time: 1.0,
+            decay_function: |_| 1.0,
+            skip_ground_check_duration: default(),
+            extra_jumps: default(),
+            coyote_time_duration: default(),
+            buffer_duration: default(),
+            ledge_forgiveness: default(),
+        }
+    }
+}
+
+/// Settings for floating above the ground. Part of [`ControllerSettings`].
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FloatSettings {
+    /// How far to attempt to float away from the ground.
+    pub distance: f32,
+    /// How strongly to float away from the ground.
+    pub strength: f32,
+    /// How strongly to dampen floating away from the ground, to prevent jittering/oscillating float movement.
+    pub dampen: f32,
+    /// While floating, the character can be floating at a different exact distance than [`distance`](FloatSettings::distance) depending on other forces acting on them.
+    /// This field controls how much lower than [`distance`](FloatSettings::distance) they can be and still be considered grounded.
+    ///
+    /// This helps keep jumps more consistent when the ground cast length is longer than the float distance.
+    pub min_offset: f32,
+    /// While floating, the character can be floating at a different exact distance than [`distance`](FloatSettings::distance) depending on other forces acting on them.
+    /// This field controls how much higher than [`distance`](FloatSettings::distance) they can be and still be considered grounded.
+    ///
+    /// This helps keep jumps more consistent when the ground cast length is longer than the float distance.
+    pub max_offset: f32,
+    /// How much of the ground's change in velocity along [`up_vector`](ControllerSettings::up_vector) is applied directly
+    /// to the character, from 0.0 to 1.0.
+    ///
+    /// The float spring alone only reacts once the ground has already moved, so fast elevators cause bouncing when going up
+    /// and separation when going down. With this set to 1.0, the character follows the elevator as it starts and stops.
+    pub vertical_ground_velocity_tracking: f32,
+    /// How much the float damping anticipates the ground's vertical acceleration (see [`ControllerState::ground_vertical_acceleration`]).
+    /// 0.0 damps relative to the ground's current velocity, 1.0 damps relative to its predicted velocity next frame.
+    pub ground_acceleration_feed_forward: f32,
+    /// How much further than [`max_offset`](FloatSettings::max_offset) the ground can fall away from a grounded character,
+    /// per unit of its speed along the ground, and still be snapped back down to. Fast characters then hug the terrain
+    /// over crests, as in racing games, instead of launching off of them. The ground cast is lengthened to match.
+    ///
+    /// Zero, the default, leaves characters to fly off of crests.
+    pub downhill_snap: f32,
+    /// The fastest a character that just left the ground without jumping, such as by running over a crest or a bump, is
+    /// pulled back down onto the ground still below it within [`GroundCastSettings::length`]. Unlike
+    /// [`downhill_snap`](FloatSettings::downhill_snap), this doesn't keep the character grounded while the ground falls
+    /// away, but corrects it back down onto the ground.
+    ///
+    /// Zero, the default, disables it.
+    pub max_snap_speed: f32,
+    /// How hard the character pushes back down on the dynamic body it stands on, such as a raft or a seesaw, from 0.0 to
+    /// 1.0. At 1.0 the body feels the character's full weight, scaled by its mass, and the opposite of the float spring's
+    /// push, at the point the character stands on it.
+    ///
+    /// Zero, the default, leaves the ground unaffected. Other characters are never pushed.
+    pub ground_reaction: f32,
+}
+
+/// Settings for detecting the ground. Part of [`ControllerSettings`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GroundCastSettings {
     /// How long of a ray to cast to detect the ground. Setting this unnecessarily high will permanently count the player as grounded,
     /// and too low will allow the player to slip and become disconnected from the ground easily.
-    pub float_cast_length: f32,
+    pub length: f32,
     /// An offset to start the ground check from, relative to the character's origin.
-    pub float_cast_origin: Vec3,
+    pub origin: Vec3,
     /// What shape of ray to cast. See [`Collider`] and [`RapierContext::cast_shape`](RapierContext).
     #[reflect(ignore)]
-    pub float_cast_collider: Collider,
-    /// How far to attempt to float away from the ground.
-    pub float_distance: f32,
-    /// How strongly to float away from the ground.
-    pub float_strength: f32,
-    /// How strongly to dampen floating away from the ground, to prevent jittering/oscillating float movement.
-    pub float_dampen: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::collider"))]
+    pub collider: Collider,
+    /// The maximum angle that the ground can be, in radians, before it is no longer considered suitable for being "grounded" on.
+    ///
+    /// For example, if this is set to `π/4` (45 degrees), then a player standing on a slope steeper than 45 degrees will slip and fall, and will not have
+    /// their jump refreshed by landing on that surface.
+    pub max_ground_angle: f32,
+    /// How high a step can be and still be stood on when the ground cast hits its edge, which is steeper than
+    /// [`max_ground_angle`](GroundCastSettings::max_ground_angle). A step needs a near-vertical riser below the edge and
+    /// ground flat enough to stand on above it, so steep slopes aren't mistaken for steps. Zero disables it. See
+    /// [`GroundSurface`].
+    pub step_height: f32,
+    /// Override skip ground check. If true, never checks for the ground.
+    pub skip_ground_check_override: bool,
+    /// Whether other character controllers count as ground. If false, a character that lands on another one's head slides off
+    /// of it, see [`character_slide_force`](GroundCastSettings::character_slide_force).
+    pub stand_on_characters: bool,
+    /// How strongly to push a character sideways off of another character it is standing on.
+    /// Only used if [`stand_on_characters`](GroundCastSettings::stand_on_characters) is false.
+    pub character_slide_force: f32,
+    /// How fast a character on a slope steeper than [`max_ground_angle`](GroundCastSettings::max_ground_angle) accelerates
+    /// down along it, in addition to falling, in units per second per second.
+    ///
+    /// Such a character isn't grounded, and its movement can't push it up the slope or hold it back from sliding down.
+    /// See [`ControllerState::sliding`].
+    pub slope_slide_acceleration: f32,
+    /// If set, reuse the previous frame's ground cast instead of casting again while the character stays still relative to
+    /// everything it found, and touches the same colliders. This is how far (and how many radians) anything can move before
+    /// the cache is invalidated. A large saving for idle crowds, but colliders that appear under the character without
+    /// touching it are not noticed until something moves. Changing the cast's collider, origin, length or direction, e.g.
+    /// by switching stances, also casts again.
+    pub cache_tolerance: Option<f32>,
+}
+
+impl Default for GroundCastSettings {
+    fn default() -> Self {
+        Self {
+            length: default(),
+            origin: default(),
+            collider: Collider::ball(1.0),
+            max_ground_angle: default(),
+            step_height: default(),
+            skip_ground_check_override: default(),
+            stand_on_characters: default(),
+            character_slide_force: default(),
+            slope_slide_acceleration: default(),
+            cache_tolerance: None,
+        }
+    }
+}
+
+/// Settings for staying upright. Part of [`ControllerSettings`].
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct UprightSettings {
     /// How strongly to attempt to stay upright. Alternatively, see [`LockedAxes`] to lock rotation entirely.
-    pub upright_spring_strength: f32,
+    pub spring_strength: f32,
     /// How strongly to dampen staying upright. Prevents jittering/oscillating upright movement.
-    pub upright_spring_damping: f32,
+    pub spring_damping: f32,
 }
 
-impl ControllerSettings {
-    /// See [`CharacterControllerPreset`].
-    pub fn character() -> Self {
-        CharacterControllerPreset.into()
+/// Settings for turning the character around [`up_vector`](ControllerSettings::up_vector). Part of [`ControllerSettings`].
+#[derive(Clone, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FacingSettings {
+    /// Which direction the character turns to face.
+    pub mode: FacingMode,
+    /// How strongly to turn towards the facing direction.
+    pub spring_strength: f32,
+    /// How strongly to dampen turning. Prevents jittering/oscillating around the facing direction.
+    pub spring_damping: f32,
+    /// How strongly [`ControllerInput::look_at`] pulls the character towards its target, relative to the facing direction.
+    /// At 1.0, the character ends up halfway between the facing direction and the target. Without a facing direction
+    /// (e.g. no movement input in [`FacingMode::Movement`]), the character turns to face the target.
+    pub look_at_strength: f32,
+    /// If set, [`FacingMode::Locked`] is released while the character moves at more than this angle (in radians) away from
+    /// [`ControllerInput::facing`], and the character pivots to face its movement instead. The lock is restored once the
+    /// movement comes back within the angle or stops. See [`FacingLockChangedEvent`](crate::FacingLockChangedEvent).
+    pub release_lock_angle: Option<f32>,
+    /// Whether the character turns along with the ground it stands on, such as a rotating platform, instead of keeping
+    /// its facing in the world. A character parented to its ground by [`PlatformCarry::Parent`] always turns with it.
+    pub follow_ground_rotation: bool,
+    /// If set, a character standing still that has to turn at least this angle (in radians) to face its facing direction
+    /// turns in place, sending a [`TurnInPlaceEvent`](crate::TurnInPlaceEvent) so a turning animation can be played.
+    pub turn_in_place_angle: Option<f32>,
+    /// How long a character turning in place waits before it starts turning, in seconds, so the turn can follow a
+    /// turning animation that plants its feet first. See [`turn_in_place_angle`](FacingSettings::turn_in_place_angle).
+    pub turn_in_place_delay: f32,
+}
+
+impl Default for FacingSettings {
+    fn default() -> Self {
+        Self {
+            mode: default(),
+            spring_strength: 20.0,
+            spring_damping: 1.0,
+            look_at_strength: 0.5,
+            release_lock_angle: None,
+            follow_ground_rotation: false,
+            turn_in_place_angle: None,
+            turn_in_place_delay: 0.0,
+        }
+    }
+}
+
+/// Which direction a character turns to face. See [`FacingSettings::mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum FacingMode {
+    /// The controller does not turn the character; use [`ControllerInput::custom_torque`] or rotate it yourself.
+    #[default]
+    None,
+    /// Face the direction of [`ControllerInput::movement`].
+    Movement,
+    /// Face [`ControllerInput::facing`] regardless of the direction of movement, e.g. for strafing while aiming.
+    Locked,
+}
+
+/// Settings for detecting when a character is crushed between colliders pushing into it from opposite sides.
+/// Part of [`ControllerSettings`]. See [`CrushedEvent`](crate::CrushedEvent).
+#[derive(Clone, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct CrushSettings {
+    /// How deep a collider must penetrate the character while it is pressed against another collider on the opposite side
+    /// for the character to count as crushed.
+    pub penetration: f32,
+    /// How long the character must be crushed before a [`CrushedEvent`](crate::CrushedEvent) is sent and
+    /// [`response`](CrushSettings::response) is applied.
+    pub duration: f32,
+    /// What the controller does about a crushed character by itself.
+    pub response: CrushResponse,
+}
+
+impl Default for CrushSettings {
+    fn default() -> Self {
+        Self {
+            penetration: 0.05,
+            duration: 0.1,
+            response: default(),
+        }
     }
+}
 
-    /// See [`StarshipControllerPreset`].
-    pub fn starship() -> Self {
-        StarshipControllerPreset.into()
+/// Settings for recovering a character that the physics engine flung away at an absurd speed, e.g. after it was spawned
+/// inside a collider. Part of [`ControllerSettings`]. See [`ControllerRecoveredEvent`](crate::ControllerRecoveredEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct RecoverySettings {
+    /// The fastest the character can plausibly move relative to its ground. Anything faster is clamped to it, and
+    /// [`ControllerState::last_goal_velocity`] is reset. Zero disables recovery.
+    pub max_speed: f32,
+    /// Whether to also stop the character and move it back to where it last stood safely, see
+    /// [`ControllerState::safe_position`].
+    pub teleport: bool,
+}
+
+/// What the controller does about a crushed character. See [`CrushSettings::response`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum CrushResponse {
+    /// Nothing; only a [`CrushedEvent`](crate::CrushedEvent) is sent.
+    #[default]
+    None,
+    /// Move the character along [`up_vector`](ControllerSettings::up_vector) by the given distance, out from between the colliders.
+    TeleportUp(f32),
+}
+
+/// An action the controller can perform in response to input. See [`ActionSettings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum ControllerAction {
+    /// See [`JumpSettings`].
+    Jump,
+    /// See [`DashSettings`].
+    Dash,
+    /// Vaulting over an obstacle, see [`Vault`].
+    Vault,
+    /// Grabbing a ledge, see [`LedgeHang`].
+    Grab,
+    /// Climbing up onto the ledge a character hangs from, see [`LedgeHang`].
+    Mantle,
+}
+
+impl ControllerAction {
+    /// How long a request for this action stays buffered. See [`ActionSettings::buffer_durations`].
+    ///
+    /// Vaulting and mantling are started by jumping, so they default to [`JumpSettings::buffer_duration`]. Dashes and grabs
+    /// aren't buffered unless they have an entry.
+    pub fn buffer_duration(self, settings: &ControllerSettings) -> f32 {
+        if let Some(&duration) = settings.actions.buffer_durations.get(&self) {
+            return duration;
+        }
+
+        match self {
+            ControllerAction::Jump | ControllerAction::Vault | ControllerAction::Mantle => {
+                settings.jump.buffer_duration
+            }
+            ControllerAction::Dash | ControllerAction::Grab => 0.0,
+        }
+    }
+}
+
+/// Decides which actions are performed when several of them could trigger on the same frame. Part of [`ControllerSettings`].
+///
+/// By default, every action that can trigger does. Actions are triggered by the stages that carry them out, in the order
+/// they run: jumps and dashes, then vaults, then grabbing ledges and mantling. Each is resolved against the ones already
+/// performed that frame, see [`ControllerFrame::actions`]. A later action with a higher priority than an earlier one it
+/// excludes cancels it, so e.g. a vault can take the place of the jump that started it.
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ActionSettings {
+    /// Actions from highest to lowest priority. Actions not in this list have the lowest priority, in no particular order.
+    pub priority: Vec<ControllerAction>,
+    /// Pairs of actions that can't be performed on the same frame. Only the one with the higher [`priority`](ActionSettings::priority)
+    /// is performed.
+    pub exclusive: Vec<(ControllerAction, ControllerAction)>,
+    /// How long requests for each action stay buffered, both in [`ControllerIntents`] and from [`ControllerInput`].
+    /// Actions without an entry use their own setting, such as [`JumpSettings::buffer_duration`].
+    ///
+    /// A buffered request is only consumed once its action is performed, so an action that can't trigger yet (or is excluded by
+    /// a higher priority action) still fires if it becomes possible within its window.
+    pub buffer_durations: HashMap<ControllerAction, f32>,
+}
+
+impl ActionSettings {
+    /// Removes the actions from `candidates` that are excluded by an action of higher priority, and sorts the rest by priority.
+    pub fn resolve(&self, candidates: &mut Vec<ControllerAction>) {
+        let rank = |action: &ControllerAction| {
+            self.priority
+                .iter()
+                .position(|a| a == action)
+                .unwrap_or(self.priority.len())
+        };
+        candidates.sort_by_key(rank);
+
+        let mut i = 0;
+        while i < candidates.len() {
+            let action = candidates[i];
+            let excluded = candidates[..i].iter().any(|&kept| {
+                self.exclusive
+                    .iter()
+                    .any(|&pair| pair == (kept, action) || pair == (action, kept))
+            });
+            if excluded {
+                candidates.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+/// An optional queue of timestamped action requests, consumed by the character controller.
+///
+/// Unlike the booleans in [`ControllerInput`], which are only sampled once per frame, an intent remembers when the action was
+/// requested. It stays queued until the action is performed or its buffer window runs out (e.g. [`JumpSettings::buffer_duration`]
+/// for [`ControllerAction::Jump`]), measured from the time it was requested rather than the frame it was seen on. This keeps
+/// buffering consistent at low frame rates.
+///
+/// Actions that depend on where the character is are buffered the same way, so e.g. a [`ControllerAction::Grab`] requested
+/// just before a ledge comes within reach still grabs it, and a [`ControllerAction::Dash`] requested during its cooldown
+/// still fires once it's over.
+///
+/// [`ControllerInput::jumping`] is still used to decide how long a jump is held.
+#[derive(Component, Default)]
+pub struct ControllerIntents {
+    pub(crate) queue: VecDeque<ControllerIntent>,
+}
+
+impl ControllerIntents {
+    /// Request `action` at `time`, in seconds since startup (see [`Time::seconds_since_startup`]).
+    pub fn push(&mut self, action: ControllerAction, time: f64) {
+        self.queue.push_back(ControllerIntent { action, time });
+    }
+
+    /// The intents that have not been consumed or expired yet, from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &ControllerIntent> {
+        self.queue.iter()
+    }
+
+    /// Whether `action` was requested within the last `window` seconds before `now`.
+    pub(crate) fn pending(&self, action: ControllerAction, now: f64, window: f32) -> bool {
+        self.queue
+            .iter()
+            .any(|intent| intent.action == action && now - intent.time <= window as f64)
+    }
+
+    /// Removes every queued request for `action`.
+    pub(crate) fn consume(&mut self, action: ControllerAction) {
+        self.queue.retain(|intent| intent.action != action);
+    }
+
+    /// Removes the requests that are older than their action's buffer window.
+    pub(crate) fn expire(&mut self, now: f64, window: impl Fn(ControllerAction) -> f32) {
+        self.queue
+            .retain(|intent| now - intent.time <= window(intent.action) as f64);
     }
 }
 
-impl Default for ControllerSettings {
+/// A request to perform an action, see [`ControllerIntents`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ControllerIntent {
+    /// The requested action.
+    pub action: ControllerAction,
+    /// When the action was requested, in seconds since startup.
+    pub time: f64,
+}
+
+/// A queue of high-level [`ControllerCommand`]s, such as "walk over there, then jump", for driving a character from scripts,
+/// dialogue or AI without producing input every frame.
+///
+/// The commands are carried out in order by the [`process_controller_commands`](crate::process_controller_commands) system,
+/// which writes the character's [`ControllerInput`]. It only touches the input while there are commands queued, so player input
+/// can take over again once the queue runs out.
+#[derive(Component)]
+pub struct ControllerCommands {
+    /// How far from the target of the last of a run of [`MoveToward`](ControllerCommand::MoveToward)s the character
+    /// starts slowing down, so it eases to a stop. Zero keeps it at full speed until it's there.
+    pub slowdown_radius: f32,
+    /// How close to the target of a [`MoveToward`](ControllerCommand::MoveToward) followed by another the character has to
+    /// get before heading for the next one, if further than its `tolerance`. Cuts corners, so the character takes them in
+    /// a curve rather than stopping to turn at each target.
+    pub corner_cutting: f32,
+    pub(crate) queue: VecDeque<ControllerCommand>,
+    pub(crate) releasing_jump: bool,
+    pub(crate) jump_arc: Option<JumpArc>,
+}
+
+impl Default for ControllerCommands {
     fn default() -> Self {
         Self {
-            acceleration: default(),
-            max_speed: default(),
-            max_acceleration_force: default(),
-            up_vector: default(),
-            gravity: default(),
-            max_ground_angle: default(),
-            min_float_offset: default(),
-            max_float_offset: default(),
-            jump_initial_force: default(),
-            jump_force: default(),
-            jump_stop_force: default(),
-            jump_time: 1.0,
-            jump_decay_function: |_| 1.0,
-            jump_skip_ground_check_duration: default(),
-            skip_ground_check_override: default(),
-            extra_jumps: default(),
-            coyote_time_duration: default(),
-            jump_buffer_duration: default(),
-            force_scale: default(),
-            float_cast_length: default(),
-            float_cast_origin: default(),
-            float_cast_collider: Collider::ball(1.0),
-            float_distance: default(),
-            float_strength: default(),
-            float_dampen: default(),
-            upright_spring_strength: default(),
-            upright_spring_damping: default(),
+            slowdown_radius: 1.0,
+            corner_cutting: 0.0,
+            queue: default(),
+            releasing_jump: false,
+            jump_arc: None,
+        }
+    }
+}
+
+impl ControllerCommands {
+    /// Queue `command` after the commands already queued.
+    pub fn push(&mut self, command: ControllerCommand) {
+        self.queue.push_back(command);
+    }
+
+    /// Drop every queued command, e.g. to interrupt a script. A [`JumpTo`](ControllerCommand::JumpTo) in progress
+    /// stops steering the character, which falls the rest of the way by itself.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.jump_arc = None;
+    }
+
+    /// The command being carried out, if any.
+    pub fn current(&self) -> Option<&ControllerCommand> {
+        self.queue.front()
+    }
+
+    /// Whether every command has been carried out.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// A high-level command for a character. See [`ControllerCommands`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControllerCommand {
+    /// Move towards `target` until the character is within `tolerance` of it, ignoring height along
+    /// [`up_vector`](ControllerSettings::up_vector).
+    MoveToward {
+        /// Where to move to, in world space.
+        target: Vec3,
+        /// How close is close enough.
+        tolerance: f32,
+    },
+    /// Press jump for a single frame, for the shortest possible jump.
+    JumpOnce,
+    /// Set [`ControllerInput::facing`]. Only turns the character in [`FacingMode::Locked`], see [`SetMode`](ControllerCommand::SetMode).
+    FaceDirection(Vec3),
+    /// Set [`FacingSettings::mode`].
+    SetMode(FacingMode),
+    /// Jump along an arc to `target`, such as across an off-mesh link of a navmesh. The arc is worked out from
+    /// [`ControllerSettings::gravity`] so it lands the character on `target`, and is checked for obstacles before jumping.
+    /// Waits until the character is grounded.
+    ///
+    /// See [`JumpLinkStartedEvent`](crate::JumpLinkStartedEvent), [`JumpLinkFinishedEvent`](crate::JumpLinkFinishedEvent)
+    /// and [`JumpLinkFailedEvent`](crate::JumpLinkFailedEvent).
+    JumpTo {
+        /// The point on the ground to land on, in world space.
+        target: Vec3,
+        /// How far above the higher of the start and `target` the arc peaks.
+        height: f32,
+    },
+}
+
+/// Drives a character along a path through a list of waypoints, e.g. for cutscenes, by writing its [`ControllerInput`].
+///
+/// The character keeps all of its physics, so it still reacts to being bumped, and the path waits for it to catch up.
+/// Passing each waypoint sends a [`PathProgressEvent`](crate::PathProgressEvent). The component stays in place once the end
+/// is reached, holding the character there; remove it to give control back.
+#[derive(Component)]
+pub struct PathFollow {
+    /// The points to pass through, in world space. Height along [`up_vector`](ControllerSettings::up_vector) is ignored.
+    pub waypoints: Vec<Vec3>,
+    /// The speed to move at around each waypoint, blended in between. If there are fewer speeds than waypoints,
+    /// the last one is used for the rest of the path.
+    pub speeds: Vec<f32>,
+    /// Follow a smooth curve through the waypoints rather than straight lines between them.
+    pub smooth: bool,
+    /// How far the point the character steers towards can get ahead of the character before it waits for it to catch up.
+    pub lookahead: f32,
+    /// How close the character has to get to the last waypoint to finish the path.
+    pub arrival_radius: f32,
+    /// How far from the end of the path the character starts slowing down, so it eases to a stop at the last waypoint.
+    /// Zero keeps it at full speed until it's almost there.
+    pub slowdown_radius: f32,
+    /// How far further along the path than the point it follows the character steers towards, so it starts turning into
+    /// bends early and takes them in a curve. Zero steers straight at the point it follows.
+    pub corner_cutting: f32,
+    pub(crate) distance: f32,
+    pub(crate) passed: usize,
+}
+
+impl PathFollow {
+    /// Follow straight lines through `waypoints` at a constant `speed`.
+    pub fn new(waypoints: Vec<Vec3>, speed: f32) -> Self {
+        Self {
+            waypoints,
+            speeds: vec![speed],
+            smooth: false,
+            lookahead: 0.5,
+            arrival_radius: 0.5,
+            slowdown_radius: 0.0,
+            corner_cutting: 0.0,
+            distance: 0.0,
+            passed: 0,
+        }
+    }
+
+    /// Use a different speed around each waypoint. See [`speeds`](PathFollow::speeds).
+    pub fn with_speeds(mut self, speeds: Vec<f32>) -> Self {
+        self.speeds = speeds;
+        self
+    }
+
+    /// Follow a smooth curve. See [`smooth`](PathFollow::smooth).
+    pub fn smooth(mut self) -> Self {
+        self.smooth = true;
+        self
+    }
+
+    /// Finish within `radius` of the last waypoint, slowing down from `slowdown_radius` away. See
+    /// [`arrival_radius`](PathFollow::arrival_radius) and [`slowdown_radius`](PathFollow::slowdown_radius).
+    pub fn with_arrival(mut self, radius: f32, slowdown_radius: f32) -> Self {
+        self.arrival_radius = radius;
+        self.slowdown_radius = slowdown_radius;
+        self
+    }
+
+    /// Start turning into bends `distance` early. See [`corner_cutting`](PathFollow::corner_cutting).
+    pub fn with_corner_cutting(mut self, distance: f32) -> Self {
+        self.corner_cutting = distance;
+        self
+    }
+
+    /// How many waypoints have been passed so far.
+    pub fn passed(&self) -> usize {
+        self.passed
+    }
+
+    /// Whether the character has reached the last waypoint.
+    pub fn finished(&self) -> bool {
+        self.passed >= self.waypoints.len()
+    }
+
+    /// The speed around waypoint `index`.
+    pub(crate) fn speed(&self, index: usize) -> f32 {
+        self.speeds
+            .get(index)
+            .or_else(|| self.speeds.last())
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// The point `t` (from 0.0 to 1.0) of the way from waypoint `index` to the next one.
+    pub(crate) fn point(&self, index: usize, t: f32) -> Vec3 {
+        let last = self.waypoints.len() - 1;
+        let p = |i: usize| self.waypoints[i.min(last)];
+        let (p1, p2) = (p(index), p(index + 1));
+        if !self.smooth {
+            return p1.lerp(p2, t);
         }
+
+        // Catmull-Rom, repeating the first and last waypoints.
+        let (p0, p3) = (p(index.saturating_sub(1)), p(index + 2));
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
     }
 }
 
+/// Walks a character back and forth between waypoints, e.g. for guards, by writing its [`ControllerInput`].
+///
+/// The character heads straight for each waypoint in turn, waits there for its pause, and sends a
+/// [`PatrolWaypointEvent`](crate::PatrolWaypointEvent) on arrival. Which waypoint comes next depends on the
+/// [`mode`](PatrolRoute::mode). Remove the component to give control back.
+#[derive(Component)]
+pub struct PatrolRoute {
+    /// The points to walk between, in world space. Height along [`up_vector`](ControllerSettings::up_vector) is ignored.
+    pub waypoints: Vec<Vec3>,
+    /// What to do after the last waypoint.
+    pub mode: PatrolMode,
+    /// The speed to walk at.
+    pub speed: f32,
+    /// How long to wait at each waypoint, in seconds. If there are fewer pauses than waypoints, the last one is used for
+    /// the rest of the route.
+    pub pauses: Vec<f32>,
+    /// How close the character has to get to a waypoint to reach it.
+    pub arrival_radius: f32,
+    pub(crate) target: usize,
+    pub(crate) reversed: bool,
+    pub(crate) pause: Countdown,
+    pub(crate) finished: bool,
+}
+
+impl PatrolRoute {
+    /// Walk between `waypoints` at `speed` without stopping, in the given `mode`.
+    pub fn new(waypoints: Vec<Vec3>, speed: f32, mode: PatrolMode) -> Self {
+        Self {
+            waypoints,
+            mode,
+            speed,
+            pauses: Vec::new(),
+            arrival_radius: 0.5,
+            target: 0,
+            reversed: false,
+            pause: default(),
+            finished: false,
+        }
+    }
+
+    /// Wait at each waypoint. See [`pauses`](PatrolRoute::pauses).
+    pub fn with_pauses(mut self, pauses: Vec<f32>) -> Self {
+        self.pauses = pauses;
+        self
+    }
+
+    /// Reach waypoints within `radius`. See [`arrival_radius`](PatrolRoute::arrival_radius).
+    pub fn with_arrival_radius(mut self, radius: f32) -> Self {
+        self.arrival_radius = radius;
+        self
+    }
+
+    /// The index of the waypoint the character is heading for, or waiting at.
+    pub fn target(&self) -> usize {
+        self.target
+    }
+
+    /// Whether the character is waiting at a waypoint.
+    pub fn is_paused(&self) -> bool {
+        self.pause.is_running()
+    }
+
+    /// Whether a [`PatrolMode::Once`] route has reached its last waypoint.
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    /// How long to wait at waypoint `index`.
+    pub(crate) fn pause_at(&self, index: usize) -> f32 {
+        self.pauses
+            .get(index)
+            .or_else(|| self.pauses.last())
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Move on to the waypoint after the one just reached.
+    pub(crate) fn advance(&mut self) {
+        let last = self.waypoints.len() - 1;
+        match self.mode {
+            PatrolMode::Loop => {
+                self.target = if self.target >= last {
+                    0
+                } else {
+                    self.target + 1
+                }
+            }
+            PatrolMode::PingPong => {
+                if self.target >= last {
+                    self.reversed = true;
+                } else if self.target == 0 {
+                    self.reversed = false;
+                }
+                self.target = if self.reversed {
+                    self.target.saturating_sub(1)
+                } else {
+                    (self.target + 1).min(last)
+                };
+            }
+            PatrolMode::Once => {
+                if self.target >= last {
+                    self.finished = true;
+                } else {
+                    self.target += 1;
+                }
+            }
+        }
+    }
+}
+
+/// What a [`PatrolRoute`] does after its last waypoint.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PatrolMode {
+    /// Head back to the first waypoint and go around again.
+    #[default]
+    Loop,
+    /// Turn around and walk the waypoints in reverse, then turn around again at the first one.
+    PingPong,
+    /// Stop at the last waypoint.
+    Once,
+}
+
+/// Keeps a character in formation with a leader, e.g. for a squad, by writing its [`ControllerInput`].
+///
+/// The character's slot is [`offset`](FormationSlot::offset) from the leader, turned with the way the leader faces. It
+/// matches the leader's velocity and catches up with its slot on top of that. When the slot is behind a wall or inside an
+/// obstacle, it is pulled in towards the leader until it's clear. Remove the component to give control back.
+#[derive(Component)]
+pub struct FormationSlot {
+    /// The character to keep in formation with.
+    pub leader: Entity,
+    /// Where the slot is relative to the leader, in the leader's local space with its facing flattened along
+    /// [`up_vector`](ControllerSettings::up_vector). `-Z` is in front of the leader and `X` is to its right.
+    pub offset: Vec3,
+    /// How close to the slot is close enough, so the character doesn't keep shuffling around in it.
+    pub arrival_radius: f32,
+    /// How fast the character closes the distance to its slot, as a fraction of the distance per second, on top of the
+    /// leader's velocity.
+    pub catch_up: f32,
+    /// How much room the slot needs around it. Obstacles closer than this pull the slot in towards the leader.
+    pub clearance: f32,
+    pub(crate) slot: Option<Vec3>,
+}
+
+impl FormationSlot {
+    /// Keep `offset` away from `leader`.
+    pub fn new(leader: Entity, offset: Vec3) -> Self {
+        Self {
+            leader,
+            offset,
+            arrival_radius: 0.25,
+            catch_up: 2.0,
+            clearance: 0.5,
+            slot: None,
+        }
+    }
+
+    /// Catch up with the slot at `rate`. See [`catch_up`](FormationSlot::catch_up).
+    pub fn with_catch_up(mut self, rate: f32) -> Self {
+        self.catch_up = rate;
+        self
+    }
+
+    /// Leave `clearance` room around the slot. See [`clearance`](FormationSlot::clearance).
+    pub fn with_clearance(mut self, clearance: f32) -> Self {
+        self.clearance = clearance;
+        self
+    }
+
+    /// Where the slot is in world space after pulling it clear of obstacles, once the leader has been found.
+    pub fn slot(&self) -> Option<Vec3> {
+        self.slot
+    }
+}
+
+/// Keeps a character within a band of distances from a target, e.g. for ranged enemies, by writing its
+/// [`ControllerInput`].
+///
+/// The character approaches the target when further than [`max_distance`](KeepDistance::max_distance), backs away when
+/// closer than [`min_distance`](KeepDistance::min_distance), and circles it in between. It is turned to face the target
+/// through [`ControllerInput::facing`], so it keeps facing the target while backing away or circling in
+/// [`FacingMode::Locked`]. Remove the component to give control back.
+#[derive(Component)]
+pub struct KeepDistance {
+    /// The entity to keep away from.
+    pub target: Entity,
+    /// How close the character lets the target get before backing away.
+    pub min_distance: f32,
+    /// How far the character lets the target get before approaching it.
+    pub max_distance: f32,
+    /// The speed to approach and back away at.
+    pub speed: f32,
+    /// The speed to circle the target at while within the band, counterclockwise around
+    /// [`up_vector`](ControllerSettings::up_vector) when positive and clockwise when negative. Zero stands still.
+    pub strafe_speed: f32,
+}
+
+impl KeepDistance {
+    /// Stay between `min_distance` and `max_distance` away from `target`, moving at `speed` and not circling it.
+    pub fn new(target: Entity, min_distance: f32, max_distance: f32, speed: f32) -> Self {
+        Self {
+            target,
+            min_distance,
+            max_distance,
+            speed,
+            strafe_speed: 0.0,
+        }
+    }
+
+    /// Circle the target at `speed` while within the band. See [`strafe_speed`](KeepDistance::strafe_speed).
+    pub fn with_strafe(mut self, speed: f32) -> Self {
+        self.strafe_speed = speed;
+        self
+    }
+}
+
+/// Ticks a character less often the further it is from the nearest [`LodViewer`], so large crowds stay affordable.
+///
+/// Characters within [`full_rate_distance`](ControllerLod::full_rate_distance) are moved every frame, those within
+/// [`half_rate_distance`](ControllerLod::half_rate_distance) every 2nd frame, and the rest every 4th frame, each time with
+/// the time elapsed since they were last moved. Characters without this component, or in a world without viewers, are
+/// moved every frame.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ControllerLod {
+    /// Up to which distance from a viewer the character is moved every frame.
+    pub full_rate_distance: f32,
+    /// Up to which distance from a viewer the character is moved every 2nd frame.
+    pub half_rate_distance: f32,
+    /// Multiplies both distances, so important characters stay responsive further away.
+    pub importance: f32,
+    pub(crate) interval: u32,
+    pub(crate) due: bool,
+    pub(crate) elapsed: f32,
+}
+
+impl Default for ControllerLod {
+    fn default() -> Self {
+        Self {
+            full_rate_distance: 20.0,
+            half_rate_distance: 50.0,
+            importance: 1.0,
+            interval: 1,
+            due: true,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl ControllerLod {
+    /// Every how many frames the character is currently moved: 1, 2 or 4.
+    pub fn interval(&self) -> u32 {
+        self.interval
+    }
+}
+
+/// Marks an entity, such as a player or camera, that [`ControllerLod`] and [`ControllerActivation`] distances are measured from,
+/// and that the [`WorldOrigin`](crate::WorldOrigin) follows.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct LodViewer;
+
+/// Where an entity is in the double precision world of a [`WorldOrigin`](crate::WorldOrigin), updated every frame from its
+/// [`GlobalTransform`]. Zero without a [`WorldOrigin`](crate::WorldOrigin).
+#[derive(Component, Default, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct WorldPosition(pub DVec3);
+
+/// Marks a root entity that is neither a [`RigidBody`] nor a [`Collider`], such as a camera or a light, to be moved along
+/// with the physics world when the [`WorldOrigin`](crate::WorldOrigin) is recentered. [`LodViewer`]s are moved regardless.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ShiftWithOrigin;
+
+/// Puts a character to sleep while it is further than [`radius`](ControllerActivation::radius) from every [`LodViewer`].
+///
+/// Sleeping characters are marked with [`ControllerAsleep`]. They are not moved and cast nothing, and their rigid body is
+/// allowed to sleep, but their [`ControllerState`] is kept as-is so they pick up where they left off when woken. Characters
+/// wake when a viewer comes within the radius, or when a [`WakeControllerEvent`](crate::WakeControllerEvent) is sent for them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ControllerActivation {
+    /// How close a viewer has to be to keep the character awake.
+    pub radius: f32,
+    /// How long a [`WakeControllerEvent`](crate::WakeControllerEvent) keeps the character awake with no viewer nearby.
+    pub wake_duration: f32,
+    pub(crate) wake_timer: Countdown,
+}
+
+impl Default for ControllerActivation {
+    fn default() -> Self {
+        Self {
+            radius: 100.0,
+            wake_duration: 5.0,
+            wake_timer: default(),
+        }
+    }
+}
+
+impl ControllerActivation {
+    /// Keep the character awake within `radius` of a viewer.
+    pub fn new(radius: f32) -> Self {
+        Self {
+            radius,
+            ..default()
+        }
+    }
+}
+
+/// Marks a character put to sleep by its [`ControllerActivation`].
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ControllerAsleep;
+
+/// Named variants of a character's [`ControllerSettings`], such as "combat" and "exploration", and which one is active.
+///
+/// Call [`switch_to`](ControllerStances::switch_to) to change stance. The character's [`ControllerSettings`] are then replaced
+/// (or blended over time) by the [`switch_stances`](crate::switch_stances) system, which also sends a
+/// [`StanceChangedEvent`](crate::StanceChangedEvent).
+///
+/// Stances can also change the character's [`Collider`], such as a shorter one for crouching. Give the stance a matching
+/// [`GroundCastSettings::collider`] so ground detection fits the character's new footprint.
+#[derive(Component)]
+pub struct ControllerStances {
+    /// The settings of each stance, by name.
+    pub stances: HashMap<String, ControllerSettings>,
+    /// The collider of the character in each stance, by name. When switching to a stance listed here, the character's
+    /// collider is blended along with the settings if both are capsules, balls, cuboids or cylinders, and otherwise replaced
+    /// as soon as the switch starts. The collider is kept as-is when switching to any other stance.
+    pub colliders: HashMap<String, Collider>,
+    /// How far sideways a character may be pushed when it switches to a stance whose collider doesn't fit where it is, such
+    /// as standing up under an overhang. It is moved to the nearest spot within this distance where the new collider fits,
+    /// instead of being left for the physics solver to push out. Set to 0.0 to never push characters.
+    pub push_out_distance: f32,
+    pub(crate) current: Option<String>,
+    pub(crate) pending: Option<(String, f32)>,
+    pub(crate) blend: Option<StanceBlend>,
+}
+
+impl Default for ControllerStances {
+    fn default() -> Self {
+        Self {
+            stances: default(),
+            colliders: default(),
+            push_out_distance: 0.5,
+            current: None,
+            pending: None,
+            blend: None,
+        }
+    }
+}
+
+pub(crate) struct StanceBlend {
+    pub(crate) from: ControllerSettings,
+    pub(crate) from_collider: Option<Collider>,
+    pub(crate) elapsed: f32,
+    pub(crate) duration: f32,
+}
+
+impl ControllerStances {
+    /// Add a stance with the given settings.
+    pub fn with_stance(
+        mut self,
+        name: impl Into<String>,
+        settings: impl Into<ControllerSettings>,
+    ) -> Self {
+        self.stances.insert(name.into(), settings.into());
+        self
+    }
+
+    /// Add a stance with the given settings, which also changes the character's collider. See [`colliders`](ControllerStances::colliders).
+    pub fn with_stance_collider(
+        mut self,
+        name: impl Into<String>,
+        settings: impl Into<ControllerSettings>,
+        collider: Collider,
+    ) -> Self {
+        let name = name.into();
+        self.colliders.insert(name.clone(), collider);
+        self.with_stance(name, settings)
+    }
+
+    /// The name of the current stance, if one has been switched to. While blending, this is the stance being blended to.
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Is the character currently blending between two stances?
+    pub fn is_blending(&self) -> bool {
+        self.blend.is_some()
+    }
+
+    /// Switch to the stance named `name`. Numeric settings are blended over `blend_time` seconds, while all others
+    /// switch immediately. A `blend_time` of 0.0 switches instantly.
+    pub fn switch_to(&mut self, name: impl Into<String>, blend_time: f32) {
+        self.pending = Some((name.into(), blend_time));
+    }
+}
+
+/// Settings for how hard a character can brake and turn on the ground before it skids. Part of [`ControllerSettings`].
+///
+/// When the character's movement asks to slow down faster than [`limit`](TractionSettings::limit), such as when stopping
+/// or turning around at speed, it skids: it only slows down at the limit and can barely steer until it has caught up with
+/// its movement. Give slippery colliders a [`SurfaceTraction`] to lower the limit on them. See
+/// [`SkiddingChangedEvent`](crate::SkiddingChangedEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct TractionSettings {
+    /// The fastest the character can slow down relative to the ground without skidding, in units per second per second.
+    /// Zero disables skidding.
+    pub limit: f32,
+    /// How long a skid lasts at least, in seconds.
+    pub skid_duration: f32,
+    /// How much of the character's sideways acceleration is kept while skidding, from 0.0 to 1.0.
+    pub skid_steering: f32,
+}
+
+/// Settings for sliding down and jumping off of walls found by a [`WallContact`]. Part of [`ControllerSettings`].
+///
+/// An airborne character pressing towards a wall falls along it no faster than [`slide_speed`](WallSettings::slide_speed),
+/// and jumping while next to a wall launches it up and away from the wall. Wall jumps don't use up
+/// [`JumpSettings::extra_jumps`]. See [`WallSlidingChangedEvent`](crate::WallSlidingChangedEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WallSettings {
+    /// The fastest a character pressing towards a wall falls along it. `None` disables wall sliding.
+    pub slide_speed: Option<f32>,
+    /// How fast a wall jump pushes the character away from the wall. Zero, along with [`jump_up`](WallSettings::jump_up),
+    /// disables wall jumps.
+    pub jump_away: f32,
+    /// How fast a wall jump launches the character upwards, instead of [`JumpSettings::initial_force`].
+    pub jump_up: f32,
+    /// How long after a wall jump the character's movement can't steer it towards or away from the wall, in seconds, so it
+    /// can't climb straight back up the wall it jumped off of.
+    pub jump_lockout: f32,
+}
+
+/// Settings for swimming in [`FluidVolume`]s. Part of [`ControllerSettings`].
+///
+/// While swimming, buoyancy holds the character up instead of the float spring, and [`ControllerInput::movement`] moves
+/// it up and down as well. Jumping near the surface hops out of the fluid. See
+/// [`SwimmingChangedEvent`](crate::SwimmingChangedEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct SwimSettings {
+    /// How fast the character swims. Zero disables swimming, so the character walks along the bottom of fluids.
+    pub max_speed: f32,
+    /// Like [`LocomotionSettings::acceleration`], while swimming.
+    pub acceleration: f32,
+    /// Like [`LocomotionSettings::max_acceleration_force`], while swimming.
+    pub max_acceleration_force: f32,
+    /// How tall the character is from its feet, for working out how much of it is submerged.
+    pub height: f32,
+    /// How much of the character, from 0.0 to 1.0, has to be submerged for it to swim rather than wade.
+    pub min_submersion: f32,
+    /// How far below the surface the top of the character can be for jumping to hop out of the fluid.
+    pub hop_depth: f32,
+    /// How fast a hop launches the character upwards, instead of [`JumpSettings::initial_force`].
+    pub hop_force: f32,
+}
+
+/// Settings for gliding, e.g. with a glider or parachute. Part of [`ControllerSettings`].
+///
+/// An airborne character glides while it falls with [`ControllerInput::jumping`] held, once any jump is over and its
+/// coyote time has run out, so jumping off a ledge late still jumps. Its fall is slowed to
+/// [`fall_speed`](GlideSettings::fall_speed) and its movement uses the glide's own speed and acceleration. Gliding stops on
+/// landing or when jump is let go. See [`GlidingChangedEvent`](crate::GlidingChangedEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GlideSettings {
+    /// How fast the character glides forward. Zero disables gliding.
+    pub max_speed: f32,
+    /// Like [`LocomotionSettings::acceleration`], while gliding.
+    pub acceleration: f32,
+    /// The fastest the character falls while gliding.
+    pub fall_speed: f32,
+}
+
+/// Settings for flying, e.g. for debug cameras, spectators or creative modes. Part of [`ControllerSettings`].
+///
+/// While [`enabled`](FlySettings::enabled), the ground isn't looked for, gravity, the float spring and the upright torque
+/// are turned off, and [`ControllerInput::movement`] moves the character in every direction. Can be switched on and off
+/// at any time.
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FlySettings {
+    /// Whether the character is flying.
+    pub enabled: bool,
+    /// Whether a flying character passes through colliders. Turns the character's collider into a [`Sensor`] while
+    /// flying.
+    pub noclip: bool,
+    /// How fast the character flies.
+    pub max_speed: f32,
+    /// How quickly the character speeds up to the velocity its movement asks for.
+    pub acceleration: f32,
+    /// How quickly the character slows down once it stops moving.
+    pub damping: f32,
+}
+
+/// Settings for climbing [`LadderVolume`]s. Part of [`ControllerSettings`].
+///
+/// A character inside a ladder volume grabs on by pressing towards the ladder. While climbing, gravity and the float
+/// spring are suspended, and pressing towards the ladder or up climbs up along it, while pressing away or down climbs
+/// down, letting go once the character reaches the ground. Jumping pushes the character off of the ladder. See
+/// [`ClimbingChangedEvent`](crate::ClimbingChangedEvent).
+#[derive(Clone, Default, Reflect)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ClimbSettings {
+    /// How fast the character climbs. Zero disables climbing.
+    pub speed: f32,
+    /// How much movement has to point towards a ladder to grab on, from 0.0 to 1.0.
+    pub min_press: f32,
+    /// How fast jumping off of a ladder pushes the character away from it.
+    pub jump_away: f32,
+    /// How fast jumping off of a ladder launches the character upwards, instead of [`JumpSettings::initial_force`].
+    pub jump_up: f32,
+    /// How long after jumping off of a ladder the character's movement can't steer it back towards the ladder, nor grab
+    /// on again, in seconds.
+    pub jump_lockout: f32,
+}
+
+/// Limits how long a character can sprint for. See [`SprintSettings`].
+///
+/// Sprinting drains stamina, and it refills once the character has stopped sprinting for
+/// [`regen_delay`](SprintStamina::regen_delay). A character that runs out can't sprint again until it has refilled to
+/// [`recover`](SprintStamina::recover). Other systems can spend stamina too, e.g. for dodging, with
+/// [`set_current`](SprintStamina::set_current).
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SprintStamina {
+    /// How much stamina the character has when full.
+    pub max: f32,
+    /// How much stamina sprinting uses per second.
+    pub drain: f32,
+    /// How much stamina refills per second.
+    pub regen: f32,
+    /// How long after the character stops sprinting before stamina starts to refill, in seconds.
+    pub regen_delay: f32,
+    /// How much stamina a character that ran out needs before it can sprint again.
+    pub recover: f32,
+    pub(crate) current: f32,
+    pub(crate) exhausted: bool,
+    pub(crate) regen_timer: Countdown,
+}
+
+impl Default for SprintStamina {
+    fn default() -> Self {
+        Self {
+            max: 5.0,
+            drain: 1.0,
+            regen: 1.0,
+            regen_delay: 1.0,
+            recover: 1.0,
+            current: 5.0,
+            exhausted: false,
+            regen_timer: default(),
+        }
+    }
+}
+
+impl SprintStamina {
+    /// How much stamina the character has left.
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+
+    /// Set how much stamina the character has left, up to [`max`](SprintStamina::max). Setting it to zero exhausts the
+    /// character, as if it had sprinted until it ran out.
+    pub fn set_current(&mut self, current: f32) {
+        self.current = current.clamp(0.0, self.max);
+        if self.current <= 0.0 {
+            self.exhausted = true;
+        }
+    }
+
+    /// Whether the character ran out of stamina and hasn't refilled to [`recover`](SprintStamina::recover) yet.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+}
+
+/// Lets a character thrust upwards while airborne and [`ControllerInput::thrusting`] is held, burning fuel.
+///
+/// Thrust only kicks in once a jump has finished, so holding the same control for jumping and thrusting jumps first and
+/// then keeps the character going up. Thrusting drops any buffered jump, so landing while thrusting doesn't jump again.
+/// Fuel refills while the character is grounded.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Jetpack {
+    /// The upwards force applied every second while thrusting. Has to be greater than
+    /// [`ControllerSettings::gravity`] to gain height.
+    pub thrust: f32,
+    /// How much fuel the character has when full.
+    pub max_fuel: f32,
+    /// How much fuel thrusting uses per second.
+    pub burn_rate: f32,
+    /// How much fuel refills per second while grounded.
+    pub regen_rate: f32,
+    pub(crate) fuel: f32,
+    pub(crate) thrusting: bool,
+}
+
+impl Default for Jetpack {
+    fn default() -> Self {
+        Self {
+            thrust: 40.0,
+            max_fuel: 2.0,
+            burn_rate: 1.0,
+            regen_rate: 1.0,
+            fuel: 2.0,
+            thrusting: false,
+        }
+    }
+}
+
+impl Jetpack {
+    /// How much fuel the character has left.
+    pub fn fuel(&self) -> f32 {
+        self.fuel
+    }
+
+    /// Set how much fuel the character has left, up to [`max_fuel`](Jetpack::max_fuel), e.g. for refuelling pickups.
+    pub fn set_fuel(&mut self, fuel: f32) {
+        self.fuel = fuel.clamp(0.0, self.max_fuel);
+    }
+
+    /// Whether the character thrusted this frame.
+    pub fn is_thrusting(&self) -> bool {
+        self.thrusting
+    }
+}
+
+/// Makes a character crouch by itself when it moves under something too low to stand under, and stand again once there's
+/// room, by switching between two of its [`ControllerStances`].
+///
+/// The standing stance must have a collider in [`ControllerStances::colliders`], which is what is checked for room. Only
+/// crouches started by this component are stood up from, so crouching on purpose is left alone.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct AutoCrouch {
+    /// The name of the stance to crouch in.
+    pub crouch: String,
+    /// The name of the stance to stand in.
+    pub stand: String,
+    /// How far ahead to look for low obstacles, in seconds of the character's current velocity.
+    pub lookahead: f32,
+    /// How long to blend between the stances, see [`ControllerStances::switch_to`].
+    pub blend_time: f32,
+    pub(crate) crouched: bool,
+}
+
+impl Default for AutoCrouch {
+    fn default() -> Self {
+        Self {
+            crouch: "crouch".into(),
+            stand: "stand".into(),
+            lookahead: 0.25,
+            blend_time: 0.2,
+            crouched: false,
+        }
+    }
+}
+
+impl AutoCrouch {
+    /// Whether the character is currently crouching because of this component.
+    pub fn is_crouched(&self) -> bool {
+        self.crouched
+    }
+}
+
+/// The key in [`ControllerPresets`](crate::ControllerPresets) of the preset this character's settings were last set from.
+/// Inserted by [`SetControllerPreset`](crate::SetControllerPreset).
+#[derive(Component, Default, Reflect, Clone, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub struct ControllerArchetype(pub String);
+
+/// The impulses the character controller applies this frame, split by what caused them. Each movement stage fills in its own
+/// part, and [`apply_forces`](crate::apply_forces) applies their sum. Look at this to find out which part of the controller
+/// is responsible for unexpected motion.
+#[derive(Component, Default, Reflect, Debug)]
+#[reflect(Component)]
+pub struct ControllerForces {
+    /// The impulse applied to reach the goal velocity.
+    pub movement: Vec3,
+    /// The impulse applied by jumping.
+    pub jump: Vec3,
+    /// The impulse applied by the float spring.
+    pub float: Vec3,
+    /// The impulse applied by gravity.
+    pub gravity: Vec3,
+    /// The torque impulse applied to stay upright.
+    pub upright: Vec3,
+    /// The torque impulse applied to turn towards the facing direction. See [`FacingSettings`].
+    pub facing: Vec3,
+    /// The impulse applied from [`ControllerInput::custom_impulse`].
+    pub external_impulse: Vec3,
+    /// The torque impulse applied from [`ControllerInput::custom_torque`].
+    pub external_torque: Vec3,
+}
+
+/// What the movement stages found out about a character this frame, passed from each stage to the next. Written by
+/// [`find_ground`](crate::find_ground) at the start of every frame, see [`MovementSet`](crate::MovementSet). A stage
+/// replacing a built-in one can read it, and should keep it up to date for the stages after it.
+#[derive(Component, Default, Clone)]
+pub struct ControllerFrame {
+    /// Whether the character moves this frame. It doesn't on frames where no time passes for it, or that its
+    /// [`ControllerLod`] skips.
+    pub active: bool,
+    /// How much time the character moves for this frame, including the frames skipped by its [`ControllerLod`].
+    pub dt: f32,
+    /// Where the character was as of the last physics step.
+    pub position: Vec3,
+    /// How the character was rotated as of the last physics step.
+    pub rotation: Quat,
+    /// The character's velocity at the start of the frame.
+    pub velocity: Velocity,
+    /// The ground the ground cast found, if any. This is set while the character floats up to
+    /// [`FloatSettings::max_offset`] too high, even though it isn't [`grounded`](ControllerFrame::grounded) yet.
+    pub ground: Option<ShapeHit>,
+    /// Whether the character is on the ground, within the float offsets.
+    pub grounded: bool,
+    /// The velocity of the ground that the character moves along with, where the character stands on it, so that it
+    /// includes the ground's rotation. While airborne inside a [`VehicleInterior`], the vehicle's velocity where the
+    /// character is. Zero if there is no ground, or if the character is parented to it by [`PlatformCarry::Parent`].
+    pub ground_velocity: Vec3,
+    /// How much [`ground_velocity`](ControllerFrame::ground_velocity) changed since last frame, if the character stands on
+    /// the same ground. While airborne inside a [`VehicleInterior`], this also turns the character's velocity along with
+    /// the vehicle.
+    pub ground_velocity_delta: Vec3,
+    /// The angular velocity of the ground, e.g. a rotating platform. Zero under the same conditions as
+    /// [`ground_velocity`](ControllerFrame::ground_velocity).
+    pub ground_angular_velocity: Vec3,
+    /// The push sliding the character off of another character's head. See [`GroundCastSettings::stand_on_characters`].
+    pub character_slide: Vec3,
+    /// The slope too steep to stand on that the character is sliding down or standing at the foot of, if any. See
+    /// [`GroundCastSettings::slope_slide_acceleration`].
+    pub steep_slope: Option<ShapeHit>,
+    /// The ground the character was standing on last frame.
+    pub previous_ground: Option<Entity>,
+    /// Why the previous ground was lost, if it was lost this frame for reasons other than the character moving off of it.
+    pub lost: Option<GroundLost>,
+    /// Whether the character jumped this frame.
+    pub jumped: bool,
+    /// The actions performed this frame so far, resolved against each other by [`ActionSettings`].
+    pub actions: Vec<ControllerAction>,
+}
+
+/// Information about the ground found by the character controller's ground cast, updated every frame. Useful for gameplay
+/// code that reacts to the ground, such as footstep sounds, decals or AI.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct GroundCastInfo {
+    /// The entity the ground cast hit this frame, if any.
+    pub ground: Option<Entity>,
+    /// Where the ground cast touched the ground. Only meaningful if [`ground`](GroundCastInfo::ground) is set.
+    pub point: Vec3,
+    /// The normal of the ground where the ground cast touched it. Only meaningful if [`ground`](GroundCastInfo::ground)
+    /// is set.
+    pub normal: Vec3,
+    /// How far the ground cast travelled before touching the ground. Only meaningful if
+    /// [`ground`](GroundCastInfo::ground) is set.
+    pub distance: f32,
+    /// Whether the ground cast started out already overlapping the ground, in which case
+    /// [`point`](GroundCastInfo::point) and [`normal`](GroundCastInfo::normal) are unreliable. Only meaningful if
+    /// [`ground`](GroundCastInfo::ground) is set.
+    pub penetrating: bool,
+    /// Whether the ground is within the float range, so the character stands on it. See [`FloatSettings`].
+    pub grounded: bool,
+    /// How the nearest surface the ground cast hit was classified, if it hit any. Useful for debugging steps and slopes.
+    pub surface: Option<GroundSurface>,
+    /// Set on the frame the previous ground was lost for reasons other than the character moving off of it.
+    pub lost: Option<GroundLost>,
+}
+
+/// Makes a character's ground cast ignore some colliders, such as constructs built by its teammates or its own summons,
+/// without changing what any other character stands on. Also applies to its [`LandingPrediction`].
+#[derive(Component, Default)]
+pub struct GroundCastFilter {
+    /// Colliders that never count as ground.
+    pub ignore: Vec<Entity>,
+    /// Called with every other collider the ground cast hits. The collider is ignored if this returns `false`.
+    pub predicate: Option<Box<dyn Fn(Entity) -> bool + Send + Sync>>,
+}
+
+impl GroundCastFilter {
+    /// Only count colliders as ground if `predicate` returns `true` for them.
+    pub fn new(predicate: impl Fn(Entity) -> bool + Send + Sync + 'static) -> Self {
+        Self {
+            ignore: Vec::new(),
+            predicate: Some(Box::new(predicate)),
+        }
+    }
+
+    /// Never count the given colliders as ground.
+    pub fn ignoring(ignore: impl IntoIterator<Item = Entity>) -> Self {
+        Self {
+            ignore: ignore.into_iter().collect(),
+            predicate: None,
+        }
+    }
+
+    /// Whether `collider` can count as ground.
+    pub fn allows(&self, collider: Entity) -> bool {
+        !self.ignore.contains(&collider)
+            && self
+                .predicate
+                .as_ref()
+                .is_none_or(|predicate| predicate(collider))
+    }
+}
+
+/// Where and when an airborne character is predicted to land, following its current velocity and gravity.
+/// Add this to a character to have it updated every frame, e.g. to start landing animations early.
+///
+/// The prediction ignores input and jump force, so it is only exact for characters that are falling freely. Characters
+/// without a [`Velocity`] are predicted to fall from rest.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LandingPrediction {
+    /// How far ahead to predict, in seconds.
+    pub horizon: f32,
+    /// How many straight segments the predicted path is split into. More segments follow the curve of a fall more closely.
+    pub segments: u32,
+    /// How long until the character lands, if it is airborne and will land within [`horizon`](LandingPrediction::horizon).
+    pub time_to_land: Option<f32>,
+    /// What the character will land on.
+    pub ground: Option<Entity>,
+    /// Where the character's origin will be when it lands.
+    pub position: Vec3,
+    /// The normal of the surface the character will land on. This may be a wall, if the character is falling into one.
+    pub normal: Vec3,
+}
+
+impl Default for LandingPrediction {
+    fn default() -> Self {
+        Self {
+            horizon: 1.0,
+            segments: 8,
+            time_to_land: None,
+            ground: None,
+            position: Vec3::ZERO,
+            normal: Vec3::ZERO,
+        }
+    }
+}
+
+/// Where a grounded character's feet should be placed, for inverse kinematics. Add this to a character to have a ray cast
+/// down from each foot every frame, with the same filter as the controller's ground cast.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct FootPlacement {
+    /// How far down from each foot's [`offset`](Foot::offset) to look for the ground.
+    pub length: f32,
+    /// See [`Foot`].
+    pub left: Foot,
+    /// See [`Foot`].
+    pub right: Foot,
+}
+
+impl Default for FootPlacement {
+    fn default() -> Self {
+        Self {
+            length: 1.5,
+            left: Foot {
+                offset: Vec3::new(-0.15, 0.0, 0.0),
+                ..default()
+            },
+            right: Foot {
+                offset: Vec3::new(0.15, 0.0, 0.0),
+                ..default()
+            },
+        }
+    }
+}
+
+/// One foot of a [`FootPlacement`].
+#[derive(Default, Reflect)]
+pub struct Foot {
+    /// Where to cast from, relative to the character's origin and rotation.
+    pub offset: Vec3,
+    /// The entity under the foot, if the character is grounded and there is anything within [`FootPlacement::length`].
+    pub ground: Option<Entity>,
+    /// Where the foot should be placed. Only meaningful if [`ground`](Foot::ground) is set.
+    pub position: Vec3,
+    /// The normal of the ground under the foot. Only meaningful if [`ground`](Foot::ground) is set.
+    pub normal: Vec3,
+}
+
+/// Where a character aims from and towards, e.g. for spawning projectiles. Add this to a character to have it updated every frame,
+/// aiming from its [`eye`](ControllerSettings::eye).
+///
+/// Unlike the character's own transform, this does not bob up and down with the float spring, so aiming is not thrown off by the
+/// controller's vertical micro-motion.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct AimTransform {
+    /// The direction the character is aiming in, in world space. If zero, the character's forward direction is used.
+    pub direction: Vec3,
+    /// The stabilized eye position, looking along the aim direction.
+    pub transform: Transform,
+}
+
+impl Default for AimTransform {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::ZERO,
+            transform: Transform::identity(),
+        }
+    }
+}
+
+/// What a character is looking at, for "press E to interact" prompts. Add this to a character to have a ray cast every frame
+/// from its [`eye`](ControllerSettings::eye) along the look direction, with the same filter as the controller's ground cast.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct InteractionRay {
+    /// The direction the character is looking in, in world space. If zero, the character's forward direction is used.
+    ///
+    /// Set this from the camera in first person games.
+    pub direction: Vec3,
+    /// How far the character can reach.
+    pub length: f32,
+    /// The entity the character is looking at, if any is within [`length`](InteractionRay::length).
+    pub hovered: Option<Entity>,
+    /// Where the ray hit [`hovered`](InteractionRay::hovered). Only meaningful if it is set.
+    pub point: Vec3,
+    /// The normal of [`hovered`](InteractionRay::hovered) where the ray hit it. Only meaningful if it is set.
+    pub normal: Vec3,
+}
+
+impl Default for InteractionRay {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::ZERO,
+            length: 2.0,
+            hovered: None,
+            point: Vec3::ZERO,
+            normal: Vec3::ZERO,
+        }
+    }
+}
+
+/// What is in a character's way, shared by features like vaulting, mantling and auto-jumping, and by AI. Add this to a
+/// character to have it probed every frame with shape casts along its movement direction, with the same filter as the
+/// controller's ground cast.
+///
+/// Heights are measured from the character's feet: where it last touched the ground while grounded, and
+/// [`FloatSettings::distance`] below its ground cast origin while airborne.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ForwardObstruction {
+    /// How far ahead to look.
+    pub range: f32,
+    /// The radius of the spheres cast to probe for obstacles.
+    pub probe_radius: f32,
+    /// Obstacles up to this high are [`ObstructionHeight::Step`]s.
+    pub step_height: f32,
+    /// Obstacles up to this high, and higher than [`step_height`](ForwardObstruction::step_height), are
+    /// [`ObstructionHeight::Waist`] high.
+    pub waist_height: f32,
+    /// The direction that was probed in, perpendicular to [`up_vector`](ControllerSettings::up_vector). This is the
+    /// direction of the character's [`movement`](ControllerInput::movement), or its forward direction while it isn't moving.
+    pub direction: Vec3,
+    /// The obstacle in the way, if any is within [`range`](ForwardObstruction::range).
+    pub obstacle: Option<Entity>,
+    /// How far ahead the obstacle is. Only meaningful if [`obstacle`](ForwardObstruction::obstacle) is set.
+    pub distance: f32,
+    /// The normal of the obstacle where it was hit. Only meaningful if [`obstacle`](ForwardObstruction::obstacle) is set.
+    pub normal: Vec3,
+    /// How high the obstacle is, or `None` if nothing is in the way.
+    pub height: Option<ObstructionHeight>,
+}
+
+impl Default for ForwardObstruction {
+    fn default() -> Self {
+        Self {
+            range: 1.5,
+            probe_radius: 0.2,
+            step_height: 0.4,
+            waist_height: 1.2,
+            direction: Vec3::ZERO,
+            obstacle: None,
+            distance: 0.0,
+            normal: Vec3::ZERO,
+            height: None,
+        }
+    }
+}
+
+/// Reports when a character's [`movement`](ControllerInput::movement) has been blocked for a while, so that an AI driving
+/// it can find another way instead of walking into a wall forever. Add this to a character to have it checked every frame.
+/// See [`MovementBlockedEvent`](crate::MovementBlockedEvent).
+///
+/// A grounded character is blocked if it makes too little progress in the direction it is asked to move in, or if it is
+/// about to walk off a ledge. Ledges are reported right away, since the character would walk off of them otherwise.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MovementBlockage {
+    /// How long the character has to be blocked for before it is reported, and then to move freely for before it is no
+    /// longer blocked, in seconds.
+    pub duration: f32,
+    /// How much of the speed the character is asked to move at it has to make towards its movement to not count as
+    /// blocked, from 0.0 to 1.0.
+    pub min_progress: f32,
+    /// How far ahead of the character to look for a ledge.
+    pub ledge_distance: f32,
+    /// How far the ground has to drop away ahead of the character to count as a ledge.
+    pub ledge_depth: f32,
+    pub(crate) blocked_time: f32,
+    #[reflect(ignore)]
+    pub(crate) reason: Option<MovementBlockedReason>,
+}
+
+impl Default for MovementBlockage {
+    fn default() -> Self {
+        Self {
+            duration: 0.5,
+            min_progress: 0.25,
+            ledge_distance: 0.8,
+            ledge_depth: 1.0,
+            blocked_time: 0.0,
+            reason: None,
+        }
+    }
+}
+
+impl MovementBlockage {
+    /// Why the character's movement is blocked, if it has been for at least [`duration`](MovementBlockage::duration).
+    pub fn reason(&self) -> Option<MovementBlockedReason> {
+        self.reason
+    }
+}
+
+/// Why a character's movement is blocked. See [`MovementBlockage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MovementBlockedReason {
+    /// Something is in the way, such as a wall.
+    Obstacle(Entity),
+    /// The ground ahead is too steep to walk up, see [`GroundCastSettings::max_ground_angle`].
+    SteepSlope,
+    /// The ground drops away ahead, by at least [`MovementBlockage::ledge_depth`].
+    Ledge,
+    /// The character isn't getting anywhere, but nothing in particular is in its way, e.g. because it is being pushed back.
+    Stuck,
+}
+
+/// The wall beside a character, for wall sliding and wall jumping, see [`WallSettings`]. Add this to a character to have
+/// it probed for every frame with shape casts around it, perpendicular to its
+/// [`up_vector`](ControllerSettings::up_vector), with the same filter as the controller's ground cast.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct WallContact {
+    /// How far from the character's center a wall can be, usually a little more than the radius of its collider.
+    pub range: f32,
+    /// The radius of the spheres cast to probe for walls.
+    pub probe_radius: f32,
+    /// The nearest wall within [`range`](WallContact::range), if any. Surfaces facing too far up or down, such as the
+    /// ground or a ceiling, are not walls.
+    pub wall: Option<Entity>,
+    /// The normal of the wall, pointing away from it. Only meaningful if [`wall`](WallContact::wall) is set.
+    pub normal: Vec3,
+    /// How far from the character's center the wall is. Only meaningful if [`wall`](WallContact::wall) is set.
+    pub distance: f32,
+    /// Where the wall was hit. Only meaningful if [`wall`](WallContact::wall) is set.
+    pub point: Vec3,
+}
+
+impl Default for WallContact {
+    fn default() -> Self {
+        Self {
+            range: 0.8,
+            probe_radius: 0.2,
+            wall: None,
+            normal: Vec3::ZERO,
+            distance: 0.0,
+            point: Vec3::ZERO,
+        }
+    }
+}
+
+/// How high an obstacle found by [`ForwardObstruction`] is.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[reflect_value(PartialEq)]
+pub enum ObstructionHeight {
+    /// Low enough to step over, up to [`ForwardObstruction::step_height`].
+    Step,
+    /// Low enough to vault over, up to [`ForwardObstruction::waist_height`].
+    Waist,
+    /// Higher than that, such as a wall.
+    #[default]
+    Full,
+}
+
+/// Lets a character vault over [`ObstructionHeight::Waist`] high obstacles found by its [`ForwardObstruction`], which it
+/// also needs. By default the character vaults when it jumps next to such an obstacle, see
+/// [`automatic`](Vault::automatic).
+///
+/// The character follows an arc from where it starts to [`length`](Vault::length) ahead, peaking
+/// [`clearance`](Vault::clearance) above the obstacle, and ignores its other movement until it's done. See
+/// [`VaultStartedEvent`](crate::VaultStartedEvent) and [`VaultFinishedEvent`](crate::VaultFinishedEvent) for animating it.
+/// Vaulting is resolved against the character's other actions as [`ControllerAction::Vault`], see [`ActionSettings`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Vault {
+    /// Whether to vault whenever the character runs into a waist-high obstacle, rather than only when it jumps.
+    pub automatic: bool,
+    /// How close the obstacle has to be to vault over it.
+    pub max_distance: f32,
+    /// How far above the top of the obstacle the character's feet pass.
+    pub clearance: f32,
+    /// How far the character travels along the ground during a vault.
+    pub length: f32,
+    /// How long a vault takes, in seconds. A vault always takes at least one frame.
+    pub duration: f32,
+    #[reflect(ignore)]
+    pub(crate) arc: Option<VaultArc>,
+}
+
+impl Default for Vault {
+    fn default() -> Self {
+        Self {
+            automatic: false,
+            max_distance: 0.6,
+            clearance: 0.2,
+            length: 2.0,
+            duration: 0.5,
+            arc: None,
+        }
+    }
+}
+
+impl Vault {
+    /// Whether the character is in the middle of a vault.
+    pub fn is_vaulting(&self) -> bool {
+        self.arc.is_some()
+    }
+}
+
+/// The ballistic arc of a [`ControllerCommand::JumpTo`] in progress. Part of [`ControllerCommands`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct JumpArc {
+    pub(crate) start: Vec3,
+    /// The velocity the arc starts out with.
+    pub(crate) velocity: Vec3,
+    /// The acceleration of gravity along the arc.
+    pub(crate) gravity: Vec3,
+    pub(crate) elapsed: f32,
+    pub(crate) duration: f32,
+}
+
+impl JumpArc {
+    /// The arc from `start` to `end` under `gravity` along `-up`, peaking `height` above the higher of the two. `None` if
+    /// there is no gravity to bring the character back down.
+    pub(crate) fn new(start: Vec3, end: Vec3, height: f32, gravity: f32, up: Vec3) -> Option<Self> {
+        if gravity <= 0.0 {
+            return None;
+        }
+        let rise = up.dot(end - start);
+        let peak = rise.max(0.0) + height.max(0.0);
+        let up_speed = (2.0 * gravity * peak).sqrt();
+        let duration = (up_speed + (2.0 * gravity * (peak - rise)).sqrt()) / gravity;
+        if duration <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            start,
+            velocity: (end - start).reject_from(up) / duration + up * up_speed,
+            gravity: -up * gravity,
+            elapsed: 0.0,
+            duration,
+        })
+    }
+
+    /// The point on the arc `time` seconds into it.
+    pub(crate) fn at(&self, time: f32) -> Vec3 {
+        self.start + self.velocity * time + self.gravity * 0.5 * time * time
+    }
+
+    /// The velocity along the arc `time` seconds into it.
+    pub(crate) fn velocity_at(&self, time: f32) -> Vec3 {
+        self.velocity + self.gravity * time
+    }
+}
+
+/// The path of a vault in progress. Part of [`Vault`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct VaultArc {
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    /// How far above the straight line from `start` to `end` the arc peaks.
+    pub(crate) rise: f32,
+    pub(crate) up: Vec3,
+    pub(crate) elapsed: f32,
+    pub(crate) duration: f32,
+}
+
+impl VaultArc {
+    /// The point on the arc `t` of the way through it, from 0.0 to 1.0.
+    pub(crate) fn at(&self, t: f32) -> Vec3 {
+        self.start.lerp(self.end, t) + self.up * self.rise * 4.0 * t * (1.0 - t)
+    }
+}
+
+/// Lets a character grab ledges while falling, hang from them and shimmy along them, then climb up or drop down. Ledges are
+/// found by probing ahead of the character at [`hand_height`](LedgeHang::hand_height) for a wall whose top is within
+/// [`reach`](LedgeHang::reach), and following the edge between the wall and its top. Characters can also climb down to
+/// hang from the edge of the ground they stand on, see [`drop_down_speed`](LedgeHang::drop_down_speed).
+///
+/// While hanging, the character ignores gravity and its other movement. Moving along the ledge shimmies, turning around
+/// corners. [`jumping`](ControllerInput::jumping) climbs up onto the ledge if the character fits on top of it, and
+/// [`dropping`](ControllerInput::dropping) lets go of it. See [`LedgeDetectedEvent`](crate::LedgeDetectedEvent),
+/// [`LedgeGrabbedEvent`](crate::LedgeGrabbedEvent) and [`LedgeReleasedEvent`](crate::LedgeReleasedEvent) for animating it.
+/// Grabbing and climbing up are resolved against the character's other actions as [`ControllerAction::Grab`] and
+/// [`ControllerAction::Mantle`], see [`ActionSettings`]. Pressing grab or jump shortly before they're possible is buffered
+/// for their [`buffer_duration`](ControllerAction::buffer_duration), as are their [`ControllerIntents`].
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LedgeHang {
+    /// How high above the character's origin its hands are while hanging.
+    pub hand_height: f32,
+    /// How far above or below the character's hands a ledge can be grabbed.
+    pub reach: f32,
+    /// How far ahead of the character's origin a ledge can be grabbed.
+    pub grab_distance: f32,
+    /// How far from the wall the character's origin hangs.
+    pub hang_offset: f32,
+    /// How fast the character shimmies along the ledge.
+    pub shimmy_speed: f32,
+    /// How long climbing up onto the ledge takes, in seconds.
+    pub climb_duration: f32,
+    /// How long after letting go of a ledge before the character can grab one again, in seconds.
+    pub regrab_delay: f32,
+    /// Walking off a ledge at this speed or slower grabs it on the way down, turning around to hang from it. Zero disables
+    /// it. Walking off a ledge at any speed, or standing near one, while [`grabbing`](ControllerInput::grabbing) does the
+    /// same.
+    pub drop_down_speed: f32,
+    /// Whether to grab ledges found while falling by itself. If `false`, they are only grabbed while
+    /// [`grabbing`](ControllerInput::grabbing).
+    pub auto_grab: bool,
+    #[reflect(ignore)]
+    pub(crate) state: LedgeHangState,
+    #[reflect(ignore)]
+    pub(crate) detected: Option<Ledge>,
+    pub(crate) regrab_timer: Countdown,
+    pub(crate) jump_held: bool,
+    pub(crate) grab_held: bool,
+    pub(crate) grab_buffer_timer: Countdown,
+    pub(crate) mantle_buffer_timer: Countdown,
+}
+
+impl Default for LedgeHang {
+    fn default() -> Self {
+        Self {
+            hand_height: 1.0,
+            reach: 0.3,
+            grab_distance: 0.8,
+            hang_offset: 0.55,
+            shimmy_speed: 1.5,
+            climb_duration: 0.6,
+            regrab_delay: 0.3,
+            drop_down_speed: 0.0,
+            auto_grab: true,
+            state: default(),
+            detected: None,
+            regrab_timer: default(),
+            jump_held: false,
+            grab_held: false,
+            grab_buffer_timer: default(),
+            mantle_buffer_timer: default(),
+        }
+    }
+}
+
+impl LedgeHang {
+    /// The ledge the character is hanging from, if any.
+    pub fn ledge(&self) -> Option<&Ledge> {
+        match &self.state {
+            LedgeHangState::Hanging(ledge) => Some(ledge),
+            _ => None,
+        }
+    }
+
+    /// The ledge within reach of the character's hands that it didn't grab, if any. See
+    /// [`auto_grab`](LedgeHang::auto_grab).
+    pub fn detected(&self) -> Option<&Ledge> {
+        self.detected.as_ref()
+    }
+
+    /// Whether the character is hanging from a ledge.
+    pub fn is_hanging(&self) -> bool {
+        self.ledge().is_some()
+    }
+
+    /// Whether the character is climbing up onto a ledge.
+    pub fn is_climbing(&self) -> bool {
+        matches!(self.state, LedgeHangState::Climbing(_))
+    }
+}
+
+/// A ledge a character hangs from. See [`LedgeHang`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ledge {
+    /// The collider the ledge belongs to.
+    pub entity: Entity,
+    /// Where on the edge the character's hands are.
+    pub anchor: Vec3,
+    /// The normal of the wall below the edge, perpendicular to the character's up vector.
+    pub normal: Vec3,
+    /// The direction the edge runs in, between the wall and the top of the ledge.
+    pub along: Vec3,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) enum LedgeHangState {
+    #[default]
+    Free,
+    Hanging(Ledge),
+    Climbing(ClimbPath),
+}
+
+/// The path of a climb up onto a ledge: up over the edge first, then forward onto the top.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ClimbPath {
+    pub(crate) start: Vec3,
+    pub(crate) end: Vec3,
+    pub(crate) up: Vec3,
+    pub(crate) elapsed: f32,
+    pub(crate) duration: f32,
+}
+
+impl ClimbPath {
+    /// The point on the path `t` of the way through it, from 0.0 to 1.0.
+    pub(crate) fn at(&self, t: f32) -> Vec3 {
+        let smooth = |x: f32| {
+            let x = x.clamp(0.0, 1.0);
+            x * x * (3.0 - 2.0 * x)
+        };
+        let offset = self.end - self.start;
+        let rise = self.up * offset.dot(self.up);
+        self.start + rise * smooth(t / 0.6) + (offset - rise) * smooth((t - 0.4) / 0.6)
+    }
+}
+
+/// What a surface is made of, e.g. `"grass"` or `"metal"`. Add this to colliders so effects like footstep sounds can tell the
+/// ground a character walks on apart.
+#[derive(Component, Default, Clone, Debug, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct SurfaceMaterial(pub String);
+
+/// How much grip a surface gives characters standing on it, scaling their [`TractionSettings::limit`]. E.g. 0.2 for ice.
+/// Add this to colliders.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct SurfaceTraction(pub f32);
+
+impl Default for SurfaceTraction {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// A body of water or other fluid that characters swim in, see [`SwimSettings`]. Add this to a sensor collider filling the
+/// volume of the fluid.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct FluidVolume {
+    /// How dense the fluid is compared to characters. At 1.0 a character floats just below the surface, and above it,
+    /// part of the character sticks out.
+    pub density: f32,
+    /// How much of a swimming character's velocity the fluid takes away per second.
+    pub drag: f32,
+    /// How high the surface of the fluid is above the origin of this entity, along each character's
+    /// [`up_vector`](ControllerSettings::up_vector). E.g. the half height of a cuboid collider.
+    pub surface_height: f32,
+}
+
+impl Default for FluidVolume {
+    fn default() -> Self {
+        Self {
+            density: 1.2,
+            drag: 1.0,
+            surface_height: 0.0,
+        }
+    }
+}
+
+/// A ladder, vine-covered wall or other surface characters can climb, see [`ClimbSettings`]. Add this to a sensor collider
+/// filling the space in front of the surface that characters climb in.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct LadderVolume {
+    /// The direction the ladder faces, towards the characters climbing it, in this entity's local space.
+    pub normal: Vec3,
+    /// The direction up the ladder, in this entity's local space.
+    pub axis: Vec3,
+}
+
+impl Default for LadderVolume {
+    fn default() -> Self {
+        Self {
+            normal: Vec3::Z,
+            axis: Vec3::Y,
+        }
+    }
+}
+
+/// A point on a surface where something happened to a character, e.g. where it landed, for spawning effects like dust.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect)]
+pub struct ContactTransform {
+    /// Where on the surface, in world space.
+    pub position: Vec3,
+    /// The normal of the surface there.
+    pub normal: Vec3,
+}
+
+impl ContactTransform {
+    /// A transform at [`position`](ContactTransform::position), rotated so its local Y axis points along
+    /// [`normal`](ContactTransform::normal).
+    pub fn transform(&self) -> Transform {
+        Transform::from_translation(self.position).with_rotation(Quat::from_rotation_arc(
+            Vec3::Y,
+            self.normal.normalize_or_zero(),
+        ))
+    }
+}
+
+/// How a surface hit by the ground cast was classified. See [`GroundCastInfo::surface`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect_value(PartialEq)]
+pub enum GroundSurface {
+    /// Flat enough to stand on, within [`GroundCastSettings::max_ground_angle`].
+    Flat,
+    /// Too steep, but the edge of a step no higher than [`GroundCastSettings::step_height`], so the character stands on
+    /// the top of the step.
+    Step,
+    /// Too steep, and not a step, so the character slides off of it.
+    Slope,
+}
+
+/// How the ground was lost from under a character. See [`GroundCastInfo::lost`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+#[reflect_value(PartialEq)]
+pub enum GroundLost {
+    /// The ground entity was despawned.
+    Despawned,
+    /// The ground moved out from under the character while it was standing still.
+    MovedAway,
+}
+
+/// How a character standing on a moving platform is carried along with it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect_value(PartialEq)]
+pub enum PlatformCarry {
+    /// The character inherits the velocity of the ground it is standing on.
+    #[default]
+    Velocity,
+    /// The character's transform is temporarily parented to any non-fixed rigidbody it stands on, so it moves with it
+    /// exactly. Velocity is not inherited from the platform while parented. This is more stable than [`PlatformCarry::Velocity`]
+    /// for platforms with abrupt stops, such as elevators.
+    ///
+    /// The character is unparented again when it jumps or leaves the platform.
+    Parent,
+}
+
+/// Lets a character swing from a grappling hook or rope. Add this to a character, and [`attach`](GrappleState::attach) it
+/// to an anchor point, e.g. where a hook shot hit.
+///
+/// While the rope is taut, the character is kept within [`length`](GrappleState::length) of the anchor without losing the
+/// velocity it has around it, so it swings like a pendulum. The float spring is turned off and
+/// [`ControllerInput::movement`] only pushes the swing along instead of steering towards a goal velocity, which would
+/// brake it. Set [`reel`](GrappleState::reel) to shorten or lengthen the rope.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GrappleState {
+    /// The point the rope is attached to, in world space, or `None` while not grappling.
+    pub anchor: Option<Vec3>,
+    /// How long the rope is.
+    pub length: f32,
+    /// The shortest the rope can be reeled in to.
+    pub min_length: f32,
+    /// The longest the rope can be reeled out to.
+    pub max_length: f32,
+    /// How fast the rope is reeled in or out.
+    pub reel_speed: f32,
+    /// How much to reel in, from -1.0 to reel out to 1.0 to reel in, like an input.
+    pub reel: f32,
+    /// How hard [`ControllerInput::movement`] pushes the swing along, per second.
+    pub swing_force: f32,
+    pub(crate) taut: bool,
+}
+
+impl Default for GrappleState {
+    fn default() -> Self {
+        Self {
+            anchor: None,
+            length: 0.0,
+            min_length: 1.0,
+            max_length: 30.0,
+            reel_speed: 5.0,
+            reel: 0.0,
+            swing_force: 5.0,
+            taut: false,
+        }
+    }
+}
+
+impl GrappleState {
+    /// Attach the rope to `anchor`, with its length set to the distance from `position`, the character's.
+    pub fn attach(&mut self, anchor: Vec3, position: Vec3) {
+        self.anchor = Some(anchor);
+        self.length = anchor
+            .distance(position)
+            .clamp(self.min_length, self.max_length.max(self.min_length));
+    }
+
+    /// Let go of the rope. The character keeps the velocity of its swing.
+    pub fn detach(&mut self) {
+        self.anchor = None;
+        self.taut = false;
+    }
+
+    /// Whether the rope is attached.
+    pub fn is_attached(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// Whether the rope is pulled tight, so the character is swinging from it.
+    pub fn is_taut(&self) -> bool {
+        self.taut
+    }
+}
+
+/// A sensor volume with no movement of its own, e.g. a trigger, checkpoint or prompt area, for [`VolumeOverlaps`] to
+/// report alongside fluids and ladders. Add this to a sensor collider filling the zone.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ZoneVolume;
+
+/// Which kind of volume an entry of [`VolumeOverlaps`] is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VolumeKind {
+    /// A [`FluidVolume`].
+    Fluid,
+    /// A [`LadderVolume`].
+    Ladder,
+    /// A [`ZoneVolume`].
+    Zone,
+}
+
+/// The [`FluidVolume`]s, [`LadderVolume`]s and [`ZoneVolume`]s a character's collider overlaps, for gameplay and UI such
+/// as "Press E to climb" prompts. Add this to a character to have it updated every frame.
+///
+/// Volumes are listed in the order the character entered them, oldest first. The component is only changed when the list
+/// does, so `Changed<VolumeOverlaps>` picks up entering and leaving volumes.
+#[derive(Component, Default, Debug)]
+pub struct VolumeOverlaps {
+    pub(crate) volumes: Vec<(Entity, VolumeKind)>,
+}
+
+impl VolumeOverlaps {
+    /// Every volume the character overlaps, oldest first.
+    pub fn volumes(&self) -> &[(Entity, VolumeKind)] {
+        &self.volumes
+    }
+
+    /// The volumes of `kind` the character overlaps, oldest first.
+    pub fn of_kind(&self, kind: VolumeKind) -> impl Iterator<Item = Entity> + '_ {
+        self.volumes
+            .iter()
+            .filter(move |(_, k)| *k == kind)
+            .map(|(entity, _)| *entity)
+    }
+
+    /// Whether the character overlaps `volume`.
+    pub fn contains(&self, volume: Entity) -> bool {
+        self.volumes.iter().any(|(entity, _)| *entity == volume)
+    }
+
+    /// The volume the character entered most recently, if any.
+    pub fn latest(&self) -> Option<(Entity, VolumeKind)> {
+        self.volumes.last().copied()
+    }
+}
+
+/// Marks a large moving rigidbody that characters walk around inside of, such as a train car, ship or aircraft.
+///
+/// A character that stood on it stays in its frame of reference while airborne, for as long as the vehicle is still below
+/// it. Jumps inside it keep moving and turning along with it, and accelerate with it, instead of flying off in a straight
+/// line while the vehicle moves or turns away underneath. See [`ControllerState::vehicle`].
+///
+/// Characters parented to the vehicle by [`PlatformCarry::Parent`] already move with it, and are unaffected.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct VehicleInterior {
+    /// How far above the vehicle an airborne character's origin can be and still count as inside it. Past this, or once
+    /// the vehicle is no longer below the character, such as after jumping out of its door, the character leaves it.
+    pub max_height: f32,
+}
+
+impl Default for VehicleInterior {
+    fn default() -> Self {
+        Self { max_height: 5.0 }
+    }
+}
+
+/// Pulls characters with a [`DynamicGravity`] towards it or along it, e.g. a planet or a room with gravity pointing at a
+/// wall.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect_value(Component, PartialEq)]
+pub enum GravitySource {
+    /// Pull towards this entity's origin, for characters within `radius` of it, e.g. to walk around a spherical planet.
+    /// The nearest point source wins where several overlap.
+    Point {
+        /// How far from the origin the pull reaches.
+        radius: f32,
+    },
+    /// Pull against `up`, in this entity's local space, for characters inside its collider. Add this to a sensor collider
+    /// filling the volume. Volumes win over point sources, so they can carve out areas of a planet.
+    Volume {
+        /// The up vector inside the volume, in this entity's local space.
+        up: Vec3,
+    },
+}
+
+impl Default for GravitySource {
+    fn default() -> Self {
+        Self::Point { radius: 50.0 }
+    }
+}
+
+/// Makes a character's [`up_vector`](ControllerSettings::up_vector) follow the [`GravitySource`] it's in every frame,
+/// so gravity, floating and staying upright all turn with it.
+///
+/// The up vector turns towards each new direction over time rather than snapping to it, so the character rolls over
+/// smoothly when moving between sources or around a small planet. Characters moving in a [`ReferenceFrame`] with a body
+/// follow the body instead.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct DynamicGravity {
+    /// How quickly the up vector turns towards the source's, as a fraction of the way per second. Zero snaps to it
+    /// instantly.
+    pub turn_rate: f32,
+    /// The up vector to go back to outside every source. `None` keeps the one the character had last.
+    pub fallback: Option<Vec3>,
+    pub(crate) source: Option<Entity>,
+}
+
+impl Default for DynamicGravity {
+    fn default() -> Self {
+        Self {
+            turn_rate: 5.0,
+            fallback: None,
+            source: None,
+        }
+    }
+}
+
+impl DynamicGravity {
+    /// The [`GravitySource`] the character is in, if any.
+    pub fn source(&self) -> Option<Entity> {
+        self.source
+    }
+}
+
+/// Makes a character move entirely in the frame of reference of another rigidbody, rather than the world's, such as inside
+/// a spaceship flying around.
+///
+/// Every frame, the character's [`up_vector`](ControllerSettings::up_vector) is set to [`up`](ReferenceFrame::up) turned
+/// by the body, so gravity and floating follow the body as it rolls and pitches. The character always counts as inside
+/// the body like with a [`VehicleInterior`], so it accelerates and turns along with the body even while airborne, and
+/// doesn't get slammed into walls when the body speeds up.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct ReferenceFrame {
+    /// The rigidbody whose frame the character moves in. The character moves in the world's frame while this is `None`.
+    pub body: Option<Entity>,
+    /// The character's up vector, in the body's local space.
+    pub up: Vec3,
+    /// Whether [`ControllerInput::movement`], [`ControllerInput::facing`] and [`ControllerInput::look_at`] are given in
+    /// the body's local space, rather than the world's.
+    pub local_input: bool,
+    /// The movement, facing and look at target as they were set in the body's local space, to restore once the movement
+    /// stages are done.
+    #[reflect(ignore)]
+    pub(crate) local: Option<(Vec3, Vec3, Option<Vec3>)>,
+}
+
+impl ReferenceFrame {
+    /// Move in the frame of reference of `body`.
+    pub fn new(body: Entity) -> Self {
+        Self {
+            body: Some(body),
+            ..default()
+        }
+    }
+}
+
+impl Default for ReferenceFrame {
+    fn default() -> Self {
+        Self {
+            body: None,
+            up: Vec3::Y,
+            local_input: true,
+            local: None,
+        }
+    }
+}
+
+/// Smooths the movement of a render-only child of a character controller, such as the root of its mesh, between physics steps.
+/// Useful when Rapier runs with `TimestepMode::Interpolated` at a lower rate than the game renders, and the controller body
+/// itself should stay at its physics pose.
+///
+/// The child's [`Transform`] is overwritten every frame, so set [`offset`](RenderInterpolation::offset) instead.
+/// Does nothing if the body has Rapier's `TransformInterpolation`, since the body is already interpolated then.
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct RenderInterpolation {
+    /// The transform of the child relative to the body.
+    pub offset: Transform,
+    /// The body's transform before and after the last physics step.
+    #[reflect(ignore)]
+    pub(crate) steps: Option<(Transform, Transform)>,
+}
+
+impl RenderInterpolation {
+    /// Interpolate a child placed at `offset` relative to the body.
+    pub fn new(offset: Transform) -> Self {
+        Self {
+            offset,
+            steps: None,
+        }
+    }
+}
+
+/// The region a character controller is allowed to be in, such as above a kill plane.
+/// An [`OutOfBoundsEvent`](crate::OutOfBoundsEvent) is sent when the character leaves it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ControllerBounds {
+    /// See [`BoundsShape`].
+    pub shape: BoundsShape,
+    /// Where to [teleport](crate::TeleportController) the character when it leaves its bounds, if anywhere.
+    pub respawn: Option<Vec3>,
+    /// Whether the character was out of bounds last frame, so the event is only sent once.
+    pub(crate) out: bool,
+}
+
+impl ControllerBounds {
+    /// Bounds along the character's [`up_vector`](ControllerSettings::up_vector), measured from the world origin.
+    pub fn height(min: f32, max: f32) -> Self {
+        Self::new(BoundsShape::Height { min, max })
+    }
+
+    /// Bounds within a world-space box.
+    pub fn aabb(min: Vec3, max: Vec3) -> Self {
+        Self::new(BoundsShape::Aabb { min, max })
+    }
+
+    /// Bounds of the given shape.
+    pub fn new(shape: BoundsShape) -> Self {
+        Self {
+            shape,
+            respawn: None,
+            out: false,
+        }
+    }
+
+    /// Teleport the character to `position` when it leaves its bounds.
+    pub fn with_respawn(mut self, position: Vec3) -> Self {
+        self.respawn = Some(position);
+        self
+    }
+
+    /// Whether `position` is inside these bounds.
+    pub fn contains(&self, position: Vec3, up: Vec3) -> bool {
+        match self.shape {
+            BoundsShape::Height { min, max } => {
+                let height = position.dot(up);
+                height >= min && height <= max
+            }
+            BoundsShape::Aabb { min, max } => {
+                position.cmpge(min).all() && position.cmple(max).all()
+            }
+        }
+    }
+}
+
+impl Default for ControllerBounds {
+    fn default() -> Self {
+        Self::height(f32::NEG_INFINITY, f32::INFINITY)
+    }
+}
+
+/// The shape of a [`ControllerBounds`].
+#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+#[reflect_value(PartialEq)]
+pub enum BoundsShape {
+    /// Between two heights along the character's [`up_vector`](ControllerSettings::up_vector).
+    Height {
+        /// The lowest the character can be, e.g. a kill plane.
+        min: f32,
+        /// The highest the character can be.
+        max: f32,
+    },
+    /// Within a world-space axis-aligned box.
+    Aabb {
+        /// The corner of the box with the lowest coordinates.
+        min: Vec3,
+        /// The corner of the box with the highest coordinates.
+        max: Vec3,
+    },
+}
+
 /// This is the interface for applying input to the character controller.
 /// See each field for more information.
 #[derive(Component, Default, Reflect)]
@@ -164,11 +2995,29 @@ impl Default for ControllerSettings {
 pub struct ControllerInput {
     /// This field represents movement in 3D space.
     /// The majority of games will map this to WASD/Analog joystick in 2D space along the ground.
-    /// To ensure movement does not affect the Y axis, set [`ControllerSettings::force_scale`] to `Vec3(1.0, 0.0, 1.0)`.
+    /// To ensure movement does not affect the Y axis, set [`LocomotionSettings::force_scale`] to `Vec3(1.0, 0.0, 1.0)`.
     /// This field will be normalized when read by the movement system.
     pub movement: Vec3,
     /// This field represents if the jump control is currently pressed.
     pub jumping: bool,
+    /// Whether to crouch, see [`CrouchSettings`].
+    pub crouching: bool,
+    /// Whether to sprint, see [`SprintSettings`].
+    pub sprinting: bool,
+    /// Whether the dash control is pressed. A dash starts when it is first pressed, see [`DashSettings`].
+    pub dashing: bool,
+    /// Whether to thrust upwards, see [`Jetpack`].
+    pub thrusting: bool,
+    /// Whether to let go of the ledge the character is hanging from, see [`LedgeHang`].
+    pub dropping: bool,
+    /// Whether to grab the edge of the ground the character stands on or walks off of, and climb down to hang from it. See
+    /// [`LedgeHang::drop_down_speed`].
+    pub grabbing: bool,
+    /// The direction to face when [`FacingSettings::mode`] is [`FacingMode::Locked`]. Ignored if zero.
+    pub facing: Vec3,
+    /// A point to turn towards, e.g. the nearest enemy when locked on, while still moving freely.
+    /// Only has an effect if [`FacingSettings::mode`] is not [`FacingMode::None`]. See [`FacingSettings::look_at_strength`].
+    pub look_at: Option<Vec3>,
     /// Allows supplying a custom force to the controller to be applied next frame,
     /// which is necessary because the controller monopolizes and controls the [`ExternalImpulse`]
     /// which rapier uses to apply impulse forces to a rigidbody.
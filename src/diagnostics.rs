@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The work counted for [`WanderlustDiagnosticsPlugin`].
+#[derive(Clone, Copy)]
+pub(crate) enum Counter {
+    /// Ground casts actually cast, rather than reused from the cache.
+    GroundCasts,
+    /// Ground casts reused from the cache, see [`GroundCastSettings::cache_tolerance`](crate::GroundCastSettings::cache_tolerance).
+    GroundCacheHits,
+    /// Calls to [`RapierContext::cast_shape`](bevy_rapier3d::prelude::RapierContext::cast_shape). A single ground cast takes
+    /// one for each collider it passes through, plus one.
+    ShapeCasts,
+    /// Calls to [`RapierContext::cast_ray`](bevy_rapier3d::prelude::RapierContext::cast_ray) and its variants.
+    Raycasts,
+    /// Characters moved this frame, see [`ControllerFrame::active`](crate::ControllerFrame::active).
+    CharactersMoved,
+}
+
+static COUNTERS: [AtomicU32; 5] = [
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+    AtomicU32::new(0),
+];
+
+/// Counts `n` units of work. Does nothing without the `profiling` feature.
+#[inline]
+pub(crate) fn count(counter: Counter, n: u32) {
+    if cfg!(feature = "profiling") {
+        COUNTERS[counter as usize].fetch_add(n, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "profiling")]
+pub use self::plugin::WanderlustDiagnosticsPlugin;
+
+#[cfg(feature = "profiling")]
+mod plugin {
+    use super::{Counter, COUNTERS};
+    use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+    use bevy::prelude::*;
+    use std::sync::atomic::Ordering;
+
+    /// Reports how much work the character controllers do each frame as [`Diagnostics`], e.g. to compare ground detection
+    /// settings with real data. Add `bevy`'s `LogDiagnosticsPlugin` to print them.
+    ///
+    /// Requires the `profiling` feature. The counts are shared by every [`App`] in the process.
+    pub struct WanderlustDiagnosticsPlugin;
+
+    impl WanderlustDiagnosticsPlugin {
+        /// Ground casts actually cast, rather than reused from the cache.
+        pub const GROUND_CASTS: DiagnosticId =
+            DiagnosticId::from_u128(204689447105961716843893284315374305141);
+        /// Ground casts reused from the cache, see [`GroundCastSettings::cache_tolerance`](crate::GroundCastSettings::cache_tolerance).
+        pub const GROUND_CACHE_HITS: DiagnosticId =
+            DiagnosticId::from_u128(62391734408713066395148613473260217925);
+        /// Shape casts against the physics world. A single ground cast takes one for each collider it passes through, plus one.
+        pub const SHAPE_CASTS: DiagnosticId =
+            DiagnosticId::from_u128(289934130167215702618377400158962741017);
+        /// Raycasts against the physics world, e.g. for [`FootPlacement`](crate::FootPlacement).
+        pub const RAYCASTS: DiagnosticId =
+            DiagnosticId::from_u128(110519284306624773826617153095813850650);
+        /// Characters moved this frame, which is less than all of them with [`ControllerLod`](crate::ControllerLod) or
+        /// [`ControllerActivation`](crate::ControllerActivation).
+        pub const CHARACTERS_MOVED: DiagnosticId =
+            DiagnosticId::from_u128(17838045329514474914436830853046458457);
+
+        const DIAGNOSTICS: [(DiagnosticId, Counter, &'static str); 5] = [
+            (
+                Self::GROUND_CASTS,
+                Counter::GroundCasts,
+                "wanderlust_ground_casts",
+            ),
+            (
+                Self::GROUND_CACHE_HITS,
+                Counter::GroundCacheHits,
+                "wanderlust_ground_cache_hits",
+            ),
+            (
+                Self::SHAPE_CASTS,
+                Counter::ShapeCasts,
+                "wanderlust_shape_casts",
+            ),
+            (Self::RAYCASTS, Counter::Raycasts, "wanderlust_raycasts"),
+            (
+                Self::CHARACTERS_MOVED,
+                Counter::CharactersMoved,
+                "wanderlust_characters_moved",
+            ),
+        ];
+    }
+
+    impl Plugin for WanderlustDiagnosticsPlugin {
+        fn build(&self, app: &mut App) {
+            app.add_startup_system(setup_diagnostics)
+                .add_system_to_stage(CoreStage::Last, measure_diagnostics);
+        }
+    }
+
+    fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+        for (id, _, name) in WanderlustDiagnosticsPlugin::DIAGNOSTICS {
+            diagnostics.add(Diagnostic::new(id, name, 20));
+        }
+    }
+
+    fn measure_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+        for (id, counter, _) in WanderlustDiagnosticsPlugin::DIAGNOSTICS {
+            let value = COUNTERS[counter as usize].swap(0, Ordering::Relaxed);
+            diagnostics.add_measurement(id, || value as f64);
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use crate::components::Countdown;
+use crate::systems::{float_spring_force, held_jump_force};
+use crate::{CharacterControllerPreset, ControllerSettings, FloatSettings, JumpSettings};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// The 2D [character controller](ControllerState2d) plugin, built on `bevy_rapier2d`. Necessary to have 2D character
+/// controllers work.
+///
+/// The 2D controller covers the core of the 3D one: floating over the ground, running, and jumping with coyote time, jump
+/// buffering and extra jumps, tuned with the same [`FloatSettings`] and [`JumpSettings`]. Its characters don't rotate, so
+/// keep their rotation locked, as [`CharacterController2dBundle`] does.
+///
+/// Requires the `rapier2d` feature, and `bevy_rapier2d`'s `RapierPhysicsPlugin`.
+pub struct Wanderlust2dPlugin;
+
+impl Plugin for Wanderlust2dPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ControllerState2d>()
+            .register_type::<ControllerSettings2d>()
+            .register_type::<GroundCastSettings2d>()
+            .register_type::<ControllerInput2d>()
+            .add_system(movement_2d);
+    }
+}
+
+/// The 2D equivalent of [`ControllerState`](crate::ControllerState).
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ControllerState2d {
+    /// The velocity along the ground that the character was trying to reach last frame.
+    pub last_goal_velocity: f32,
+    pub(crate) skip_ground_check_timer: Countdown,
+    pub(crate) jump_timer: Countdown,
+    /// Was [`ControllerInput2d::jumping`] pressed last frame.
+    pub jump_pressed_last_frame: bool,
+    pub(crate) coyote_timer: Countdown,
+    pub(crate) jump_buffer_timer: Countdown,
+    /// How many extra jumps are remaining.
+    pub remaining_jumps: u32,
+    /// Was the character grounded at the end of last frame.
+    pub is_grounded: bool,
+    /// The ground entity the character was standing on last frame, if any.
+    pub last_ground: Option<Entity>,
+}
+
+/// The 2D equivalent of [`ControllerSettings`].
+#[derive(Component, Clone, Reflect)]
+#[reflect(Component)]
+pub struct ControllerSettings2d {
+    /// The direction to jump and float in, and opposite of which gravity pulls.
+    pub up_vector: Vec2,
+    /// How strongly the character is pulled down while airborne.
+    pub gravity: f32,
+    /// How quickly to reach [`max_speed`](ControllerSettings2d::max_speed).
+    pub acceleration: f32,
+    /// The fastest the character moves along the ground by itself.
+    pub max_speed: f32,
+    /// The most force that can be applied at once to reach the goal velocity.
+    pub max_acceleration_force: f32,
+    /// See [`JumpSettings`]. [`ledge_forgiveness`](JumpSettings::ledge_forgiveness) is not supported in 2D.
+    pub jump: JumpSettings,
+    /// See [`FloatSettings`]. Moving ground is not tracked in 2D, so the ground velocity settings are unused.
+    pub float: FloatSettings,
+    /// See [`GroundCastSettings2d`].
+    pub ground_cast: GroundCastSettings2d,
+}
+
+impl Default for ControllerSettings2d {
+    fn default() -> Self {
+        Self::character()
+    }
+}
+
+impl ControllerSettings2d {
+    /// A platformer character, tuned like [`CharacterControllerPreset`].
+    pub fn character() -> Self {
+        let ControllerSettings {
+            gravity,
+            locomotion,
+            jump,
+            float,
+            ground_cast,
+            ..
+        } = CharacterControllerPreset.into();
+
+        Self {
+            up_vector: Vec2::Y,
+            gravity,
+            acceleration: locomotion.acceleration,
+            max_speed: locomotion.max_speed,
+            max_acceleration_force: locomotion.max_acceleration_force,
+            jump,
+            float,
+            ground_cast: GroundCastSettings2d {
+                length: ground_cast.length,
+                collider: Collider::ball(0.45),
+                max_ground_angle: ground_cast.max_ground_angle,
+                ..default()
+            },
+        }
+    }
+}
+
+/// The 2D equivalent of [`GroundCastSettings`](crate::GroundCastSettings). Part of [`ControllerSettings2d`].
+#[derive(Clone, Reflect)]
+pub struct GroundCastSettings2d {
+    /// How far down to look for the ground.
+    pub length: f32,
+    /// Where to start looking for the ground from, relative to the character's origin.
+    pub origin: Vec2,
+    /// What shape to cast to look for the ground.
+    #[reflect(ignore)]
+    pub collider: Collider,
+    /// The steepest ground, in radians, that the character can stand on.
+    pub max_ground_angle: f32,
+}
+
+impl Default for GroundCastSettings2d {
+    fn default() -> Self {
+        Self {
+            length: default(),
+            origin: default(),
+            collider: Collider::ball(1.0),
+            max_ground_angle: default(),
+        }
+    }
+}
+
+/// The 2D equivalent of [`ControllerInput`](crate::ControllerInput).
+#[derive(Component, Default, Reflect)]
+#[reflect(Component)]
+pub struct ControllerInput2d {
+    /// How fast to move along the ground, from -1.0 (full speed left) to 1.0 (full speed right), relative to
+    /// [`up_vector`](ControllerSettings2d::up_vector).
+    pub movement: f32,
+    /// Whether the jump button is held.
+    pub jumping: bool,
+    /// An additional impulse to apply this frame.
+    pub custom_impulse: Vec2,
+}
+
+/// Contains the components needed for a 2D character controller, with reasonable default values.
+#[derive(Bundle)]
+pub struct CharacterController2dBundle {
+    /// See [`ControllerState2d`].
+    pub controller: ControllerState2d,
+    /// See [`ControllerSettings2d`].
+    pub settings: ControllerSettings2d,
+    /// See [`ControllerInput2d`].
+    pub input: ControllerInput2d,
+    /// See [`RigidBody`].
+    pub rigidbody: RigidBody,
+    /// See [`Collider`].
+    pub collider: Collider,
+    /// See [`Velocity`].
+    pub velocity: Velocity,
+    /// See [`GravityScale`].
+    pub gravity: GravityScale,
+    /// See [`ExternalImpulse`].
+    pub force: ExternalImpulse,
+    /// See [`LockedAxes`].
+    pub locked_axes: LockedAxes,
+    /// See [`Friction`].
+    pub friction: Friction,
+    /// See [`Transform`].
+    pub transform: Transform,
+    /// See [`GlobalTransform`].
+    pub global_transform: GlobalTransform,
+}
+
+impl Default for CharacterController2dBundle {
+    fn default() -> Self {
+        Self {
+            controller: default(),
+            settings: default(),
+            input: default(),
+            rigidbody: default(),
+            collider: Collider::capsule_y(0.25, 0.5),
+            velocity: default(),
+            gravity: GravityScale(0.0),
+            force: default(),
+            locked_axes: LockedAxes::ROTATION_LOCKED,
+            friction: Friction {
+                coefficient: 0.0,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+            transform: default(),
+            global_transform: default(),
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`Wanderlust2dPlugin`] instead.*
+///
+/// The 2D equivalent of the movement stages, see [`MovementSet`](crate::MovementSet).
+pub fn movement_2d(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut ExternalImpulse,
+        &mut ControllerState2d,
+        &ControllerSettings2d,
+        &mut ControllerInput2d,
+    )>,
+    velocities: Query<&Velocity>,
+    time: Res<Time>,
+    ctx: Res<RapierContext>,
+) {
+    let dt = time.delta_seconds();
+    if dt == 0.0 {
+        return;
+    }
+
+    for (entity, tf, mut body, mut controller, settings, mut input) in bodies.iter_mut() {
+        let velocity = velocities
+            .get(entity)
+            .expect("Character controllers must have a Velocity component")
+            .linvel;
+        let up = settings.up_vector;
+        let right = -up.perp();
+
+        let ground_cast = if !controller.skip_ground_check_timer.is_running() {
+            ctx.cast_shape(
+                tf.translation().truncate() + settings.ground_cast.origin,
+                0.0,
+                -up,
+                &settings.ground_cast.collider,
+                settings.ground_cast.length,
+                QueryFilter::new()
+                    .predicate(&|collider| collider != entity)
+                    .exclude_sensors(),
+            )
+            .filter(|(_, toi)| {
+                toi.status != TOIStatus::Penetrating
+                    && toi.normal1.angle_between(up).abs() <= settings.ground_cast.max_ground_angle
+            })
+        } else {
+            controller.skip_ground_check_timer.tick(dt);
+            None
+        };
+
+        let offset = ground_cast.map(|(_, toi)| toi.toi - settings.float.distance);
+        let grounded = offset.is_some_and(|offset| {
+            offset <= settings.float.max_offset && offset >= settings.float.min_offset
+        });
+        if grounded {
+            controller.remaining_jumps = settings.jump.extra_jumps;
+            controller
+                .coyote_timer
+                .start(settings.jump.coyote_time_duration);
+        } else {
+            controller.coyote_timer.tick(dt);
+        }
+
+        let ground_vel = ground_cast
+            .and_then(|(ground, _)| velocities.get(ground).ok())
+            .map_or(Vec2::ZERO, |v| v.linvel);
+
+        let gravity = if ground_cast.is_none() {
+            -up * settings.gravity * dt
+        } else {
+            Vec2::ZERO
+        };
+
+        let mut float_spring = match offset {
+            Some(offset) => {
+                let relative_speed = (-up).dot(velocity - ground_vel);
+                -up * float_spring_force(&settings.float, offset, relative_speed)
+            }
+            None => Vec2::ZERO,
+        };
+
+        let movement = {
+            let input_goal_vel = input.movement.clamp(-1.0, 1.0) * settings.max_speed;
+            let goal_vel = controller.last_goal_velocity
+                + (input_goal_vel + right.dot(ground_vel) - controller.last_goal_velocity)
+                    * (settings.acceleration * dt).min(1.0);
+            controller.last_goal_velocity = goal_vel;
+
+            right
+                * (goal_vel - right.dot(velocity)).clamp(
+                    -settings.max_acceleration_force,
+                    settings.max_acceleration_force,
+                )
+        };
+
+        let just_jumped = input.jumping && !controller.jump_pressed_last_frame;
+        if !grounded {
+            if just_jumped {
+                controller
+                    .jump_buffer_timer
+                    .start(settings.jump.buffer_duration);
+            } else {
+                controller.jump_buffer_timer.tick(dt);
+            }
+        }
+
+        let mut jump = if controller.jump_timer.is_running() && !grounded {
+            if !input.jumping {
+                controller.jump_timer.stop();
+                up * velocity.dot(up) * -settings.jump.stop_force
+            } else {
+                controller.jump_timer.tick(dt);
+                float_spring = Vec2::ZERO;
+                up * held_jump_force(&settings.jump, controller.jump_timer.remaining()) * dt
+            }
+        } else {
+            Vec2::ZERO
+        };
+
+        let mut jumped = false;
+        if (just_jumped || controller.jump_buffer_timer.is_running())
+            && (grounded || controller.coyote_timer.is_running() || controller.remaining_jumps > 0)
+        {
+            if !grounded && !controller.coyote_timer.is_running() {
+                controller.remaining_jumps -= 1;
+            }
+
+            controller.jump_buffer_timer.stop();
+            controller.jump_timer.start(settings.jump.time);
+            controller
+                .skip_ground_check_timer
+                .start(settings.jump.skip_ground_check_duration);
+            // Negating the vertical velocity relative to the ground keeps jumps consistent, as in 3D.
+            jump = -up * (velocity - ground_vel).dot(up) + up * settings.jump.initial_force;
+            float_spring = Vec2::ZERO;
+            jumped = true;
+        }
+
+        body.impulse = movement + jump + float_spring + gravity + input.custom_impulse;
+        input.custom_impulse = Vec2::ZERO;
+
+        controller.jump_pressed_last_frame = input.jumping;
+        controller.last_ground = ground_cast.map(|(ground, _)| ground);
+        controller.is_grounded = grounded && !jumped;
+    }
+}
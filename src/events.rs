@@ -0,0 +1,396 @@
+use crate::components::{ContactTransform, GroundLost, Ledge, MovementBlockedReason};
+use bevy::prelude::*;
+
+/// Sent when a character controller stops being grounded.
+#[derive(Debug, Clone)]
+pub struct UngroundedEvent {
+    /// The character controller that became airborne.
+    pub entity: Entity,
+    /// The ground the character was standing on before becoming airborne.
+    pub ground: Option<Entity>,
+    /// Why the character became airborne.
+    pub cause: UngroundedCause,
+    /// Where the character last touched the ground.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character controller becomes grounded, for any reason. See [`LandedEvent`] for only landing after being
+/// airborne.
+#[derive(Debug, Clone)]
+pub struct GroundedEvent {
+    /// The character controller that became grounded.
+    pub entity: Entity,
+    /// The ground the character is standing on.
+    pub ground: Option<Entity>,
+    /// Where the character touches the ground.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character controller lands on the ground after being airborne for at least a frame, along with a
+/// [`GroundedEvent`]. Useful for landing sounds or fall damage.
+#[derive(Debug, Clone)]
+pub struct LandedEvent {
+    /// The character controller that landed.
+    pub entity: Entity,
+    /// The ground the character landed on.
+    pub ground: Option<Entity>,
+    /// How fast the character was moving towards the ground, relative to the ground's own velocity, when it landed.
+    pub impact_speed: f32,
+    /// Where the character touched the ground.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character controller jumps.
+#[derive(Debug, Clone)]
+pub struct JumpEvent {
+    /// The character controller that jumped.
+    pub entity: Entity,
+    /// What kind of jump it was.
+    pub kind: JumpKind,
+    /// How many extra jumps the character has left, see [`JumpSettings::extra_jumps`](crate::JumpSettings::extra_jumps).
+    pub remaining_jumps: u32,
+    /// The ground the character jumped off of, if any, the wall for [`JumpKind::Wall`], the fluid for
+    /// [`JumpKind::Swim`], or the ladder for [`JumpKind::Climb`].
+    pub ground: Option<Entity>,
+    /// Where the character last touched the ground, touched the wall for [`JumpKind::Wall`], the surface of the fluid
+    /// for [`JumpKind::Swim`], or where the character held onto the ladder for [`JumpKind::Climb`].
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character controller starts dashing. See [`DashSettings`](crate::DashSettings).
+#[derive(Debug, Clone)]
+pub struct DashEvent {
+    /// The character controller that dashed.
+    pub entity: Entity,
+    /// The direction of the dash.
+    pub direction: Vec3,
+}
+
+/// What kind of jump a character made. See [`JumpEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JumpKind {
+    /// A jump off of the ground, pressed this frame.
+    Grounded,
+    /// A jump off of the ground that was pressed shortly before landing, see
+    /// [`JumpSettings::buffer_duration`](crate::JumpSettings::buffer_duration), or queued as a
+    /// [`ControllerIntent`](crate::ControllerIntent).
+    Buffered,
+    /// A jump shortly after leaving the ground, see
+    /// [`JumpSettings::coyote_time_duration`](crate::JumpSettings::coyote_time_duration).
+    Coyote,
+    /// An extra jump in the air, see [`JumpSettings::extra_jumps`](crate::JumpSettings::extra_jumps).
+    Air,
+    /// A jump off of a wall, see [`WallSettings`](crate::WallSettings).
+    Wall,
+    /// A hop out of a fluid near its surface, see [`SwimSettings`](crate::SwimSettings).
+    Swim,
+    /// A jump off of a ladder, see [`ClimbSettings`](crate::ClimbSettings).
+    Climb,
+}
+
+/// Sent when a character controller starts or stops sliding. See [`ControllerState::sliding`](crate::ControllerState::sliding).
+#[derive(Debug, Clone)]
+pub struct SlidingChangedEvent {
+    /// The character controller that started or stopped sliding.
+    pub entity: Entity,
+    /// Whether the character started (`true`) or stopped (`false`) sliding.
+    pub sliding: bool,
+    /// Where the character touches the surface it slides on, or last touched the ground if it stopped sliding.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character starts or stops skidding. See [`TractionSettings`](crate::TractionSettings).
+#[derive(Debug, Clone)]
+pub struct SkiddingChangedEvent {
+    /// The character controller that started or stopped skidding.
+    pub entity: Entity,
+    /// Whether the character started (`true`) or stopped (`false`) skidding.
+    pub skidding: bool,
+    /// Where the character touches the ground, or last touched it if it stopped skidding by leaving the ground.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character starts or stops sliding down a wall. See [`WallSettings::slide_speed`](crate::WallSettings::slide_speed).
+#[derive(Debug, Clone)]
+pub struct WallSlidingChangedEvent {
+    /// The character controller that started or stopped sliding down a wall.
+    pub entity: Entity,
+    /// The wall the character is sliding down, or is still next to if it stopped.
+    pub wall: Option<Entity>,
+    /// Whether the character started (`true`) or stopped (`false`) sliding.
+    pub sliding: bool,
+    /// Where the character touches the wall, or last touched it if it stopped sliding.
+    pub contact: ContactTransform,
+}
+
+/// Sent when a character starts or stops swimming. See [`SwimSettings`](crate::SwimSettings).
+#[derive(Debug, Clone)]
+pub struct SwimmingChangedEvent {
+    /// The character controller that started or stopped swimming.
+    pub entity: Entity,
+    /// The [`FluidVolume`](crate::FluidVolume) the character is swimming in, or was swimming in if it stopped.
+    pub volume: Entity,
+    /// Whether the character started (`true`) or stopped (`false`) swimming.
+    pub swimming: bool,
+}
+
+/// Sent when a character starts or stops gliding. See [`GlideSettings`](crate::GlideSettings).
+#[derive(Debug, Clone)]
+pub struct GlidingChangedEvent {
+    /// The character controller that started or stopped gliding.
+    pub entity: Entity,
+    /// Whether the character started (`true`) or stopped (`false`) gliding.
+    pub gliding: bool,
+}
+
+/// Sent when a character grabs onto or lets go of a ladder. See [`ClimbSettings`](crate::ClimbSettings).
+#[derive(Debug, Clone)]
+pub struct ClimbingChangedEvent {
+    /// The character controller that grabbed onto or let go of a ladder.
+    pub entity: Entity,
+    /// The [`LadderVolume`](crate::LadderVolume) the character is climbing, or was climbing if it let go.
+    pub ladder: Entity,
+    /// Whether the character started (`true`) or stopped (`false`) climbing.
+    pub climbing: bool,
+}
+
+/// Sent when a character starts vaulting over an obstacle. See [`Vault`](crate::Vault).
+#[derive(Debug, Clone)]
+pub struct VaultStartedEvent {
+    /// The character controller that started vaulting.
+    pub entity: Entity,
+    /// The obstacle being vaulted over.
+    pub obstacle: Entity,
+    /// The top of the obstacle in front of the character, e.g. for placing its hand.
+    pub contact: ContactTransform,
+    /// Where the character will be once the vault is done.
+    pub end: Vec3,
+    /// How long the vault will take, in seconds.
+    pub duration: f32,
+}
+
+/// Sent when a character finishes vaulting over an obstacle. See [`Vault`](crate::Vault).
+#[derive(Debug, Clone)]
+pub struct VaultFinishedEvent {
+    /// The character controller that finished vaulting.
+    pub entity: Entity,
+}
+
+/// Sent when a character starts a [`ControllerCommand::JumpTo`](crate::ControllerCommand::JumpTo).
+#[derive(Debug, Clone)]
+pub struct JumpLinkStartedEvent {
+    /// The character controller that jumped.
+    pub entity: Entity,
+    /// The velocity the character jumped with.
+    pub velocity: Vec3,
+    /// How long the character will be in the air, in seconds.
+    pub duration: f32,
+}
+
+/// Sent when a character lands at the end of a [`ControllerCommand::JumpTo`](crate::ControllerCommand::JumpTo).
+#[derive(Debug, Clone)]
+pub struct JumpLinkFinishedEvent {
+    /// The character controller that landed.
+    pub entity: Entity,
+}
+
+/// Sent when a [`ControllerCommand::JumpTo`](crate::ControllerCommand::JumpTo) is dropped without jumping, because
+/// the character would hit something along the way or has no gravity to come back down with.
+#[derive(Debug, Clone)]
+pub struct JumpLinkFailedEvent {
+    /// The character controller that couldn't jump.
+    pub entity: Entity,
+    /// Where the character was asked to jump to.
+    pub target: Vec3,
+    /// What's in the way, if anything.
+    pub obstacle: Option<Entity>,
+}
+
+/// Sent when a ledge comes within reach of a character that isn't hanging from one, whether or not it grabs it. See
+/// [`LedgeHang::auto_grab`](crate::LedgeHang::auto_grab).
+#[derive(Debug, Clone)]
+pub struct LedgeDetectedEvent {
+    /// The character controller that found the ledge.
+    pub entity: Entity,
+    /// The ledge that was found.
+    pub ledge: Ledge,
+}
+
+/// Sent when a character grabs a ledge. See [`LedgeHang`](crate::LedgeHang).
+#[derive(Debug, Clone)]
+pub struct LedgeGrabbedEvent {
+    /// The character controller that grabbed the ledge.
+    pub entity: Entity,
+    /// The ledge that was grabbed.
+    pub ledge: Ledge,
+}
+
+/// Sent when a character stops hanging from a ledge. See [`LedgeHang`](crate::LedgeHang).
+#[derive(Debug, Clone)]
+pub struct LedgeReleasedEvent {
+    /// The character controller that let go of the ledge.
+    pub entity: Entity,
+    /// The ledge it let go of.
+    pub ledge: Ledge,
+    /// Why it let go.
+    pub cause: LedgeReleaseCause,
+}
+
+/// Why a character stopped hanging from a ledge. See [`LedgeReleasedEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgeReleaseCause {
+    /// The character started climbing up onto the ledge.
+    ClimbedUp,
+    /// The character let go of the ledge, see [`ControllerInput::dropping`](crate::ControllerInput::dropping).
+    Dropped,
+    /// The ledge couldn't be found anymore, e.g. because it moved away or was despawned, or the character touched the
+    /// ground.
+    Lost,
+}
+
+/// Why a character controller became airborne. See [`UngroundedEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UngroundedCause {
+    /// The character moved off of the ground by itself, e.g. by walking off a ledge.
+    WalkedOff,
+    /// The ground was despawned or moved out from under the character, e.g. a crumbling or falling platform.
+    GroundLost(GroundLost),
+    /// The character jumped.
+    Jumped,
+}
+
+/// Sent when a character starts switching to a different stance. See [`ControllerStances`](crate::ControllerStances).
+#[derive(Debug, Clone)]
+pub struct StanceChangedEvent {
+    /// The character controller that changed stance.
+    pub entity: Entity,
+    /// The previous stance, if any.
+    pub from: Option<String>,
+    /// The new stance.
+    pub to: String,
+    /// How long the settings will take to blend to the new stance.
+    pub blend_time: f32,
+}
+
+/// Sent when a character controller has been squeezed between colliders for
+/// [`CrushSettings::duration`](crate::CrushSettings::duration), such as when caught between a moving platform and a ceiling.
+///
+/// Useful for killing the character, in addition to (or instead of) the automatic [`CrushSettings::response`](crate::CrushSettings::response).
+#[derive(Debug, Clone)]
+pub struct CrushedEvent {
+    /// The character controller that was crushed.
+    pub entity: Entity,
+    /// The two colliders pressing into the character from opposite sides.
+    pub colliders: (Entity, Entity),
+}
+
+/// Sent when a character controller moving faster than [`RecoverySettings::max_speed`](crate::RecoverySettings::max_speed)
+/// is recovered.
+#[derive(Debug, Clone)]
+pub struct ControllerRecoveredEvent {
+    /// The character controller that was recovered.
+    pub entity: Entity,
+    /// How fast the character was moving relative to its ground.
+    pub speed: f32,
+    /// Where the character was moved back to, if [`RecoverySettings::teleport`](crate::RecoverySettings::teleport) is set.
+    pub teleported_to: Option<Vec3>,
+}
+
+/// Sent when a character controller leaves its [`ControllerBounds`](crate::ControllerBounds), such as by falling off the map.
+#[derive(Debug, Clone)]
+pub struct OutOfBoundsEvent {
+    /// The character controller that left its bounds.
+    pub entity: Entity,
+    /// Where the character was when it left its bounds.
+    pub position: Vec3,
+}
+
+/// Sent when a character in [`FacingMode::Locked`](crate::FacingMode::Locked) moves too far behind its facing direction and
+/// breaks the lock to pivot towards its movement, and again when the lock is restored.
+/// See [`FacingSettings::release_lock_angle`](crate::FacingSettings::release_lock_angle).
+#[derive(Debug, Clone)]
+pub struct FacingLockChangedEvent {
+    /// The character controller whose lock changed.
+    pub entity: Entity,
+    /// Whether the lock was released (`true`) or restored (`false`).
+    pub released: bool,
+}
+
+/// Sent when a character standing still starts turning in place. See
+/// [`FacingSettings::turn_in_place_angle`](crate::FacingSettings::turn_in_place_angle).
+#[derive(Debug, Clone)]
+pub struct TurnInPlaceEvent {
+    /// The character controller that is turning.
+    pub entity: Entity,
+    /// How far the character is turning, in radians. Positive angles turn counterclockwise around
+    /// [`up_vector`](crate::ControllerSettings::up_vector), i.e. to the left.
+    pub angle: f32,
+}
+
+/// Sent when a character following a [`PathFollow`](crate::PathFollow) passes one of its waypoints.
+#[derive(Debug, Clone)]
+pub struct PathProgressEvent {
+    /// The character controller following the path.
+    pub entity: Entity,
+    /// The index of the waypoint that was passed.
+    pub waypoint: usize,
+    /// Whether this was the last waypoint, so the character has finished the path.
+    pub finished: bool,
+}
+
+/// Sent when a character following a [`PatrolRoute`](crate::PatrolRoute) reaches one of its waypoints.
+#[derive(Debug, Clone)]
+pub struct PatrolWaypointEvent {
+    /// The character controller on patrol.
+    pub entity: Entity,
+    /// The index of the waypoint that was reached.
+    pub waypoint: usize,
+    /// Whether this was the last waypoint of a [`PatrolMode::Once`](crate::PatrolMode::Once) route, so the character has
+    /// finished its patrol.
+    pub finished: bool,
+}
+
+/// Sent when a character with a [`MovementBlockage`](crate::MovementBlockage) has been blocked from moving for a while, and
+/// again whenever the reason changes.
+#[derive(Debug, Clone)]
+pub struct MovementBlockedEvent {
+    /// The character controller that is blocked.
+    pub entity: Entity,
+    /// Why it is blocked.
+    pub reason: MovementBlockedReason,
+    /// The direction the character is asked to move in, perpendicular to
+    /// [`up_vector`](crate::ControllerSettings::up_vector).
+    pub direction: Vec3,
+}
+
+/// Send to wake a sleeping character, and keep it awake for [`ControllerActivation::wake_duration`](crate::ControllerActivation::wake_duration)
+/// even with no viewer nearby.
+#[derive(Debug, Clone)]
+pub struct WakeControllerEvent {
+    /// The character controller to wake.
+    pub entity: Entity,
+}
+
+/// Send to knock a character back or launch it, setting its velocity outright. Unlike
+/// [`ControllerInput::custom_impulse`](crate::ControllerInput::custom_impulse), the character's movement doesn't
+/// immediately brake it back to the speed it wants to move at.
+#[derive(Debug, Clone)]
+pub struct KnockbackEvent {
+    /// The character controller to knock back.
+    pub entity: Entity,
+    /// The velocity to give the character.
+    pub velocity: Vec3,
+    /// How long to turn off the character's movement for, in seconds, so it can't steer against the knockback. Zero
+    /// still lets the knockback carry over into the character's movement, which slows it down only as fast as
+    /// [`LocomotionSettings::acceleration`](crate::LocomotionSettings::acceleration) allows.
+    pub lockout: f32,
+}
+
+/// Sent when a character with a [`ControllerActivation`](crate::ControllerActivation) falls asleep or wakes up.
+#[derive(Debug, Clone)]
+pub struct ActivationChangedEvent {
+    /// The character controller that fell asleep or woke up.
+    pub entity: Entity,
+    /// Whether the character woke up (`true`) or fell asleep (`false`).
+    pub awake: bool,
+}
@@ -0,0 +1,106 @@
+use crate::events::{LandedEvent, SlidingChangedEvent};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_hanabi::{EffectAsset, ParticleEffect, ParticleEffectBundle};
+
+/// Spawns `bevy_hanabi` particle effects for character controllers' movement events, using the contacts the events carry:
+/// dust where characters land, and a trail while they slide. Configure the effects with the [`MovementEffects`] resource.
+///
+/// Requires the `hanabi` feature, and `bevy_hanabi`'s `HanabiPlugin`.
+pub struct WanderlustFxPlugin;
+
+impl Plugin for WanderlustFxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MovementEffects>()
+            .add_system_to_stage(CoreStage::PostUpdate, spawn_movement_effects)
+            .add_system(despawn_finished_effects);
+    }
+}
+
+/// Which effects [`WanderlustFxPlugin`] spawns. Effects left as `None` are skipped.
+pub struct MovementEffects {
+    /// Spawned where a character lands, facing away from the ground. Give it a spawner that only spawns once.
+    pub land: Option<Handle<EffectAsset>>,
+    /// Attached to a character's feet while it slides.
+    pub slide: Option<Handle<EffectAsset>>,
+    /// How long an effect is kept around after it stops spawning, so its last particles can finish.
+    pub lifetime: f32,
+}
+
+impl Default for MovementEffects {
+    fn default() -> Self {
+        Self {
+            land: None,
+            slide: None,
+            lifetime: 2.0,
+        }
+    }
+}
+
+/// Despawns an effect once it runs out, see [`MovementEffects::lifetime`].
+#[derive(Component)]
+struct EffectLifetime(Timer);
+
+fn spawn_movement_effects(
+    mut commands: Commands,
+    effects: Res<MovementEffects>,
+    characters: Query<&GlobalTransform>,
+    mut particles: Query<&mut ParticleEffect>,
+    mut landed_events: EventReader<LandedEvent>,
+    mut sliding_events: EventReader<SlidingChangedEvent>,
+    mut trails: Local<HashMap<Entity, Entity>>,
+) {
+    let lifetime = || EffectLifetime(Timer::from_seconds(effects.lifetime, false));
+
+    if let Some(land) = &effects.land {
+        for event in landed_events.iter() {
+            commands
+                .spawn_bundle(ParticleEffectBundle {
+                    transform: event.contact.transform(),
+                    ..ParticleEffectBundle::new(land.clone())
+                })
+                .insert(lifetime());
+        }
+    }
+
+    for event in sliding_events.iter() {
+        if event.sliding {
+            let (slide, tf) = match (&effects.slide, characters.get(event.entity)) {
+                (Some(slide), Ok(tf)) => (slide, tf),
+                _ => continue,
+            };
+            // Follow the character, starting from where it touches the surface.
+            let local =
+                Transform::from_matrix(tf.compute_matrix().inverse()) * event.contact.transform();
+            let trail = commands
+                .spawn_bundle(ParticleEffectBundle {
+                    transform: local,
+                    ..ParticleEffectBundle::new(slide.clone())
+                })
+                .id();
+            commands.entity(event.entity).add_child(trail);
+            trails.insert(event.entity, trail);
+        } else if let Some(trail) = trails.remove(&event.entity) {
+            if let Ok(mut effect) = particles.get_mut(trail) {
+                if let Some(spawner) = effect.maybe_spawner() {
+                    spawner.set_active(false);
+                }
+                commands.entity(trail).insert(lifetime());
+            }
+        }
+    }
+
+    trails.retain(|character, _| characters.contains(*character));
+}
+
+fn despawn_finished_effects(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut effects: Query<(Entity, &mut EffectLifetime)>,
+) {
+    for (entity, mut lifetime) in effects.iter_mut() {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
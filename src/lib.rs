@@ -1,18 +1,85 @@
 #![deny(missing_docs)]
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "kira")]
+mod audio;
+mod backend;
 mod bundles;
+mod commands;
 mod components;
+mod diagnostics;
+#[cfg(feature = "rapier2d")]
+mod dim2;
+mod events;
+#[cfg(feature = "hanabi")]
+mod fx;
 mod plugins;
 mod presets;
 mod resources;
+#[cfg(feature = "serde")]
+mod serialization;
 mod systems;
 
 pub use self::{
+    backend::{ContactPoint, PhysicsBackend, RapierBackend, RayHit, ShapeHit},
     bundles::{CharacterControllerBundle, ControllerPhysicsBundle, StarshipControllerBundle},
-    components::{ControllerInput, ControllerSettings, ControllerState},
-    plugins::WanderlustPlugin,
-    presets::{CharacterControllerPreset, StarshipControllerPreset},
-    resources::WanderlustPhysicsTweaks,
-    systems::{movement, setup_physics_context},
+    commands::{ShiftControllerOrigin, ShiftOrigin, TeleportController, TeleportEntityCommandsExt},
+    components::{
+        ActionSettings, AimTransform, AutoCrouch, BoundsShape, ClimbSettings, ContactTransform,
+        ControllerAction, ControllerActivation, ControllerArchetype, ControllerAsleep,
+        ControllerBody, ControllerBounds, ControllerCommand, ControllerCommands, ControllerForces,
+        ControllerFrame, ControllerInput, ControllerIntent, ControllerIntents, ControllerLod,
+        ControllerSettings, ControllerStances, ControllerState, CrouchSettings, CrushResponse,
+        CrushSettings, DashSettings, DynamicGravity, FacingMode, FacingSettings, FloatSettings,
+        FluidVolume, FlySettings, Foot, FootPlacement, FormationSlot, ForwardObstruction,
+        GlideSettings, GrappleState, GravitySource, GroundCastFilter, GroundCastInfo,
+        GroundCastSettings, GroundLost, GroundSurface, InteractionRay, Jetpack, JumpSettings,
+        KeepDistance, LadderVolume, LandingPrediction, Ledge, LedgeHang, LocomotionSettings,
+        LodViewer, MovementBlockage, MovementBlockedReason, ObstructionHeight, PathFollow,
+        PatrolMode, PatrolRoute, PlatformCarry, RecoverySettings, ReferenceFrame,
+        RenderInterpolation, ShiftWithOrigin, SprintSettings, SprintStamina, SurfaceMaterial,
+        SurfaceTraction, SwimSettings, TractionSettings, UprightSettings, Vault, VehicleInterior,
+        VolumeKind, VolumeOverlaps, WallContact, WallSettings, WorldPosition, ZoneVolume,
+    },
+    events::{
+        ActivationChangedEvent, ClimbingChangedEvent, ControllerRecoveredEvent, CrushedEvent,
+        DashEvent, FacingLockChangedEvent, GlidingChangedEvent, GroundedEvent, JumpEvent, JumpKind,
+        JumpLinkFailedEvent, JumpLinkFinishedEvent, JumpLinkStartedEvent, KnockbackEvent,
+        LandedEvent, LedgeDetectedEvent, LedgeGrabbedEvent, LedgeReleaseCause, LedgeReleasedEvent,
+        MovementBlockedEvent, OutOfBoundsEvent, PathProgressEvent, PatrolWaypointEvent,
+        SkiddingChangedEvent, SlidingChangedEvent, StanceChangedEvent, SwimmingChangedEvent,
+        TurnInPlaceEvent, UngroundedCause, UngroundedEvent, VaultFinishedEvent, VaultStartedEvent,
+        WakeControllerEvent, WallSlidingChangedEvent,
+    },
+    plugins::{MovementSet, WanderlustPlugin, WanderlustStage},
+    presets::{
+        CharacterControllerPreset, PresetCommandsExt, PresetEntityCommandsExt, SetControllerPreset,
+        StarshipControllerPreset,
+    },
+    resources::{ControllerPresets, WanderlustPhysicsTweaks, WanderlustSchedule, WorldOrigin},
+    systems::{
+        apply_float, apply_forces, apply_gravity, apply_jump, apply_movement, apply_upright,
+        auto_crouch, cast_interaction_rays, climb, crouch, dash, detect_blocked_movement,
+        detect_fluids, detect_ladders, detect_walls, enter_reference_frame, find_ground, fly,
+        follow_formation, follow_gravity_sources, follow_paths, glide, grapple, interpolate_render,
+        jump_link, keep_distance, knockback, leave_reference_frame, ledge_hang, out_of_bounds,
+        patrol, place_feet, platform_carry, predict_landing, probe_forward_obstruction,
+        process_controller_commands, push_ground, recenter_world_origin, recover, sanitize_input,
+        schedule_lod, setup_physics_context, sprint, swim, switch_stances, track_volume_overlaps,
+        update_activation, update_aim, update_query_pipeline, vault, wall_slide,
+    },
 };
+
+#[cfg(feature = "kira")]
+pub use self::audio::{MovementSounds, SurfaceSounds, WanderlustAudioPlugin};
+#[cfg(feature = "profiling")]
+pub use self::diagnostics::WanderlustDiagnosticsPlugin;
+#[cfg(feature = "rapier2d")]
+pub use self::dim2::{
+    movement_2d, CharacterController2dBundle, ControllerInput2d, ControllerSettings2d,
+    ControllerState2d, GroundCastSettings2d, Wanderlust2dPlugin,
+};
+#[cfg(feature = "hanabi")]
+pub use self::fx::{MovementEffects, WanderlustFxPlugin};
+#[cfg(feature = "serde")]
+pub use self::serialization::{ControllerSettingsV0, VersionedControllerSettings};
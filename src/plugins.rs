@@ -1,16 +1,459 @@
-use crate::{components::*, systems::*};
-use bevy::prelude::*;
+use crate::backend::{PhysicsBackend, RapierBackend};
+use crate::commands::BackendCommands;
+use crate::{components::*, events::*, resources::*, systems::*};
+use bevy::{
+    ecs::schedule::{ParallelSystemDescriptor, ShouldRun},
+    prelude::*,
+    transform::TransformSystem,
+};
+use std::marker::PhantomData;
 
 /// The [character controller](CharacterController) plugin. Necessary to have the character controller
 /// work.
-pub struct WanderlustPlugin;
+///
+/// Runs on the physics engine `B`, Rapier unless another [`PhysicsBackend`] is given with
+/// `WanderlustPlugin::<MyBackend>::new()`.
+pub struct WanderlustPlugin<B: PhysicsBackend = RapierBackend>(PhantomData<B>);
 
-impl Plugin for WanderlustPlugin {
+impl<B: PhysicsBackend> WanderlustPlugin<B> {
+    /// The plugin running on `B`.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl Default for WanderlustPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: PhysicsBackend> Plugin for WanderlustPlugin<B> {
     fn build(&self, app: &mut App) {
         app.register_type::<ControllerState>()
+            .register_type::<Countdown>()
             .register_type::<ControllerSettings>()
+            .register_type::<LocomotionSettings>()
+            .register_type::<JumpSettings>()
+            .register_type::<FloatSettings>()
+            .register_type::<GroundCastSettings>()
+            .register_type::<UprightSettings>()
+            .register_type::<FacingSettings>()
+            .register_type::<FacingMode>()
+            .register_type::<ActionSettings>()
+            .register_type::<CrushSettings>()
+            .register_type::<CrushResponse>()
+            .register_type::<RecoverySettings>()
+            .register_type::<CrouchSettings>()
+            .register_type::<SprintSettings>()
+            .register_type::<DashSettings>()
+            .register_type::<TractionSettings>()
+            .register_type::<WallSettings>()
+            .register_type::<SwimSettings>()
+            .register_type::<ClimbSettings>()
+            .register_type::<FlySettings>()
+            .register_type::<GlideSettings>()
+            .register_type::<ControllerClock>()
+            .register_type::<ControllerBody>()
+            .register_type::<ControllerAction>()
             .register_type::<ControllerInput>()
-            .add_startup_system(setup_physics_context)
-            .add_system(movement);
+            .register_type::<GroundCastInfo>()
+            .register_type::<GroundSurface>()
+            .register_type::<ControllerArchetype>()
+            .register_type::<ControllerForces>()
+            .register_type::<RenderInterpolation>()
+            .register_type::<ControllerBounds>()
+            .register_type::<LandingPrediction>()
+            .register_type::<ForwardObstruction>()
+            .register_type::<ObstructionHeight>()
+            .register_type::<WallContact>()
+            .register_type::<MovementBlockage>()
+            .register_type::<Vault>()
+            .register_type::<LedgeHang>()
+            .register_type::<AutoCrouch>()
+            .register_type::<SprintStamina>()
+            .register_type::<Jetpack>()
+            .register_type::<GrappleState>()
+            .register_type::<ControllerLod>()
+            .register_type::<LodViewer>()
+            .register_type::<ControllerActivation>()
+            .register_type::<ControllerAsleep>()
+            .register_type::<FootPlacement>()
+            .register_type::<Foot>()
+            .register_type::<ContactTransform>()
+            .register_type::<AimTransform>()
+            .register_type::<SurfaceMaterial>()
+            .register_type::<SurfaceTraction>()
+            .register_type::<GravitySource>()
+            .register_type::<DynamicGravity>()
+            .register_type::<FluidVolume>()
+            .register_type::<ZoneVolume>()
+            .register_type::<LadderVolume>()
+            .register_type::<VehicleInterior>()
+            .register_type::<ReferenceFrame>()
+            .register_type::<WorldPosition>()
+            .register_type::<ShiftWithOrigin>()
+            .register_type::<InteractionRay>()
+            .init_resource::<ControllerPresets>()
+            .add_event::<UngroundedEvent>()
+            .add_event::<StanceChangedEvent>()
+            .add_event::<CrushedEvent>()
+            .add_event::<OutOfBoundsEvent>()
+            .add_event::<ControllerRecoveredEvent>()
+            .add_event::<FacingLockChangedEvent>()
+            .add_event::<TurnInPlaceEvent>()
+            .add_event::<GroundedEvent>()
+            .add_event::<LandedEvent>()
+            .add_event::<JumpEvent>()
+            .add_event::<DashEvent>()
+            .add_event::<SlidingChangedEvent>()
+            .add_event::<SkiddingChangedEvent>()
+            .add_event::<WallSlidingChangedEvent>()
+            .add_event::<SwimmingChangedEvent>()
+            .add_event::<GlidingChangedEvent>()
+            .add_event::<ClimbingChangedEvent>()
+            .add_event::<VaultStartedEvent>()
+            .add_event::<VaultFinishedEvent>()
+            .add_event::<JumpLinkStartedEvent>()
+            .add_event::<JumpLinkFinishedEvent>()
+            .add_event::<JumpLinkFailedEvent>()
+            .add_event::<LedgeDetectedEvent>()
+            .add_event::<LedgeGrabbedEvent>()
+            .add_event::<LedgeReleasedEvent>()
+            .add_event::<PathProgressEvent>()
+            .add_event::<PatrolWaypointEvent>()
+            .add_event::<MovementBlockedEvent>()
+            .add_event::<WakeControllerEvent>()
+            .add_event::<KnockbackEvent>()
+            .add_event::<ActivationChangedEvent>()
+            .insert_resource(BackendCommands::new::<B>())
+            .add_startup_system(setup_physics_context::<B>)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_render::<B>.before(TransformSystem::TransformPropagate),
+            );
+
+        let schedule = app
+            .world
+            .get_resource::<WanderlustSchedule>()
+            .cloned()
+            .unwrap_or_default();
+
+        if schedule.stage == WanderlustStage::AfterPhysicsSync.as_label() {
+            app.add_stage_after(
+                B::sync_stage(),
+                WanderlustStage::AfterPhysicsSync,
+                SystemStage::parallel(),
+            );
+        }
+        if schedule.update_query_pipeline {
+            app.add_system_to_stage(
+                schedule.stage,
+                update_query_pipeline::<B>.before(MovementSet::FindGround),
+            );
+        }
+
+        let stage = |set: MovementSet, system: ParallelSystemDescriptor| {
+            let system = system.label(set);
+            if schedule.disabled.contains(&set) {
+                system.with_run_criteria(never)
+            } else {
+                system
+            }
+        };
+        app.add_system_to_stage(
+            schedule.stage,
+            stage(
+                MovementSet::FindGround,
+                find_ground::<B>.before(MovementSet::Gravity),
+            ),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(
+                MovementSet::Gravity,
+                apply_gravity.after(MovementSet::FindGround),
+            ),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(MovementSet::Float, apply_float.after(MovementSet::Gravity)),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(
+                MovementSet::Movement,
+                apply_movement::<B>.after(MovementSet::Float),
+            ),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(MovementSet::Jump, apply_jump.after(MovementSet::Movement)),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(MovementSet::Upright, apply_upright.after(MovementSet::Jump)),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            stage(
+                MovementSet::ApplyForces,
+                apply_forces::<B>.after(MovementSet::Upright),
+            ),
+        );
+
+        app.add_system_to_stage(
+            schedule.stage,
+            recover::<B>
+                .after(MovementSet::FindGround)
+                .before(MovementSet::Gravity),
+        )
+        .add_system_to_stage(schedule.stage, auto_crouch::<B>.before(switch_stances::<B>))
+        .add_system_to_stage(
+            schedule.stage,
+            switch_stances::<B>.before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            crouch::<B>
+                .after(switch_stances::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            sprint::<B>
+                .after(crouch::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            process_controller_commands
+                .after(switch_stances::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            follow_paths::<B>
+                .after(process_controller_commands)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            patrol::<B>
+                .after(follow_paths::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            follow_formation::<B>
+                .after(patrol::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            keep_distance
+                .after(follow_formation::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            knockback::<B>
+                .after(update_activation::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            sanitize_input
+                .after(keep_distance)
+                .before(enter_reference_frame),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            enter_reference_frame
+                .after(keep_distance)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            follow_gravity_sources::<B>
+                .after(enter_reference_frame)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            leave_reference_frame.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            update_activation::<B>.before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            schedule_lod
+                .after(update_activation::<B>)
+                .before(MovementSet::FindGround),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            platform_carry::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            push_ground::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            recenter_world_origin::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            out_of_bounds.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            predict_landing::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            probe_forward_obstruction::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            detect_blocked_movement::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            detect_walls::<B>
+                .after(MovementSet::FindGround)
+                .before(MovementSet::Movement),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            wall_slide
+                .after(MovementSet::Jump)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            glide
+                .after(MovementSet::Jump)
+                .after(wall_slide)
+                .after(dash)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            detect_fluids::<B>
+                .after(MovementSet::FindGround)
+                .before(MovementSet::Gravity),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            detect_ladders::<B>
+                .after(MovementSet::FindGround)
+                .before(MovementSet::Gravity),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            climb
+                .after(MovementSet::Jump)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            grapple
+                .after(MovementSet::Upright)
+                .after(wall_slide)
+                .after(glide)
+                .after(swim)
+                .after(climb)
+                .after(ledge_hang::<B>)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            fly::<B>
+                .after(grapple)
+                .after(wall_slide)
+                .after(glide)
+                .after(swim)
+                .after(climb)
+                .after(ledge_hang::<B>)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            swim.after(MovementSet::Jump)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            dash.after(MovementSet::Jump).before(vault::<B>),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            jump_link::<B>.after(MovementSet::Jump).before(vault::<B>),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            vault::<B>
+                .after(MovementSet::Jump)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            ledge_hang::<B>
+                .after(vault::<B>)
+                .before(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            place_feet::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(schedule.stage, update_aim.after(MovementSet::ApplyForces))
+        .add_system_to_stage(
+            schedule.stage,
+            track_volume_overlaps::<B>.after(MovementSet::ApplyForces),
+        )
+        .add_system_to_stage(
+            schedule.stage,
+            cast_interaction_rays::<B>.after(MovementSet::ApplyForces),
+        );
     }
 }
+
+/// The stages a character's movement is worked out in every frame, in order. Each stage is a system labeled with its
+/// variant here, so other systems can be ordered around it. The stages communicate through each character's
+/// [`ControllerFrame`] and [`ControllerForces`].
+///
+/// A stage can be left out with [`WanderlustSchedule::disabled`], and replaced by labeling another system with the same
+/// variant.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy, SystemLabel)]
+pub enum MovementSet {
+    /// Finds the ground under each character, see [`find_ground`]. Runs first.
+    FindGround,
+    /// Pulls characters down while they aren't over the ground, see [`apply_gravity`].
+    Gravity,
+    /// Floats characters above the ground, see [`apply_float`].
+    Float,
+    /// Moves characters along the ground, see [`apply_movement`].
+    Movement,
+    /// Makes characters jump, see [`apply_jump`].
+    Jump,
+    /// Keeps characters upright and turns them, see [`apply_upright`].
+    Upright,
+    /// Applies the forces of all the other stages, see [`apply_forces`]. Runs last.
+    ApplyForces,
+}
+
+fn never() -> ShouldRun {
+    ShouldRun::No
+}
+
+/// Stages added by [`WanderlustPlugin`], depending on [`WanderlustSchedule`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone, StageLabel)]
+pub enum WanderlustStage {
+    /// A stage right after [`PhysicsBackend::sync_stage`], where changes made to transforms this frame have already been
+    /// sent to the physics engine, but the simulation has not been stepped yet.
+    AfterPhysicsSync,
+}
@@ -1,4 +1,10 @@
-use crate::ControllerSettings;
+use crate::{
+    CharacterControllerBundle, ClimbSettings, ControllerArchetype, ControllerPresets,
+    ControllerSettings, CrouchSettings, DashSettings, FloatSettings, FlySettings, GlideSettings,
+    GroundCastSettings, JumpSettings, LocomotionSettings, RecoverySettings, SprintSettings,
+    SwimSettings, UprightSettings,
+};
+use bevy::ecs::system::{Command, EntityCommands};
 use bevy::math::vec3;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::Collider;
@@ -9,29 +15,105 @@ pub struct CharacterControllerPreset;
 impl From<CharacterControllerPreset> for ControllerSettings {
     fn from(_: CharacterControllerPreset) -> ControllerSettings {
         ControllerSettings {
-            acceleration: 50.0,
-            max_speed: 10.0,
-            max_acceleration_force: 10.0,
             up_vector: Vec3::Y,
             gravity: 25.0,
-            max_ground_angle: 45.0 * (std::f32::consts::PI / 180.0),
-            min_float_offset: -0.3,
-            max_float_offset: 0.05,
-            jump_time: 0.5,
-            jump_initial_force: 15.0,
-            jump_stop_force: 0.3,
-            jump_decay_function: |x| (1.0 - x).sqrt(),
-            jump_skip_ground_check_duration: 0.5,
-            coyote_time_duration: 0.16,
-            jump_buffer_duration: 0.16,
-            force_scale: vec3(1.0, 0.0, 1.0),
-            float_cast_length: 1.0,
-            float_cast_collider: Collider::ball(0.45),
-            float_distance: 0.55,
-            float_strength: 10.0,
-            float_dampen: 0.5,
-            upright_spring_strength: 100.0,
-            upright_spring_damping: 10.0,
+            locomotion: LocomotionSettings {
+                acceleration: 50.0,
+                max_speed: 10.0,
+                max_acceleration_force: 10.0,
+                force_scale: vec3(1.0, 0.0, 1.0),
+                ground_acceleration_feed_forward: 1.0,
+                corner_nudge: 10.0,
+                corner_nudge_width: 0.3,
+                max_step_height: 0.3,
+                project_on_ground: true,
+                ..default()
+            },
+            jump: JumpSettings {
+                time: 0.5,
+                initial_force: 15.0,
+                stop_force: 0.3,
+                decay_function: |x| (1.0 - x).sqrt(),
+                skip_ground_check_duration: 0.5,
+                coyote_time_duration: 0.16,
+                buffer_duration: 0.16,
+                ledge_forgiveness: 0.2,
+                ..default()
+            },
+            float: FloatSettings {
+                distance: 0.55,
+                strength: 10.0,
+                dampen: 0.5,
+                min_offset: -0.3,
+                max_offset: 0.05,
+                vertical_ground_velocity_tracking: 1.0,
+                ground_acceleration_feed_forward: 1.0,
+                downhill_snap: 0.0,
+                max_snap_speed: 0.0,
+                ground_reaction: 0.0,
+            },
+            ground_cast: GroundCastSettings {
+                length: 1.0,
+                collider: Collider::ball(0.45),
+                max_ground_angle: 45.0 * (std::f32::consts::PI / 180.0),
+                step_height: 0.3,
+                character_slide_force: 5.0,
+                slope_slide_acceleration: 10.0,
+                ..default()
+            },
+            upright: UprightSettings {
+                spring_strength: 100.0,
+                spring_damping: 10.0,
+            },
+            eye: Vec3::new(0.0, 0.5, 0.0),
+            crouch: CrouchSettings {
+                height: 1.0,
+                float_distance: 0.3,
+                max_speed: 4.0,
+            },
+            sprint: SprintSettings {
+                max_speed: 16.0,
+                acceleration: 50.0,
+            },
+            dash: DashSettings {
+                speed: 20.0,
+                duration: 0.2,
+                cooldown: 0.5,
+                ignore_gravity: true,
+                ground_only: false,
+            },
+            recovery: RecoverySettings {
+                max_speed: 100.0,
+                teleport: false,
+            },
+            swim: SwimSettings {
+                max_speed: 5.0,
+                acceleration: 10.0,
+                max_acceleration_force: 5.0,
+                height: 1.6,
+                min_submersion: 0.6,
+                hop_depth: 0.5,
+                hop_force: 10.0,
+            },
+            climb: ClimbSettings {
+                speed: 3.0,
+                min_press: 0.5,
+                jump_away: 5.0,
+                jump_up: 6.0,
+                jump_lockout: 0.3,
+            },
+            fly: FlySettings {
+                enabled: false,
+                noclip: false,
+                max_speed: 10.0,
+                acceleration: 10.0,
+                damping: 5.0,
+            },
+            glide: GlideSettings {
+                max_speed: 8.0,
+                acceleration: 3.0,
+                fall_speed: 2.0,
+            },
             ..default()
         }
     }
@@ -43,12 +125,78 @@ pub struct StarshipControllerPreset;
 impl From<StarshipControllerPreset> for ControllerSettings {
     fn from(_: StarshipControllerPreset) -> ControllerSettings {
         ControllerSettings {
-            acceleration: 0.3,
-            max_speed: 100.0,
-            max_acceleration_force: 10.0,
             up_vector: Vec3::Y,
-            force_scale: vec3(1.0, 1.0, 1.0),
+            locomotion: LocomotionSettings {
+                acceleration: 0.3,
+                max_speed: 100.0,
+                max_acceleration_force: 10.0,
+                force_scale: vec3(1.0, 1.0, 1.0),
+                ..default()
+            },
             ..default()
         }
     }
 }
+
+/// A [`Command`] which replaces an entity's [`ControllerSettings`] with the ones registered in [`ControllerPresets`] under `key`,
+/// and sets its [`ControllerArchetype`]. The entity's [`ControllerState`](crate::ControllerState) is kept as-is.
+pub struct SetControllerPreset {
+    /// The character to change.
+    pub entity: Entity,
+    /// The key of the preset in [`ControllerPresets`].
+    pub key: String,
+}
+
+impl Command for SetControllerPreset {
+    fn write(self, world: &mut World) {
+        let settings = world
+            .get_resource::<ControllerPresets>()
+            .and_then(|presets| presets.get(&self.key))
+            .cloned();
+
+        match (settings, world.get_entity_mut(self.entity)) {
+            (Some(settings), Some(mut entity)) => {
+                entity
+                    .insert(settings)
+                    .insert(ControllerArchetype(self.key));
+            }
+            (None, _) => warn!("No controller preset is registered as {:?}", self.key),
+            (_, None) => warn!(
+                "Cannot set controller preset {:?} on {:?}, which does not exist",
+                self.key, self.entity
+            ),
+        }
+    }
+}
+
+/// Extension methods on [`Commands`] for spawning characters from [`ControllerPresets`].
+pub trait PresetCommandsExt<'w, 's> {
+    /// Spawn a [`CharacterControllerBundle`] using the settings registered in [`ControllerPresets`] under `key`.
+    fn spawn_preset<'a>(&'a mut self, key: impl Into<String>) -> EntityCommands<'w, 's, 'a>;
+}
+
+impl<'w, 's> PresetCommandsExt<'w, 's> for Commands<'w, 's> {
+    fn spawn_preset<'a>(&'a mut self, key: impl Into<String>) -> EntityCommands<'w, 's, 'a> {
+        let mut entity = self.spawn_bundle(CharacterControllerBundle::default());
+        entity.set_preset(key);
+        entity
+    }
+}
+
+/// Extension methods on [`EntityCommands`] for swapping a character's settings with ones from [`ControllerPresets`].
+pub trait PresetEntityCommandsExt {
+    /// Replace this character's settings with the ones registered in [`ControllerPresets`] under `key`.
+    /// See [`SetControllerPreset`].
+    fn set_preset(&mut self, key: impl Into<String>) -> &mut Self;
+}
+
+impl PresetEntityCommandsExt for EntityCommands<'_, '_, '_> {
+    fn set_preset(&mut self, key: impl Into<String>) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(SetControllerPreset {
+            entity,
+            key: key.into(),
+        });
+        self
+    }
+}
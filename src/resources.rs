@@ -1,3 +1,14 @@
+use crate::{
+    plugins::{MovementSet, WanderlustStage},
+    ControllerSettings,
+};
+use bevy::{
+    ecs::schedule::StageLabelId,
+    math::{DVec3, Vec3},
+    prelude::{CoreStage, StageLabel},
+    utils::HashMap,
+};
+
 /// Should [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) tweak physics rules to make the character controller work better?
 /// If not present, defaults to true. Must be added before [`WanderlustPlugin`](crate::plugins::WanderlustPlugin).
 pub struct WanderlustPhysicsTweaks(#[deprecated] pub bool);
@@ -15,3 +26,137 @@ impl WanderlustPhysicsTweaks {
         self.0
     }
 }
+
+/// Where [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) schedules the character controller relative to Rapier.
+/// If not present, defaults to [`Default::default`]. Must be added before [`WanderlustPlugin`](crate::plugins::WanderlustPlugin).
+///
+/// By default the controller runs in [`CoreStage::Update`], and its ground casts see colliders where they were at the end of the
+/// last physics step. Platforms moved by setting their transform this frame are seen one frame late, which shows up as lag on fast
+/// platforms. To avoid this, use [`WanderlustStage::AfterPhysicsSync`] together with [`update_query_pipeline`](WanderlustSchedule::update_query_pipeline).
+///
+/// The controller works with any of Rapier's timestep modes, but it runs once per frame rather than once per physics step: Rapier runs
+/// all of a frame's fixed steps within one system, with no stage in between to run the controller in. Its impulses are scaled by the
+/// frame's delta time and applied by Rapier before the first of them, however many steps that frame takes.
+///
+/// With `TimestepMode::Interpolated`, keep the `TransformInterpolation` component from
+/// [`ControllerPhysicsBundle`](crate::bundles::ControllerPhysicsBundle) so the body is drawn smoothly. The controller then
+/// reads the character's pose from Rapier rather than from its interpolated transform. Prefer
+/// [`WanderlustSchedule::after_physics_sync`] so the ground cast sees colliders at their physics poses too.
+#[derive(Clone)]
+pub struct WanderlustSchedule {
+    /// The stage to add the controller's systems to. If this is [`WanderlustStage::AfterPhysicsSync`], the plugin creates it, in which
+    /// case `RapierPhysicsPlugin` must be added before [`WanderlustPlugin`](crate::plugins::WanderlustPlugin).
+    pub stage: StageLabelId,
+    /// Refresh Rapier's query pipeline before casting for the ground, so that ground casts see colliders where they are this frame.
+    /// This has a cost proportional to the amount of colliders in the world.
+    pub update_query_pipeline: bool,
+    /// The movement stages to leave out, e.g. to replace them with systems of your own. See [`MovementSet`].
+    pub disabled: Vec<MovementSet>,
+}
+
+impl Default for WanderlustSchedule {
+    fn default() -> Self {
+        Self {
+            stage: CoreStage::Update.as_label(),
+            update_query_pipeline: false,
+            disabled: Vec::new(),
+        }
+    }
+}
+
+impl WanderlustSchedule {
+    /// Run the controller after transform changes have been sent to Rapier, with an up to date query pipeline.
+    pub fn after_physics_sync() -> Self {
+        Self {
+            stage: WanderlustStage::AfterPhysicsSync.as_label(),
+            update_query_pipeline: true,
+            disabled: Vec::new(),
+        }
+    }
+}
+
+/// Where the physics world's origin is in a double precision world, for big worlds that don't fit in `f32` transforms.
+/// Insert this to have the origin follow the first [`LodViewer`](crate::LodViewer).
+///
+/// Transforms, physics and the controller all stay in `f32`, relative to the origin. Once the viewer is further than
+/// [`recenter_distance`](WorldOrigin::recenter_distance) from the origin, the root [`Transform`](bevy::prelude::Transform)s
+/// of rigid bodies, colliders, [`LodViewer`](crate::LodViewer)s and entities marked with
+/// [`ShiftWithOrigin`](crate::ShiftWithOrigin) are moved back towards it along with the controllers' state, see
+/// [`ShiftControllerOrigin`](crate::ShiftControllerOrigin), and [`position`](WorldOrigin::position) is moved by as much.
+/// Other roots, such as UI, are left where they are.
+///
+/// Every body and collider in Rapier is moved, so colliders whose root is neither a rigid body nor a collider need their
+/// root marked with [`ShiftWithOrigin`](crate::ShiftWithOrigin), or they end up out of step with their transforms. Use [`to_local`](WorldOrigin::to_local) and
+/// [`to_world`](WorldOrigin::to_world) to convert positions at the boundary, and give characters a
+/// [`WorldPosition`](crate::WorldPosition) to keep track of where they are in double precision.
+#[derive(Clone, Copy, Debug)]
+pub struct WorldOrigin {
+    /// Where the physics world's origin is.
+    pub position: DVec3,
+    /// How far the viewer can get from the origin before it is recentered.
+    pub recenter_distance: f32,
+}
+
+impl WorldOrigin {
+    /// Convert a position relative to the origin, such as a transform's translation, to the double precision world.
+    pub fn to_world(&self, local: Vec3) -> DVec3 {
+        self.position + local.as_dvec3()
+    }
+
+    /// Convert a position in the double precision world to one relative to the origin, e.g. to spawn or teleport a character.
+    pub fn to_local(&self, world: DVec3) -> Vec3 {
+        (world - self.position).as_vec3()
+    }
+}
+
+impl Default for WorldOrigin {
+    fn default() -> Self {
+        Self {
+            position: DVec3::ZERO,
+            recenter_distance: 1000.0,
+        }
+    }
+}
+
+/// A registry of named [`ControllerSettings`], for games with several kinds of characters (e.g. "heavy_knight", "werewolf").
+///
+/// Enum keys can be used by converting them to strings. Use [`PresetCommandsExt::spawn_preset`](crate::PresetCommandsExt::spawn_preset)
+/// to spawn a character by key, and [`PresetEntityCommandsExt::set_preset`](crate::PresetEntityCommandsExt::set_preset) to swap an existing
+/// character's settings at runtime.
+#[derive(Default)]
+pub struct ControllerPresets {
+    presets: HashMap<String, ControllerSettings>,
+}
+
+impl ControllerPresets {
+    /// Register `settings` under `key`, returning the settings previously registered under it, if any.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        settings: impl Into<ControllerSettings>,
+    ) -> Option<ControllerSettings> {
+        self.presets.insert(key.into(), settings.into())
+    }
+
+    /// Get the settings registered under `key`.
+    pub fn get(&self, key: &str) -> Option<&ControllerSettings> {
+        self.presets.get(key)
+    }
+
+    /// Get the settings registered under `key` mutably. Characters already using them are not affected.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ControllerSettings> {
+        self.presets.get_mut(key)
+    }
+
+    /// Unregister the settings registered under `key`, returning them.
+    pub fn remove(&mut self, key: &str) -> Option<ControllerSettings> {
+        self.presets.remove(key)
+    }
+
+    /// Iterate over all registered keys and settings.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ControllerSettings)> {
+        self.presets
+            .iter()
+            .map(|(key, settings)| (key.as_str(), settings))
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{
+    ControllerSettings, FloatSettings, GroundCastSettings, JumpSettings, LocomotionSettings,
+    UprightSettings,
+};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A serializable [`ControllerSettings`], tagged with the version of the format it was saved in.
+///
+/// Store presets (e.g. in RON assets) as this type rather than as [`ControllerSettings`] directly. Presets saved by older versions
+/// of this crate are migrated to the current format when converted into [`ControllerSettings`], and any fields added since then
+/// are set to their defaults, so old presets keep loading as the settings grow.
+///
+/// [`JumpSettings::decay_function`] cannot be serialized, and is always set to its default.
+#[derive(Serialize, Deserialize)]
+pub enum VersionedControllerSettings {
+    /// The flat layout used before the settings were grouped into [`LocomotionSettings`], [`JumpSettings`], etc.
+    V0(ControllerSettingsV0),
+    /// The current layout.
+    V1(Box<ControllerSettings>),
+}
+
+impl From<ControllerSettings> for VersionedControllerSettings {
+    fn from(settings: ControllerSettings) -> Self {
+        Self::V1(Box::new(settings))
+    }
+}
+
+impl From<VersionedControllerSettings> for ControllerSettings {
+    fn from(versioned: VersionedControllerSettings) -> Self {
+        match versioned {
+            VersionedControllerSettings::V0(v0) => v0.into(),
+            VersionedControllerSettings::V1(settings) => *settings,
+        }
+    }
+}
+
+/// The flat layout of [`ControllerSettings`] used before the settings were grouped. Only used for migration,
+/// see [`VersionedControllerSettings::V0`]. See the fields of the current settings structs for descriptions.
+#[allow(missing_docs)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControllerSettingsV0 {
+    pub acceleration: f32,
+    pub max_speed: f32,
+    pub max_acceleration_force: f32,
+    pub up_vector: Vec3,
+    pub gravity: f32,
+    pub max_ground_angle: f32,
+    pub min_float_offset: f32,
+    pub max_float_offset: f32,
+    pub jump_initial_force: f32,
+    pub jump_force: f32,
+    pub jump_stop_force: f32,
+    pub jump_time: f32,
+    pub jump_skip_ground_check_duration: f32,
+    pub skip_ground_check_override: bool,
+    pub extra_jumps: u32,
+    pub coyote_time_duration: f32,
+    pub jump_buffer_duration: f32,
+    pub force_scale: Vec3,
+    pub float_cast_length: f32,
+    pub float_cast_origin: Vec3,
+    #[serde(with = "collider")]
+    pub float_cast_collider: Collider,
+    pub float_distance: f32,
+    pub float_strength: f32,
+    pub float_dampen: f32,
+    pub upright_spring_strength: f32,
+    pub upright_spring_damping: f32,
+}
+
+impl Default for ControllerSettingsV0 {
+    fn default() -> Self {
+        let ControllerSettings {
+            up_vector,
+            gravity,
+            locomotion,
+            jump,
+            float,
+            ground_cast,
+            upright,
+            ..
+        } = default();
+
+        Self {
+            acceleration: locomotion.acceleration,
+            max_speed: locomotion.max_speed,
+            max_acceleration_force: locomotion.max_acceleration_force,
+            up_vector,
+            gravity,
+            max_ground_angle: ground_cast.max_ground_angle,
+            min_float_offset: float.min_offset,
+            max_float_offset: float.max_offset,
+            jump_initial_force: jump.initial_force,
+            jump_force: jump.force,
+            jump_stop_force: jump.stop_force,
+            jump_time: jump.time,
+            jump_skip_ground_check_duration: jump.skip_ground_check_duration,
+            skip_ground_check_override: ground_cast.skip_ground_check_override,
+            extra_jumps: jump.extra_jumps,
+            coyote_time_duration: jump.coyote_time_duration,
+            jump_buffer_duration: jump.buffer_duration,
+            force_scale: locomotion.force_scale,
+            float_cast_length: ground_cast.length,
+            float_cast_origin: ground_cast.origin,
+            float_cast_collider: ground_cast.collider,
+            float_distance: float.distance,
+            float_strength: float.strength,
+            float_dampen: float.dampen,
+            upright_spring_strength: upright.spring_strength,
+            upright_spring_damping: upright.spring_damping,
+        }
+    }
+}
+
+impl From<ControllerSettingsV0> for ControllerSettings {
+    fn from(v0: ControllerSettingsV0) -> Self {
+        ControllerSettings {
+            up_vector: v0.up_vector,
+            gravity: v0.gravity,
+            clock: default(),
+            body: default(),
+            locomotion: LocomotionSettings {
+                acceleration: v0.acceleration,
+                max_speed: v0.max_speed,
+                max_acceleration_force: v0.max_acceleration_force,
+                force_scale: v0.force_scale,
+                ..default()
+            },
+            jump: JumpSettings {
+                initial_force: v0.jump_initial_force,
+                force: v0.jump_force,
+                stop_force: v0.jump_stop_force,
+                time: v0.jump_time,
+                skip_ground_check_duration: v0.jump_skip_ground_check_duration,
+                extra_jumps: v0.extra_jumps,
+                coyote_time_duration: v0.coyote_time_duration,
+                buffer_duration: v0.jump_buffer_duration,
+                ..default()
+            },
+            float: FloatSettings {
+                distance: v0.float_distance,
+                strength: v0.float_strength,
+                dampen: v0.float_dampen,
+                min_offset: v0.min_float_offset,
+                max_offset: v0.max_float_offset,
+                ..default()
+            },
+            ground_cast: GroundCastSettings {
+                length: v0.float_cast_length,
+                origin: v0.float_cast_origin,
+                collider: v0.float_cast_collider,
+                max_ground_angle: v0.max_ground_angle,
+                skip_ground_check_override: v0.skip_ground_check_override,
+                ..default()
+            },
+            upright: UprightSettings {
+                spring_strength: v0.upright_spring_strength,
+                spring_damping: v0.upright_spring_damping,
+            },
+            ..default()
+        }
+    }
+}
+
+/// (De)serializes a [`Collider`] through its underlying shape, for use with `#[serde(with = "...")]`.
+pub(crate) mod collider {
+    use bevy_rapier3d::prelude::*;
+    use bevy_rapier3d::rapier::prelude::SharedShape;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(collider: &Collider, serializer: S) -> Result<S::Ok, S::Error> {
+        collider.raw.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Collider, D::Error> {
+        SharedShape::deserialize(deserializer).map(Collider::from)
+    }
+}
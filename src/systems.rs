@@ -1,121 +1,686 @@
-use crate::components::{ControllerInput, ControllerSettings, ControllerState};
+use crate::backend::{same_shape, PhysicsBackend, RapierBackend, ShapeHit};
+use crate::commands::{ShiftControllerOrigin, TeleportController};
+use crate::components::{
+    AimTransform, AutoCrouch, ClimbPath, ContactTransform, ControllerAction, ControllerActivation,
+    ControllerAsleep, ControllerBody, ControllerBounds, ControllerCommand, ControllerCommands,
+    ControllerForces, ControllerFrame, ControllerInput, ControllerIntents, ControllerLod,
+    ControllerSettings, ControllerStances, ControllerState, CrushResponse, DynamicGravity,
+    FacingMode, FloatSettings, FluidVolume, FootPlacement, FormationSlot, ForwardObstruction,
+    GrappleState, GravitySource, GroundCastFilter, GroundCastInfo, GroundCastSettings, GroundLost,
+    GroundSurface, InteractionRay, Jetpack, JumpArc, JumpSettings, KeepDistance, LadderVolume,
+    LandingPrediction, Ledge, LedgeHang, LedgeHangState, LodViewer, MovementBlockage,
+    MovementBlockedReason, ObstructionHeight, PathFollow, PatrolRoute, PlatformCarry,
+    ReferenceFrame, RenderInterpolation, ShiftWithOrigin, SprintStamina, StanceBlend,
+    SurfaceTraction, Vault, VaultArc, VehicleInterior, VolumeKind, VolumeOverlaps, WallContact,
+    WorldPosition, ZoneVolume,
+};
+use crate::diagnostics::{count, Counter};
+use crate::events::{
+    ActivationChangedEvent, ClimbingChangedEvent, ControllerRecoveredEvent, CrushedEvent,
+    DashEvent, FacingLockChangedEvent, GlidingChangedEvent, GroundedEvent, JumpEvent, JumpKind,
+    JumpLinkFailedEvent, JumpLinkFinishedEvent, JumpLinkStartedEvent, KnockbackEvent, LandedEvent,
+    LedgeDetectedEvent, LedgeGrabbedEvent, LedgeReleaseCause, LedgeReleasedEvent,
+    MovementBlockedEvent, OutOfBoundsEvent, PathProgressEvent, PatrolWaypointEvent,
+    SkiddingChangedEvent, SlidingChangedEvent, StanceChangedEvent, SwimmingChangedEvent,
+    TurnInPlaceEvent, UngroundedCause, UngroundedEvent, VaultFinishedEvent, VaultStartedEvent,
+    WakeControllerEvent, WallSlidingChangedEvent,
+};
+use crate::resources::WorldOrigin;
 use crate::WanderlustPhysicsTweaks;
-use bevy::{math::*, prelude::*};
+use bevy::reflect::{ReflectMut, ReflectRef};
+use bevy::{ecs::entity::Entities, math::*, prelude::*, utils::HashMap};
 use bevy_rapier3d::prelude::*;
+use std::marker::PhantomData;
+use std::ops::Range;
 
-/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.
-/// This system is useful for cases such as running on a fixed timestep.*
+/// How quickly the estimate of the ground's acceleration follows new measurements, from 0.0 to 1.0.
+const GROUND_ACCELERATION_SMOOTHING: f32 = 0.5;
+/// How far forward a character is nudged onto a ledge by [`JumpSettings::ledge_forgiveness`](crate::JumpSettings::ledge_forgiveness).
+const LEDGE_FORGIVENESS_NUDGE: f32 = 0.05;
+/// How far in front of and behind a contact to probe for obstacles when looking for an edge to nudge around.
+const CORNER_NUDGE_PROBE: f32 = 0.1;
+/// How many straight pieces each stretch between two waypoints of a [`PathFollow`] is split into.
+const PATH_SAMPLES: usize = 8;
+/// How many offsets to probe, evenly spaced up to [`LocomotionSettings::corner_nudge_width`](crate::LocomotionSettings::corner_nudge_width).
+const CORNER_NUDGE_STEPS: u32 = 4;
+/// How far above a character's feet [`ForwardObstruction`]'s lowest probe is cast, so it doesn't scrape the ground.
+const OBSTRUCTION_CLEARANCE: f32 = 0.05;
+/// How many times a kinematic character can hit something and slide along it in a single frame.
+const SLIDE_ITERATIONS: u32 = 4;
+/// How far kinematic characters stop short of what they run into, so they don't start the next frame touching it.
+const SLIDE_SKIN_WIDTH: f32 = 0.01;
+/// How far around the edge of a step to probe for its riser and tread, see [`GroundCastSettings::step_height`].
+const STEP_PROBE_DEPTH: f32 = 0.05;
+/// The most a step's riser can face up or down, as the cosine of its angle from the up vector.
+const STEP_RISER_MAX_SLOPE: f32 = 0.35;
+/// How much further than its radius and [`LocomotionSettings::max_step_height`](crate::LocomotionSettings::max_step_height)
+/// a character looks ahead for a step to step up onto.
+const STEP_UP_LOOKAHEAD: f32 = 0.2;
+/// How far past a step's riser to look down for its tread.
+const STEP_UP_INSET: f32 = 0.1;
+/// The slowest a character is assumed to be approaching a step, so it still rises onto it while starting to move.
+const STEP_UP_MIN_SPEED: f32 = 1.0;
+/// How much narrower than the crouched capsule the probe for a ceiling is, so it doesn't catch walls beside it.
+const CROUCH_PROBE_SCALE: f32 = 0.9;
+/// How far past a wall's face [`LedgeHang`] looks down for the wall's top.
+const LEDGE_INSET: f32 = 0.05;
+/// How much further than [`LedgeHang::hang_offset`] to probe for the wall while shimmying.
+const LEDGE_PROBE_MARGIN: f32 = 0.2;
+/// The fastest a character is moved towards where it should hang or climb, so grabbing a ledge doesn't snap it there.
+const LEDGE_SNAP_SPEED: f32 = 6.0;
+/// How many distances to probe, evenly spaced up to [`ControllerStances::push_out_distance`].
+const PUSH_OUT_STEPS: u32 = 4;
+/// How many directions to probe at each distance when pushing a character out, see [`ControllerStances::push_out_distance`].
+const PUSH_OUT_DIRECTIONS: u32 = 8;
+/// The fastest a character can move relative to its ground and still turn in place, see
+/// [`FacingSettings::turn_in_place_angle`](crate::FacingSettings::turn_in_place_angle).
+const TURN_IN_PLACE_MAX_SPEED: f32 = 0.5;
+/// How many directions around a character [`WallContact`] probes for walls in.
+const WALL_PROBE_DIRECTIONS: u32 = 8;
+/// The most a wall can face up or down, as the cosine of its angle from the up vector.
+const WALL_MAX_SLOPE: f32 = 0.3;
+/// How directly a character's movement has to point into a wall for it to slide down it, as the cosine of the angle
+/// between them.
+const WALL_SLIDE_MIN_PRESS: f32 = 0.3;
+/// The longest a vector in [`ControllerInput`] can be. Anything longer is a bug in whatever wrote it, and would only lose
+/// precision in the controller's math.
+const INPUT_MAX_MAGNITUDE: f32 = 1.0e6;
+/// How far apart the places kept by [`ControllerState::safe_position`] are at least.
+const SAFE_POSITION_SPACING: f32 = 1.0;
+/// How many places [`ControllerState::safe_position`] keeps.
+const SAFE_POSITION_HISTORY: usize = 8;
+/// How long a character's movement input has to be for [`MovementBlockage`] to check it.
+const BLOCKED_MIN_INPUT: f32 = 0.1;
+/// How far ahead of a character [`MovementBlockage`] looks for an obstacle.
+const BLOCKED_OBSTACLE_PROBE: f32 = 0.1;
+/// How many straight pieces the arc of a [`ControllerCommand::JumpTo`] is split into to check it for obstacles.
+const JUMP_LINK_SEGMENTS: usize = 8;
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
 ///
-/// The system that controls movement logic.
-pub fn movement(
-    mut bodies: Query<(
-        Entity,
-        &GlobalTransform,
-        &mut ExternalImpulse,
-        &mut ControllerState,
-        &ControllerSettings,
-        &mut ControllerInput,
-    )>,
-    velocities: Query<&Velocity>,
+/// The first movement stage, see [`MovementSet::FindGround`](crate::MovementSet::FindGround). Decides which characters
+/// move this frame, casts for their ground, and writes what it found to their [`ControllerFrame`]. Also resets their
+/// [`ControllerForces`], for the later stages to fill in.
+///
+/// Ground selection is deterministic: if several colliders are hit at the same time of impact,
+/// the one with the lowest entity index is chosen.
+///
+/// When the physics engine interpolates the body, see [`PhysicsBackend::Interpolation`], its [`GlobalTransform`] lags
+/// behind the last physics step. This system reads the body's pose from the physics engine instead, so the ground cast
+/// and upright spring work from where the body actually is rather than where it is drawn.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn find_ground<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut Transform,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &mut ControllerFrame,
+            &mut ControllerForces,
+            Option<&mut GroundCastInfo>,
+            Option<&B::Interpolation>,
+            Option<&mut ControllerLod>,
+            Option<&GroundCastFilter>,
+            Option<&ReferenceFrame>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    velocities: Query<&B::Velocity>,
+    characters: Query<(), With<ControllerState>>,
+    interiors: Query<&VehicleInterior>,
     time: Res<Time>,
-    ctx: Res<RapierContext>,
-    mut ground_casts: Local<Vec<(Entity, Toi)>>,
+    ctx: Res<B::Context>,
+    config: Res<B::Config>,
+    mut ground_casts: Local<GroundCastBatch<B>>,
+    mut contacts: Local<Vec<Entity>>,
+    entities: &Entities,
+    mut crushed_events: EventWriter<CrushedEvent>,
+    mut sliding_events: EventWriter<SlidingChangedEvent>,
 ) {
-    for (entity, tf, mut body, mut controller, settings, mut input) in bodies.iter_mut() {
-        let dt = time.delta_seconds();
+    // Cast for the ground of every character in one pass, before any of them is moved.
+    ground_casts.clear(entities);
+    for (entity, tf, _, controller, settings, _, _, _, _, interpolation, lod, filter, _) in
+        bodies.iter()
+    {
+        if settings.clock.delta_seconds::<B>(&time, &config) == 0.0
+            || lod.is_some_and(|lod| !lod.due)
+        {
+            continue;
+        }
+        if controller.skip_ground_check_timer.is_running()
+            || settings.ground_cast.skip_ground_check_override
+            || settings.fly.enabled
+        {
+            ground_casts.invalidate(entity);
+            continue;
+        }
+
+        let velocity = match settings.body {
+            ControllerBody::Dynamic => B::linear_velocity(
+                velocities
+                    .get(entity)
+                    .expect("Character controllers must have a velocity component"),
+            ),
+            ControllerBody::Kinematic => controller.kinematic_velocity.linvel,
+        };
+        let tf = physics_transform::<B>(&ctx, entity, tf, interpolation, &config);
+        ground_casts.cast(
+            &ctx,
+            entity,
+            tf.mul_vec3(settings.ground_cast.origin),
+            tf.to_scale_rotation_translation().1,
+            -settings.up_vector,
+            &settings.ground_cast.collider,
+            settings.ground_cast.length + downhill_snap(controller, settings, velocity),
+            &settings.ground_cast,
+            filter,
+        );
+    }
+
+    for (
+        entity,
+        tf,
+        mut transform,
+        mut controller,
+        settings,
+        input,
+        mut frame,
+        mut forces,
+        ground_cast_info,
+        interpolation,
+        lod,
+        filter,
+        reference_frame,
+    ) in bodies.iter_mut()
+    {
+        frame.active = false;
+        let mut dt = settings.clock.delta_seconds::<B>(&time, &config);
 
         // Sometimes, such as at the beginning of the game, deltatime is 0. This
         // can cause division by 0 so I just skip those frames. A better solution
         // is a fixed framerate that has a static dt, but bevy doesn't have
         // that to my knowledge.
         if dt == 0.0 {
-            return;
+            continue;
+        }
+
+        // Characters ticked at a lower rate catch up on the time they skipped.
+        if let Some(mut lod) = lod {
+            lod.elapsed += dt;
+            if !lod.due {
+                continue;
+            }
+            dt = std::mem::take(&mut lod.elapsed);
         }
+        count(Counter::CharactersMoved, 1);
+
+        let velocity = match settings.body {
+            ControllerBody::Dynamic => {
+                let velocity = velocities
+                    .get(entity)
+                    .expect("Character controllers must have a velocity component");
+                Velocity {
+                    linvel: B::linear_velocity(velocity),
+                    angvel: B::angular_velocity(velocity),
+                }
+            }
+            ControllerBody::Kinematic => controller.kinematic_velocity,
+        };
+
+        let tf = &physics_transform::<B>(&ctx, entity, tf, interpolation, &config);
+        *forces = ControllerForces::default();
 
         // Get the ground and velocities
-        let ground_cast = if controller.skip_ground_check_timer == 0.0
-            && !settings.skip_ground_check_override
+        let casts = ground_casts.hits(entity);
+        let mut surface = None;
+        let mut slope = None;
+        let ground_cast = if !controller.skip_ground_check_timer.is_running()
+            && !settings.ground_cast.skip_ground_check_override
+            && !settings.fly.enabled
         {
-            intersections_with_shape_cast(
-                &*ctx,
-                tf.mul_vec3(settings.float_cast_origin),
-                tf.to_scale_rotation_translation().1,
-                -settings.up_vector,
-                &settings.float_cast_collider,
-                settings.float_cast_length,
-                QueryFilter::new()
-                    .predicate(&|collider| collider != entity)
-                    .exclude_sensors(),
-                &mut *ground_casts,
-            );
-            ground_casts
+            let feet = character_feet(tf, &controller, settings);
+            let filter = |collider| {
+                collider != entity && filter.is_none_or(|filter| filter.allows(collider))
+            };
+            casts
                 .iter()
-                .filter(|(_, i)| {
-                    i.status != TOIStatus::Penetrating
-                        && i.normal1.angle_between(settings.up_vector) <= settings.max_ground_angle
+                .filter(|hit| {
+                    // The query pipeline isn't updated until the end of the frame, so it may still return
+                    // colliders of entities that were despawned this frame.
+                    entities.contains(hit.entity)
+                        && (settings.ground_cast.stand_on_characters
+                            || !characters.contains(hit.entity))
+                        && !hit.penetrating
+                })
+                .find_map(|hit| {
+                    let (classified, ground) = if hit.normal.angle_between(settings.up_vector)
+                        <= settings.ground_cast.max_ground_angle
+                    {
+                        (GroundSurface::Flat, Some(*hit))
+                    } else if let Some(normal) = step_tread::<B>(&ctx, &filter, hit, feet, settings)
+                    {
+                        (GroundSurface::Step, Some(ShapeHit { normal, ..*hit }))
+                    } else {
+                        if surface.is_none() {
+                            slope = Some(*hit);
+                        }
+                        (GroundSurface::Slope, None)
+                    };
+                    surface.get_or_insert(classified);
+                    ground
                 })
-                .next()
-                .cloned()
         } else {
-            controller.skip_ground_check_timer = (controller.skip_ground_check_timer - dt).max(0.0);
+            controller.skip_ground_check_timer.tick(dt);
             None
         };
 
+        // Slide off of other characters instead of standing on their heads.
+        let character_slide = if settings.ground_cast.stand_on_characters {
+            Vec3::ZERO
+        } else {
+            casts
+                .iter()
+                .find(|hit| {
+                    characters.contains(hit.entity)
+                        && hit.distance
+                            <= settings.float_distance(controller.crouched)
+                                + settings.float.max_offset
+                })
+                .map(|hit| {
+                    let up = settings.up_vector;
+                    let away = (hit.normal - up * hit.normal.dot(up)).normalize_or_zero();
+                    // Right on top, so any direction will do.
+                    let away = if away == Vec3::ZERO {
+                        (tf.forward() - up * tf.forward().dot(up)).normalize_or_zero()
+                    } else {
+                        away
+                    };
+                    away * settings.ground_cast.character_slide_force * dt
+                })
+                .unwrap_or(Vec3::ZERO)
+        };
+
         // If we hit something, just get back up instead of waiting.
-        if ctx.contacts_with(entity).next().is_some() {
-            controller.skip_ground_check_timer = 0.0;
+        B::touching(&ctx, entity, &mut contacts);
+        if !contacts.is_empty() {
+            controller.skip_ground_check_timer.stop();
         }
 
-        let float_offset = if let Some((_, toi)) = ground_cast {
-            Some(toi.toi - settings.float_distance)
-        } else {
-            None
-        };
+        let float_offset =
+            ground_cast.map(|hit| hit.distance - settings.float_distance(controller.crouched));
 
+        let max_offset =
+            settings.float.max_offset + downhill_snap(&controller, settings, velocity.linvel);
         let grounded = float_offset
-            .map(|offset| {
-                offset <= settings.max_float_offset && offset >= settings.min_float_offset
-            })
+            .map(|offset| offset <= max_offset && offset >= settings.float.min_offset)
             .unwrap_or(false);
+        controller.float_offset = float_offset.filter(|_| grounded).unwrap_or(0.0);
+
+        let contact = ground_cast.map(|hit| ContactTransform {
+            position: hit.point,
+            normal: hit.normal,
+        });
+        if let (true, Some(contact)) = (grounded, contact) {
+            controller.last_ground_contact = contact;
+        }
+
+        // Ground too steep to stand on, right where the ground would be, is slid down instead. It also keeps a character
+        // standing at its foot from being pushed up it.
+        let steep_slope = slope.filter(|hit| {
+            hit.distance <= settings.float_distance(controller.crouched) + settings.float.max_offset
+        });
+
+        let sliding =
+            character_slide != Vec3::ZERO || (steep_slope.is_some() && ground_cast.is_none());
+        if sliding != controller.sliding {
+            controller.sliding = sliding;
+            let slope_contact = steep_slope.map(|hit| ContactTransform {
+                position: hit.point,
+                normal: hit.normal,
+            });
+            sliding_events.send(SlidingChangedEvent {
+                entity,
+                sliding,
+                contact: contact
+                    .or(slope_contact)
+                    .unwrap_or(controller.last_ground_contact),
+            });
+        }
 
         if grounded {
-            controller.remaining_jumps = settings.extra_jumps;
-            controller.coyote_timer = settings.coyote_time_duration;
+            controller.remaining_jumps = settings.jump.extra_jumps;
+            controller
+                .coyote_timer
+                .start(settings.jump.coyote_time_duration);
+        } else {
+            controller.coyote_timer.tick(dt);
+        }
+
+        // Get the character out from between colliders squeezing it, before the solver makes it explode out.
+        match crushing_colliders::<B>(&ctx, entity, settings.crush.penetration) {
+            Some(colliders) => {
+                controller.crush_timer += dt;
+                if controller.crush_timer >= settings.crush.duration {
+                    controller.crush_timer = 0.0;
+                    crushed_events.send(CrushedEvent { entity, colliders });
+                    match settings.crush.response {
+                        CrushResponse::None => {}
+                        CrushResponse::TeleportUp(distance) => {
+                            transform.translation += settings.up_vector * distance;
+                        }
+                    }
+                }
+            }
+            None => controller.crush_timer = 0.0,
+        }
+
+        // Lift the character over ledges it barely failed to clear.
+        if !grounded && settings.jump.ledge_forgiveness > 0.0 {
+            if let Some(lift) = ledge_forgiveness::<B>(&ctx, entity, settings, input.movement) {
+                transform.translation += lift;
+            }
+        }
+
+        // Collect velocities
+        // A platform the character is parented to already moves it, so its velocity must not be inherited as well.
+        // A turning platform moves faster further from where it turns around, so take its velocity where the character
+        // stands on it.
+        let ground_vel = ground_cast
+            .filter(|hit| {
+                settings.locomotion.platform_carry != PlatformCarry::Parent
+                    || controller.carry_parent != Some(hit.entity)
+            })
+            .and_then(|hit| {
+                let velocity = velocities.get(hit.entity).ok()?;
+                let angvel = B::angular_velocity(velocity);
+                let center = B::center_of_mass(&ctx, hit.entity).unwrap_or(hit.point);
+                let linvel = B::linear_velocity(velocity) + angvel.cross(hit.point - center);
+                Some((linvel, angvel))
+            });
+
+        // Stay inside a vehicle while jumping around in it, for as long as it is still below the character.
+        let prev_vehicle = controller.vehicle;
+        let position = tf.translation();
+        // A character moving in a reference frame is always inside it.
+        let frame_body = reference_frame.and_then(|frame| frame.body);
+        controller.vehicle = match (frame_body, ground_cast.filter(|_| grounded)) {
+            (Some(body), _) => Some(body),
+            (None, Some(hit)) => {
+                B::body_entity(&ctx, hit.entity).filter(|&body| interiors.contains(body))
+            }
+            (None, None) => prev_vehicle.filter(|&vehicle| {
+                vehicle_below::<B>(&ctx, entity, settings, position, vehicle, &interiors)
+            }),
+        };
+        let vehicle = controller
+            .vehicle
+            .filter(|&vehicle| controller.carry_parent != Some(vehicle));
+        // The vehicle's velocity where the airborne character is, which it keeps moving along with.
+        let vehicle_vel = vehicle
+            .filter(|_| ground_vel.is_none())
+            .and_then(|vehicle| {
+                let velocity = velocities.get(vehicle).ok()?;
+                let angvel = B::angular_velocity(velocity);
+                let center = B::center_of_mass(&ctx, vehicle).unwrap_or(position);
+                let linvel = B::linear_velocity(velocity) + angvel.cross(position - center);
+                Some((linvel, angvel))
+            });
+
+        // How much the ground's velocity changed since last frame. Only meaningful if we are still
+        // standing on the same ground, otherwise stepping onto a moving platform would look like acceleration.
+        let ground_vel_delta = match (ground_cast, ground_vel) {
+            (Some(hit), Some((linvel, _))) if controller.last_ground == Some(hit.entity) => {
+                Some(linvel - controller.last_ground_velocity)
+            }
+            _ => None,
+        };
+        // Smooth the ground's vertical acceleration over the last few frames, so a single jerky frame
+        // doesn't throw off the float spring.
+        controller.ground_vertical_acceleration = match ground_vel_delta {
+            Some(delta) => {
+                let accel = settings.up_vector.dot(delta) / dt;
+                let prev = controller.ground_vertical_acceleration;
+                prev + (accel - prev) * GROUND_ACCELERATION_SMOOTHING
+            }
+            None => 0.0,
+        };
+        let (prev_ground, prev_ground_vel) =
+            (controller.last_ground, controller.last_ground_velocity);
+        let ground = ground_cast.map(|hit| hit.entity);
+        // A moving ground that left while the character was standing still must have moved out from under it.
+        let ground_lost = match prev_ground {
+            Some(prev) if ground != Some(prev) => {
+                if !entities.contains(prev) {
+                    Some(GroundLost::Despawned)
+                } else if prev_ground_vel != Vec3::ZERO && input.movement == Vec3::ZERO {
+                    Some(GroundLost::MovedAway)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        if let Some(mut info) = ground_cast_info {
+            info.ground = ground;
+            if let Some(hit) = ground_cast {
+                info.point = hit.point;
+                info.normal = hit.normal;
+                info.distance = hit.distance;
+                info.penetrating = hit.penetrating;
+            }
+            info.grounded = grounded;
+            info.surface = surface;
+            info.lost = ground_lost;
+        }
+        controller.last_ground = ground;
+        controller.last_ground_velocity =
+            ground_vel.map(|(linvel, _)| linvel).unwrap_or(Vec3::ZERO);
+        controller.ground_vertical_velocity =
+            settings.up_vector.dot(controller.last_ground_velocity);
+
+        // While airborne inside a vehicle, follow its acceleration, and turn the velocity relative to it as the vehicle
+        // turns, so jumps keep their course inside it.
+        let vehicle_vel_delta = match vehicle_vel {
+            Some((linvel, angvel)) if vehicle.is_some() && vehicle == prev_vehicle => {
+                let relative = velocity.linvel - controller.vehicle_velocity;
+                Some(linvel - controller.vehicle_velocity + angvel.cross(relative) * dt)
+            }
+            _ => None,
+        };
+        let (ground_velocity, ground_angular_velocity) = ground_vel
+            .or(vehicle_vel)
+            .unwrap_or((Vec3::ZERO, Vec3::ZERO));
+        controller.vehicle_velocity = if vehicle.is_some() {
+            ground_velocity
         } else {
-            controller.coyote_timer = (controller.coyote_timer - dt).max(0.0);
+            Vec3::ZERO
+        };
+        controller.ground_relative_velocity = velocity.linvel - ground_velocity;
+
+        let (_, rotation, position) = tf.to_scale_rotation_translation();
+        let mut actions = std::mem::take(&mut frame.actions);
+        actions.clear();
+        *frame = ControllerFrame {
+            active: true,
+            dt,
+            position,
+            rotation,
+            velocity,
+            ground: ground_cast,
+            grounded,
+            ground_velocity,
+            ground_velocity_delta: ground_vel_delta.or(vehicle_vel_delta).unwrap_or(Vec3::ZERO),
+            ground_angular_velocity,
+            character_slide,
+            steep_slope,
+            previous_ground: prev_ground,
+            lost: ground_lost,
+            jumped: false,
+            actions,
+        };
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The gravity stage, see [`MovementSet::Gravity`](crate::MovementSet::Gravity). Pulls characters down while there is
+/// no ground below them.
+pub fn apply_gravity(
+    mut bodies: Query<
+        (&ControllerSettings, &ControllerFrame, &mut ControllerForces),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (settings, frame, mut forces) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
         }
 
-        // Gravity
-        let gravity = if ground_cast.is_none() {
-            settings.up_vector * -settings.gravity * dt
+        forces.gravity = if frame.ground.is_none() {
+            settings.up_vector * -settings.gravity * frame.dt
         } else {
             Vec3::ZERO
         };
 
-        // Collect velocities
-        let velocity = velocities
-            .get(entity)
-            .expect("Character controllers must have a Velocity component");
-        let ground_vel;
+        if let Some(slope) = frame.steep_slope {
+            let downhill = (-settings.up_vector)
+                .reject_from(slope.normal)
+                .normalize_or_zero();
+            forces.gravity += downhill * settings.ground_cast.slope_slide_acceleration * frame.dt;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The float stage, see [`MovementSet::Float`](crate::MovementSet::Float). Holds characters at
+/// [`FloatSettings::distance`] above their ground.
+#[allow(clippy::type_complexity)]
+pub fn apply_float(
+    mut bodies: Query<
+        (
+            &ControllerState,
+            &ControllerSettings,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (controller, settings, frame, mut forces) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
+        let dt = frame.dt;
 
         // Calculate "floating" force, as seen [here](https://www.youtube.com/watch?v=qdskE8PJy6Q)
-        let mut float_spring = if let Some((ground, intersection)) = ground_cast {
-            ground_vel = velocities.get(ground).ok();
+        forces.float = if let Some(hit) = frame.ground {
+            let vel_align = (-settings.up_vector).dot(frame.velocity.linvel);
+            // Damp relative to where the ground's velocity is heading rather than where it is, so launch-style
+            // elevators and bounce platforms don't make the spring overshoot.
+            let ground_vel_align = (-settings.up_vector).dot(frame.ground_velocity)
+                - controller.ground_vertical_acceleration
+                    * dt
+                    * settings.float.ground_acceleration_feed_forward;
+
+            // Moving along a slope also moves up or down it, which the spring shouldn't hold back.
+            let slope_align = if settings.locomotion.project_on_ground && frame.grounded {
+                let horizontal =
+                    (frame.velocity.linvel - frame.ground_velocity).reject_from(settings.up_vector);
+                horizontal.dot(hit.normal) / hit.normal.dot(settings.up_vector)
+            } else {
+                0.0
+            };
+
+            let relative_align = vel_align - ground_vel_align - slope_align;
 
-            let vel_align = (-settings.up_vector).dot(velocity.linvel);
-            let ground_vel_align =
-                (-settings.up_vector).dot(ground_vel.map(|v| v.linvel).unwrap_or(Vec3::ZERO));
+            let snap = hit.distance - settings.float_distance(controller.crouched);
 
-            let relative_align = vel_align - ground_vel_align;
+            // Follow vertical changes in ground velocity (elevators starting/stopping) immediately
+            // rather than waiting for the spring to catch up.
+            let elevator = settings.up_vector
+                * settings.up_vector.dot(frame.ground_velocity_delta)
+                * settings.float.vertical_ground_velocity_tracking;
+
+            // Past the usual float range, only a fast character hugging the terrain is still grounded. Keep it from
+            // flying off by taking away its velocity away from the ground.
+            let hug = if frame.grounded && snap > settings.float.max_offset {
+                let away = (frame.velocity.linvel - frame.ground_velocity).dot(hit.normal);
+                -hit.normal * away.max(0.0)
+            } else {
+                Vec3::ZERO
+            };
 
-            let snap = intersection.toi - settings.float_distance;
+            // A character that walked or ran off of its ground rather than jumping is pulled back down onto it.
+            let stick = if settings.float.max_snap_speed > 0.0
+                && controller.is_grounded
+                && !frame.grounded
+                && !controller.jump_timer.is_running()
+                && snap > settings.float.max_offset
+            {
+                // Keep up with the ground falling away under the character as well as closing the gap.
+                let horizontal =
+                    (frame.velocity.linvel - frame.ground_velocity).reject_from(settings.up_vector);
+                let fall_away = horizontal.dot(hit.normal) / hit.normal.dot(settings.up_vector);
+                let pull = (fall_away.max(0.0) + snap / dt).min(settings.float.max_snap_speed);
+                -settings.up_vector * (pull - (vel_align - ground_vel_align)).max(0.0)
+            } else {
+                Vec3::ZERO
+            };
 
-            (-settings.up_vector)
-                * ((snap * settings.float_strength) - (relative_align * settings.float_dampen))
+            (-settings.up_vector) * float_spring_force(&settings.float, snap, relative_align)
+                + elevator
+                + hug
+                + stick
         } else {
-            ground_vel = None;
             Vec3::ZERO
         };
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The locomotion stage, see [`MovementSet::Movement`](crate::MovementSet::Movement). Accelerates characters towards
+/// the velocity their [`ControllerInput::movement`] asks for.
+#[allow(clippy::type_complexity)]
+pub fn apply_movement<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+            Option<&B::Collider>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    surfaces: Query<&SurfaceTraction>,
+    ctx: Res<B::Context>,
+    mut skid_events: EventWriter<SkiddingChangedEvent>,
+) {
+    for (entity, mut controller, settings, input, frame, mut forces, collider) in bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let dt = frame.dt;
+
+        // Let a knockback carry the character, and pick up moving from wherever it left it.
+        if controller.knockback_lockout.is_running() {
+            controller.knockback_lockout.tick(dt);
+            controller.last_goal_velocity = frame.velocity.linvel;
+            continue;
+        }
+
+        let ground_plane = frame
+            .ground
+            .filter(|_| frame.grounded && settings.locomotion.project_on_ground)
+            .map(|hit| hit.normal);
 
         // Calculate horizontal movement force
         let movement = {
@@ -125,148 +690,4487 @@ pub fn movement(
 
             // let vel_dot = unit_dir.dot(unit_vel);
 
-            let accel = settings.acceleration;
+            let accel = settings.acceleration(controller.sprinting);
 
-            let input_goal_vel = dir * settings.max_speed;
+            let input_goal_vel =
+                dir * settings.max_speed(controller.crouched, controller.sprinting);
+            let input_goal_vel = match ground_plane {
+                Some(normal) => {
+                    input_goal_vel.reject_from(normal).normalize_or_zero() * input_goal_vel.length()
+                }
+                None => input_goal_vel,
+            };
+
+            // Shift the previous goal by the platform's change in velocity, so platform acceleration
+            // is followed immediately instead of being smoothed out by the lerp below.
+            let platform_accel =
+                frame.ground_velocity_delta * settings.locomotion.ground_acceleration_feed_forward;
 
             let goal_vel = Vec3::lerp(
-                controller.last_goal_velocity,
-                input_goal_vel + ground_vel.map(|v| v.linvel).unwrap_or(Vec3::ZERO),
+                controller.last_goal_velocity + platform_accel,
+                input_goal_vel + frame.ground_velocity,
                 (accel * dt).min(1.0),
             );
 
-            let needed_accel = goal_vel - velocity.linvel;
+            let needed_accel = goal_vel - frame.velocity.linvel;
+
+            // Keep whatever speed the character has beyond what its input asks for, only letting it drag down.
+            let needed_accel = match settings.locomotion.external_momentum_drag {
+                Some(drag) => {
+                    let up = settings.up_vector;
+                    let velocity = (frame.velocity.linvel - frame.ground_velocity).reject_from(up);
+                    let goal_speed = (goal_vel - frame.ground_velocity).reject_from(up).length();
+                    let excess = velocity.length() - goal_speed;
+                    if excess > 0.0 {
+                        let along = velocity.normalize_or_zero();
+                        needed_accel
+                            - along * needed_accel.dot(along).min(0.0)
+                            - along * (drag * dt).min(excess)
+                    } else {
+                        needed_accel
+                    }
+                }
+                None => needed_accel,
+            };
+
+            let max_accel_force = settings.locomotion.max_acceleration_force;
+
+            let needed_accel = (needed_accel - platform_accel).clamp_length_max(max_accel_force);
+
+            // Don't push up slopes too steep to stand on, or hold the character back from sliding down them.
+            let needed_accel = match frame.steep_slope {
+                Some(slope) => {
+                    let uphill = -slope
+                        .normal
+                        .reject_from(settings.up_vector)
+                        .normalize_or_zero();
+                    needed_accel - uphill * needed_accel.dot(uphill).max(0.0)
+                }
+                None => needed_accel,
+            };
 
-            let max_accel_force = settings.max_acceleration_force;
+            let needed_accel = match frame.ground.filter(|_| frame.grounded) {
+                Some(hit) if settings.traction.limit > 0.0 => {
+                    let traction = surfaces.get(hit.entity).map_or(1.0, |surface| surface.0);
+                    let limit = settings.traction.limit * traction * dt;
+                    let (needed_accel, skidding) =
+                        apply_traction(&mut controller, settings, frame, needed_accel, limit);
+                    if skidding != controller.skidding {
+                        controller.skidding = skidding;
+                        skid_events.send(SkiddingChangedEvent {
+                            entity,
+                            skidding,
+                            contact: ContactTransform {
+                                position: hit.point,
+                                normal: hit.normal,
+                            },
+                        });
+                    }
+                    needed_accel
+                }
+                _ => {
+                    if controller.skidding {
+                        controller.skidding = false;
+                        skid_events.send(SkiddingChangedEvent {
+                            entity,
+                            skidding: false,
+                            contact: controller.last_ground_contact,
+                        });
+                    }
+                    controller.skid_timer.stop();
+                    needed_accel
+                }
+            } + platform_accel;
 
-            let needed_accel = needed_accel.clamp_length_max(max_accel_force);
+            // Don't steer back into a wall that was just jumped off of, nor brake the jump away from it.
+            let needed_accel = if controller.wall_jump_lockout.is_running() {
+                controller.wall_jump_lockout.tick(dt);
+                let normal = controller.wall_jump_normal;
+                needed_accel - normal * needed_accel.dot(normal)
+            } else {
+                needed_accel
+            };
 
             controller.last_goal_velocity = goal_vel;
 
-            needed_accel * settings.force_scale
+            // Scale along the up vector rather than the world's Y axis.
+            let to_up = Quat::from_rotation_arc(Vec3::Y, settings.up_vector);
+            let needed_accel =
+                to_up * (settings.locomotion.force_scale * (to_up.inverse() * needed_accel));
+            // Tilt the horizontal part of the force onto the ground, so it pushes along the slope.
+            let needed_accel = match ground_plane {
+                Some(normal) => {
+                    let up = settings.up_vector;
+                    needed_accel - up * needed_accel.reject_from(up).dot(normal) / normal.dot(up)
+                }
+                None => needed_accel,
+            };
+
+            needed_accel + frame.character_slide
         };
 
+        // Slide around corners that the character catches on.
+        forces.movement = if settings.locomotion.corner_nudge > 0.0 {
+            movement
+                + corner_nudge::<B>(&ctx, entity, settings, input.movement, &frame.velocity) * dt
+        } else {
+            movement
+        };
+
+        // Rise onto a step ahead in time to clear it, instead of running into it or waiting for the float spring to
+        // pop the character up once it's underneath.
+        let collider = collider.map(B::collider_shape);
+        if let Some(rise) = step_up::<B>(
+            &ctx,
+            entity,
+            settings,
+            frame,
+            input.movement,
+            collider.as_ref(),
+        ) {
+            let up = settings.up_vector;
+            let climb = up.dot(frame.velocity.linvel - frame.ground_velocity);
+            forces.movement += up * (rise - climb).max(0.0);
+            forces.float = Vec3::ZERO;
+        }
+    }
+}
+
+/// If a grounded character moving along `movement` is about to run into a step no higher than
+/// [`LocomotionSettings::max_step_height`](crate::LocomotionSettings::max_step_height), returns how fast it needs to
+/// rise to be on top of the step by the time it reaches it.
+fn step_up<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    settings: &ControllerSettings,
+    frame: &ControllerFrame,
+    movement: Vec3,
+    collider: Option<&Collider>,
+) -> Option<f32> {
+    let up = settings.up_vector;
+    let max_height = settings.locomotion.max_step_height;
+    let dir = movement.reject_from(up).normalize_or_zero();
+    let hit = frame.ground.filter(|_| frame.grounded)?;
+    if max_height <= 0.0 || dir == Vec3::ZERO {
+        return None;
+    }
+    let radius = collider
+        .and_then(|collider| collider.as_capsule())
+        .map_or(0.0, |capsule| capsule.radius());
+    let speed = dir
+        .dot(frame.velocity.linvel - frame.ground_velocity)
+        .max(STEP_UP_MIN_SPEED);
+
+    let filter = |collider| collider != entity;
+
+    // Look for the riser of a step just above the character's feet.
+    let feet = frame.position - up * up.dot(frame.position - hit.point);
+    let lookahead = radius + max_height + STEP_UP_LOOKAHEAD.max(speed * frame.dt);
+    let riser = B::cast_ray(
+        ctx,
+        feet + up * STEP_PROBE_DEPTH,
+        dir,
+        lookahead,
+        true,
+        &filter,
+    )?;
+    if riser.distance == 0.0 || riser.normal.dot(up).abs() > STEP_RISER_MAX_SLOPE {
+        return None;
+    }
+
+    // And the tread on top of it, just past the riser.
+    let above = riser.point + dir * STEP_UP_INSET + up * (max_height + STEP_PROBE_DEPTH);
+    let tread = B::cast_ray(
+        ctx,
+        above,
+        -up,
+        max_height + STEP_PROBE_DEPTH,
+        true,
+        &filter,
+    )?;
+    let height = up.dot(tread.point - feet);
+    if tread.distance == 0.0
+        || height <= 0.0
+        || height > max_height
+        || tread.normal.angle_between(up) > settings.ground_cast.max_ground_angle
+    {
+        return None;
+    }
+
+    // Rising no faster than moving forward, as if going up a ramp, so the character isn't launched off a step it's
+    // right up against.
+    let time = ((riser.distance - radius).max(0.0) / speed).max(frame.dt);
+    Some((height / time).min(speed))
+}
+
+/// Limits `needed_accel`, the change in velocity the character asks for this frame relative to the ground, to what its
+/// [`TractionSettings`](crate::TractionSettings) allow, given it can slow down by at most `limit` this frame. Also returns
+/// whether the character is skidding.
+fn apply_traction(
+    controller: &mut ControllerState,
+    settings: &ControllerSettings,
+    frame: &ControllerFrame,
+    needed_accel: Vec3,
+    limit: f32,
+) -> (Vec3, bool) {
+    let up = settings.up_vector;
+    let direction = (frame.velocity.linvel - frame.ground_velocity)
+        .reject_from(up)
+        .normalize_or_zero();
+    let along = needed_accel.dot(direction);
+
+    if -along > limit {
+        controller.skid_timer.start(settings.traction.skid_duration);
+    } else {
+        controller.skid_timer.tick(frame.dt);
+        if !controller.skid_timer.is_running() {
+            return (needed_accel, false);
+        }
+    }
+
+    // Skidding: brake (or speed up) no faster than the limit, and hardly steer.
+    let sideways = (needed_accel - direction * along).reject_from(up);
+    let vertical = needed_accel - direction * along - sideways;
+    let accel = direction * along.clamp(-limit, limit)
+        + (sideways * settings.traction.skid_steering).clamp_length_max(limit)
+        + vertical;
+    (accel, true)
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The jump stage, see [`MovementSet::Jump`](crate::MovementSet::Jump). Starts, holds and cuts short jumps, with coyote
+/// time, jump buffering and extra jumps. The float spring is turned off while jumping. Also starts dashes, which
+/// [`dash`] carries out, since they are resolved against jumps by [`ActionSettings`](crate::ActionSettings).
+#[allow(clippy::type_complexity)]
+pub fn apply_jump(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &mut ControllerFrame,
+            &mut ControllerForces,
+            Option<&mut ControllerIntents>,
+            Option<&WallContact>,
+            Option<&mut Jetpack>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    time: Res<Time>,
+    mut actions: Local<Vec<ControllerAction>>,
+    mut jump_events: EventWriter<JumpEvent>,
+    mut dash_events: EventWriter<DashEvent>,
+) {
+    for (
+        entity,
+        mut controller,
+        settings,
+        input,
+        mut frame,
+        mut forces,
+        mut intents,
+        wall,
+        jetpack,
+    ) in bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let (dt, grounded, velocity) = (frame.dt, frame.grounded, frame.velocity);
+
         let just_jumped = input.jumping && !controller.jump_pressed_last_frame;
         if !grounded {
             if just_jumped {
-                controller.jump_buffer_timer = settings.jump_buffer_duration;
+                controller
+                    .jump_buffer_timer
+                    .start(ControllerAction::Jump.buffer_duration(settings));
             } else {
-                controller.jump_buffer_timer = (controller.jump_buffer_timer - dt).max(0.0);
+                controller.jump_buffer_timer.tick(dt);
             }
         }
 
         // Calculate jump force
-        let mut jump = if controller.jump_timer > 0.0 && !grounded {
+        let mut jump = if controller.jump_timer.is_running() && !grounded {
             if !input.jumping {
-                controller.jump_timer = 0.0;
-                velocity.linvel.project_onto(settings.up_vector) * -settings.jump_stop_force
+                controller.jump_timer.stop();
+                velocity.linvel.project_onto(settings.up_vector) * -settings.jump.stop_force
             } else {
-                controller.jump_timer = (controller.jump_timer - dt).max(0.0);
+                controller.jump_timer.tick(dt);
 
                 // Float force can lead to inconsistent jump power
-                float_spring = Vec3::ZERO;
+                forces.float = Vec3::ZERO;
 
-                settings.jump_force
+                held_jump_force(&settings.jump, controller.jump_timer.remaining())
                     * settings.up_vector
                     * dt
-                    * (settings.jump_decay_function)(
-                        (settings.jump_time - controller.jump_timer) / settings.jump_time,
-                    )
             }
         } else {
             Vec3::ZERO
         };
 
+        let now = time.seconds_since_startup();
+        let pending = |action: ControllerAction| {
+            intents.as_ref().is_some_and(|intents| {
+                intents.pending(action, now, action.buffer_duration(settings))
+            })
+        };
+        let jump_intent = pending(ControllerAction::Jump);
+
+        let just_dashed = input.dashing && !controller.dash_pressed_last_frame;
+        if just_dashed {
+            controller
+                .dash_buffer_timer
+                .start(ControllerAction::Dash.buffer_duration(settings));
+        } else {
+            controller.dash_buffer_timer.tick(dt);
+        }
+        controller.dash_cooldown.tick(dt);
+        let can_dash = settings.dash.speed > 0.0
+            && !settings.fly.enabled
+            && !controller.dash_cooldown.is_running()
+            && (grounded || !settings.dash.ground_only);
+
+        let wall = wall.filter(|wall| {
+            wall.wall.is_some()
+                && !grounded
+                && !controller.coyote_timer.is_running()
+                && (settings.wall.jump_away > 0.0 || settings.wall.jump_up > 0.0)
+        });
+
+        // A swimming character can only hop out of the fluid, and only near its surface.
+        let swimming = controller.swimming.is_some();
+        let climbing = controller.climbing.is_some();
+        let can_jump = if settings.fly.enabled {
+            false
+        } else if climbing {
+            true
+        } else if swimming {
+            controller.head_depth <= settings.swim.hop_depth
+        } else {
+            grounded
+                || controller.coyote_timer.is_running()
+                || wall.is_some()
+                || controller.remaining_jumps > 0
+        };
+
+        // Decide which of the actions that could trigger this frame are performed
+        actions.clear();
+        actions.extend_from_slice(&frame.actions);
+        if (just_jumped || controller.jump_buffer_timer.is_running() || jump_intent) && can_jump {
+            actions.push(ControllerAction::Jump);
+        }
+        let dash_buffered = controller.dash_buffer_timer.is_running();
+        if (just_dashed || dash_buffered || pending(ControllerAction::Dash)) && can_dash {
+            actions.push(ControllerAction::Dash);
+        }
+        settings.actions.resolve(&mut actions);
+        frame.actions.clone_from(&actions);
+
         // Trigger a jump
-        if (just_jumped || controller.jump_buffer_timer > 0.0)
-            && (grounded || controller.coyote_timer > 0.0 || controller.remaining_jumps > 0)
-        {
-            if !grounded && controller.coyote_timer == 0.0 {
+        if actions.contains(&ControllerAction::Jump) {
+            let kind = if climbing {
+                JumpKind::Climb
+            } else if swimming {
+                JumpKind::Swim
+            } else if grounded {
+                if just_jumped {
+                    JumpKind::Grounded
+                } else {
+                    JumpKind::Buffered
+                }
+            } else if controller.coyote_timer.is_running() {
+                JumpKind::Coyote
+            } else if wall.is_some() {
+                JumpKind::Wall
+            } else {
+                JumpKind::Air
+            };
+            if kind == JumpKind::Air {
                 controller.remaining_jumps -= 1;
             }
 
-            controller.jump_buffer_timer = 0.0;
-            controller.jump_timer = settings.jump_time;
-            controller.skip_ground_check_timer = settings.jump_skip_ground_check_duration;
+            controller.jump_buffer_timer.stop();
+            controller.jump_timer.start(settings.jump.time);
+            controller
+                .skip_ground_check_timer
+                .start(settings.jump.skip_ground_check_duration);
             // Negating the current velocity increases consistency for falling jumps,
-            // and prevents stacking jumps to reach high upwards velocities
-            jump = velocity.linvel * settings.up_vector * -1.0;
-            jump += settings.jump_initial_force * settings.up_vector;
+            // and prevents stacking jumps to reach high upwards velocities.
+            // Only the velocity relative to the ground is negated, so jumping off an elevator keeps its momentum.
+            jump = (velocity.linvel - frame.ground_velocity) * settings.up_vector * -1.0;
             // Float force can lead to inconsistent jump power
-            float_spring = Vec3::ZERO;
+            forces.float = Vec3::ZERO;
+            frame.jumped = true;
+            match wall {
+                Some(wall) if kind == JumpKind::Wall => {
+                    jump += settings.wall.jump_up * settings.up_vector
+                        + settings.wall.jump_away * wall.normal;
+                    // The movement worked out this frame may still be pressing into the wall.
+                    let into_wall = forces.movement.dot(wall.normal);
+                    forces.movement -= wall.normal * into_wall;
+                    controller.wall_jump_normal = wall.normal;
+                    controller
+                        .wall_jump_lockout
+                        .start(settings.wall.jump_lockout);
+                    jump_events.send(JumpEvent {
+                        entity,
+                        kind,
+                        remaining_jumps: controller.remaining_jumps,
+                        ground: wall.wall,
+                        contact: ContactTransform {
+                            position: wall.point,
+                            normal: wall.normal,
+                        },
+                    });
+                }
+                _ if kind == JumpKind::Climb => {
+                    let normal = controller.climb_normal;
+                    jump += settings.climb.jump_up * settings.up_vector
+                        + settings.climb.jump_away * normal;
+                    // Like a wall jump, don't steer back towards the ladder.
+                    let into_ladder = forces.movement.dot(normal);
+                    forces.movement -= normal * into_ladder;
+                    controller.wall_jump_normal = normal;
+                    controller
+                        .wall_jump_lockout
+                        .start(settings.climb.jump_lockout);
+                    jump_events.send(JumpEvent {
+                        entity,
+                        kind,
+                        remaining_jumps: controller.remaining_jumps,
+                        ground: controller.climbing,
+                        contact: ContactTransform {
+                            position: frame.position,
+                            normal,
+                        },
+                    });
+                }
+                _ if kind == JumpKind::Swim => {
+                    jump += settings.swim.hop_force * settings.up_vector;
+                    jump_events.send(JumpEvent {
+                        entity,
+                        kind,
+                        remaining_jumps: controller.remaining_jumps,
+                        ground: controller.swimming,
+                        contact: controller.swim_surface,
+                    });
+                }
+                _ => {
+                    jump += settings.jump.initial_force * settings.up_vector;
+                    jump_events.send(JumpEvent {
+                        entity,
+                        kind,
+                        remaining_jumps: controller.remaining_jumps,
+                        ground: frame.ground.map(|hit| hit.entity),
+                        contact: controller.last_ground_contact,
+                    });
+                }
+            }
         }
 
-        // Calculate force to stay upright
-        let upright = {
-            let (to_goal_axis, to_goal_angle) = {
-                let current = tf.up();
-                (
-                    current.cross(settings.up_vector).normalize_or_zero(),
-                    current.angle_between(settings.up_vector),
-                )
+        // Trigger a dash
+        if actions.contains(&ControllerAction::Dash) {
+            let up = settings.up_vector;
+            let flatten = |v: Vec3| (v - up * v.dot(up)).normalize_or_zero();
+            let direction = match flatten(input.movement) {
+                Vec3::ZERO => flatten(frame.rotation * Vec3::NEG_Z),
+                direction => direction,
             };
+            controller.dash_direction = direction;
+            controller.dash_buffer_timer.stop();
+            controller.dash_timer.start(settings.dash.duration);
+            controller
+                .dash_cooldown
+                .start(settings.dash.duration + settings.dash.cooldown);
+            dash_events.send(DashEvent { entity, direction });
+        }
 
-            ((to_goal_axis * (to_goal_angle * settings.upright_spring_strength))
-                - (velocity.angvel * settings.upright_spring_damping))
-                * dt
-        };
+        if let Some(intents) = intents.as_mut() {
+            for &action in actions.iter() {
+                intents.consume(action);
+            }
+            intents.expire(now, |action| action.buffer_duration(settings));
+        }
 
-        // Apply positional force to the rigidbody
-        body.impulse = movement + jump + float_spring + gravity + input.custom_impulse;
-        input.custom_impulse = Vec3::ZERO;
-        // Apply rotational force to the rigidbody
-        body.torque_impulse = upright + input.custom_torque;
-        input.custom_torque = Vec3::ZERO;
+        // Thrust once the jump is over, and drop any buffered jump so the character doesn't jump again when it lands.
+        if let Some(mut jetpack) = jetpack {
+            let thrusting = input.thrusting
+                && !grounded
+                && !swimming
+                && !climbing
+                && !settings.fly.enabled
+                && !controller.jump_timer.is_running()
+                && jetpack.fuel > 0.0;
+            if thrusting {
+                controller.jump_buffer_timer.stop();
+                forces.float = Vec3::ZERO;
+                jump += jetpack.thrust * settings.up_vector * dt;
+                jetpack.fuel = (jetpack.fuel - jetpack.burn_rate * dt).max(0.0);
+            } else if grounded {
+                jetpack.fuel = (jetpack.fuel + jetpack.regen_rate * dt).min(jetpack.max_fuel);
+            }
+            jetpack.thrusting = thrusting;
+        }
 
+        forces.jump = jump;
         controller.jump_pressed_last_frame = input.jumping;
+        controller.dash_pressed_last_frame = input.dashing;
     }
 }
 
-/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.
-/// Alternatively, if one only wants to disable the system, use [`WanderlustPhysicsTweaks`](WanderlustPhysicsTweaks).*
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
 ///
-/// This system adds some tweaks to rapier's physics settings that make the character controller behave better.
-pub fn setup_physics_context(
-    mut ctx: ResMut<RapierContext>,
-    should_change: Option<Res<WanderlustPhysicsTweaks>>,
+/// Finds the [`FluidVolume`] each character is in and how deep, and starts and stops swimming, see
+/// [`SwimSettings`](crate::SwimSettings). Runs after [`MovementSet::FindGround`](crate::MovementSet::FindGround), and
+/// unsets [`ControllerFrame::grounded`] while a character swims.
+#[allow(clippy::type_complexity)]
+pub fn detect_fluids<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut ControllerState,
+            &ControllerSettings,
+            &mut ControllerFrame,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    fluids: Query<(&FluidVolume, &GlobalTransform)>,
+    ctx: Res<B::Context>,
+    mut events: EventWriter<SwimmingChangedEvent>,
 ) {
-    if should_change.map(|s| s.should_do_tweaks()).unwrap_or(true) {
-        let params = &mut ctx.integration_parameters;
-        // This prevents any noticeable jitter when running facefirst into a wall.
-        params.erp = 0.99;
-        // This prevents (most) noticeable jitter when running facefirst into an inverted corner.
-        params.max_velocity_iterations = 16;
-        // TODO: Fix jitter that occurs when running facefirst into a normal corner.
+    for (entity, tf, mut controller, settings, mut frame) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
+        let up = settings.up_vector;
+        let feet = character_feet(tf, &controller, settings);
+
+        // Of the fluids the character's feet are in, the one it's deepest in.
+        let mut deepest: Option<(Entity, f32)> = None;
+        B::intersections_with_point(
+            &ctx,
+            feet,
+            &|collider| fluids.contains(collider),
+            &mut |collider| {
+                if let Ok((fluid, fluid_tf)) = fluids.get(collider) {
+                    let surface = up.dot(fluid_tf.translation()) + fluid.surface_height;
+                    let depth = surface - up.dot(feet);
+                    if deepest.is_none_or(|(_, deepest)| depth > deepest) {
+                        deepest = Some((collider, depth));
+                    }
+                }
+                true
+            },
+        );
+
+        let height = settings.swim.height.max(f32::EPSILON);
+        controller.submersion = match deepest {
+            Some((_, depth)) => {
+                controller.head_depth = depth - height;
+                controller.swim_surface = ContactTransform {
+                    position: feet + up * depth,
+                    normal: up,
+                };
+                (depth / height).clamp(0.0, 1.0)
+            }
+            None => 0.0,
+        };
+
+        let swimming = deepest.map(|(fluid, _)| fluid).filter(|_| {
+            settings.swim.max_speed > 0.0
+                && !settings.fly.enabled
+                && controller.submersion >= settings.swim.min_submersion
+        });
+        if swimming != controller.swimming {
+            if swimming.is_some() {
+                controller.jump_timer.stop();
+            }
+            let volume = swimming.or(controller.swimming);
+            controller.swimming = swimming;
+            if let Some(volume) = volume {
+                events.send(SwimmingChangedEvent {
+                    entity,
+                    volume,
+                    swimming: swimming.is_some(),
+                });
+            }
+        }
+        if swimming.is_some() {
+            frame.grounded = false;
+        }
     }
 }
 
-fn intersections_with_shape_cast(
-    ctx: &RapierContext,
-    shape_pos: Vec3,
-    shape_rot: Quat,
-    shape_vel: Vec3,
-    shape: &Collider,
-    max_toi: f32,
-    filter: QueryFilter,
-    collisions: &mut Vec<(Entity, Toi)>,
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Grabs onto and lets go of [`LadderVolume`]s, see [`ClimbSettings`](crate::ClimbSettings). Runs after
+/// [`MovementSet::FindGround`](crate::MovementSet::FindGround), and unsets [`ControllerFrame::grounded`] while a character
+/// climbs.
+#[allow(clippy::type_complexity)]
+pub fn detect_ladders<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &mut ControllerFrame,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ladders: Query<(&LadderVolume, &GlobalTransform)>,
+    ctx: Res<B::Context>,
+    mut events: EventWriter<ClimbingChangedEvent>,
 ) {
-    collisions.clear();
+    for (entity, mut controller, settings, input, mut frame) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
 
-    let orig_predicate = filter.predicate;
+        let mut found = None;
+        B::intersections_with_point(
+            &ctx,
+            frame.position,
+            &|collider| ladders.contains(collider),
+            &mut |collider| {
+                found = Some(collider);
+                false
+            },
+        );
 
-    loop {
-        let predicate = |entity| {
-            !collisions.iter().any(|coll| coll.0 == entity)
-                && orig_predicate.map(|pred| pred(entity)).unwrap_or(true)
-        };
-        let filter = filter.predicate(&predicate);
+        // Don't grab the ladder that was just jumped off of again.
+        let climbing = found
+            .filter(|_| {
+                settings.climb.speed > 0.0
+                    && !settings.fly.enabled
+                    && !controller.wall_jump_lockout.is_running()
+            })
+            .and_then(|ladder| {
+                let (volume, ladder_tf) = ladders.get(ladder).ok()?;
+                let rotation = ladder_tf.to_scale_rotation_translation().1;
+                let normal = (rotation * volume.normal).normalize_or_zero();
+                let axis = (rotation * volume.axis).normalize_or_zero();
 
-        if let Some(collision) =
-            ctx.cast_shape(shape_pos, shape_rot, shape_vel, shape, max_toi, filter)
-        {
-            collisions.push(collision);
+                let pressing = input
+                    .movement
+                    .reject_from(settings.up_vector)
+                    .normalize_or_zero();
+                let grabbing = pressing.dot(-normal) >= settings.climb.min_press;
+                // Let go once climbing down onto the ground.
+                let landed = frame.grounded && climb_input(input, normal, axis) < 0.0;
+                let holding = controller.climbing == Some(ladder) && !landed;
+                (holding || grabbing).then(|| {
+                    controller.climb_normal = normal;
+                    controller.climb_axis = axis;
+                    ladder
+                })
+            });
+
+        if climbing != controller.climbing {
+            if climbing.is_some() {
+                controller.jump_timer.stop();
+            }
+            let ladder = climbing.or(controller.climbing);
+            controller.climbing = climbing;
+            if let Some(ladder) = ladder {
+                events.send(ClimbingChangedEvent {
+                    entity,
+                    ladder,
+                    climbing: climbing.is_some(),
+                });
+            }
+        }
+        if climbing.is_some() {
+            frame.grounded = false;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves climbing characters along their ladders, see [`ClimbSettings`](crate::ClimbSettings). Runs after
+/// [`MovementSet::Jump`](crate::MovementSet::Jump), and replaces the gravity, float and movement forces the earlier stages
+/// worked out while a character climbs.
+#[allow(clippy::type_complexity)]
+pub fn climb(
+    mut bodies: Query<
+        (
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (mut controller, settings, input, frame, mut forces) in bodies.iter_mut() {
+        // A character jumping off keeps the forces of the jump.
+        if !frame.active || frame.jumped || controller.climbing.is_none() {
+            continue;
+        }
+        let axis = controller.climb_axis;
+        let goal_velocity =
+            axis * settings.climb.speed * climb_input(input, controller.climb_normal, axis);
+
+        forces.movement = goal_velocity - frame.velocity.linvel;
+        forces.gravity = Vec3::ZERO;
+        forces.float = Vec3::ZERO;
+        controller.last_goal_velocity = goal_velocity;
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves flying characters, see [`FlySettings`](crate::FlySettings). Runs last before
+/// [`MovementSet::ApplyForces`](crate::MovementSet::ApplyForces), and replaces all of the forces the earlier stages worked
+/// out while a character flies.
+#[allow(clippy::type_complexity)]
+pub fn fly<B: PhysicsBackend>(
+    mut commands: Commands,
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (entity, mut controller, settings, input, frame, mut forces) in bodies.iter_mut() {
+        let noclip = settings.fly.enabled && settings.fly.noclip;
+        if noclip != controller.noclip {
+            controller.noclip = noclip;
+            if noclip {
+                commands.entity(entity).insert(B::Sensor::default());
+            } else {
+                commands.entity(entity).remove::<B::Sensor>();
+            }
+        }
+        if !frame.active || !settings.fly.enabled {
+            continue;
+        }
+
+        let dir = input.movement.clamp_length_max(1.0);
+        let rate = if dir == Vec3::ZERO {
+            settings.fly.damping
         } else {
-            break;
+            settings.fly.acceleration
+        };
+        let goal_velocity = Vec3::lerp(
+            controller.last_goal_velocity,
+            dir * settings.fly.max_speed,
+            (rate * frame.dt).min(1.0),
+        );
+
+        forces.movement = goal_velocity - frame.velocity.linvel;
+        forces.jump = Vec3::ZERO;
+        forces.gravity = Vec3::ZERO;
+        forces.float = Vec3::ZERO;
+        forces.upright = Vec3::ZERO;
+        controller.last_goal_velocity = goal_velocity;
+    }
+}
+
+/// How fast a character wants to climb along `axis`, from -1.0 for down to 1.0 for up. Pressing towards the ladder
+/// counts as up.
+fn climb_input(input: &ControllerInput, normal: Vec3, axis: Vec3) -> f32 {
+    (input.movement.dot(-normal) + input.movement.dot(axis)).clamp(-1.0, 1.0)
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves swimming characters, see [`SwimSettings`](crate::SwimSettings). Runs after
+/// [`MovementSet::Jump`](crate::MovementSet::Jump), and replaces the gravity, float and movement forces the earlier stages
+/// worked out while a character swims.
+#[allow(clippy::type_complexity)]
+pub fn swim(
+    mut bodies: Query<
+        (
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    fluids: Query<&FluidVolume>,
+) {
+    for (mut controller, settings, input, frame, mut forces) in bodies.iter_mut() {
+        let fluid = match controller.swimming.and_then(|fluid| fluids.get(fluid).ok()) {
+            Some(fluid) if frame.active => fluid,
+            _ => continue,
+        };
+        let (up, dt) = (settings.up_vector, frame.dt);
+        let swim = &settings.swim;
+
+        // Buoyancy holds the character up instead of the float spring.
+        forces.float = Vec3::ZERO;
+        forces.gravity = up * settings.gravity * (fluid.density * controller.submersion - 1.0) * dt;
+
+        let dir = input.movement.clamp_length_max(1.0);
+        let goal_vel = Vec3::lerp(
+            controller.last_goal_velocity,
+            dir * swim.max_speed,
+            (swim.acceleration * dt).min(1.0),
+        );
+        let needed_accel = goal_vel - frame.velocity.linvel;
+        // Only steer up or down as much as the input asks to, and leave the rest to buoyancy.
+        let needed_accel =
+            needed_accel.reject_from(up) + up * needed_accel.dot(up) * dir.dot(up).abs();
+        let drag = frame.velocity.linvel * (fluid.drag * dt).min(1.0);
+
+        forces.movement = needed_accel.clamp_length_max(swim.max_acceleration_force) - drag;
+        controller.last_goal_velocity = goal_vel;
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves dashing characters, see [`DashSettings`](crate::DashSettings). Runs after
+/// [`MovementSet::Jump`](crate::MovementSet::Jump), which starts dashes, and replaces the movement force the earlier stages
+/// worked out while a character dashes.
+#[allow(clippy::type_complexity)]
+pub fn dash(
+    mut bodies: Query<
+        (
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (mut controller, settings, frame, mut forces) in bodies.iter_mut() {
+        if !frame.active || !controller.dash_timer.is_running() {
+            continue;
+        }
+        let up = settings.up_vector;
+        let velocity = frame.velocity.linvel - frame.ground_velocity;
+        let vertical = up * velocity.dot(up);
+        let dash_velocity = controller.dash_direction * settings.dash.speed;
+
+        // Reach the dash velocity right away, instead of as fast as the movement's acceleration allows.
+        forces.movement = dash_velocity - (velocity - vertical);
+        if settings.dash.ignore_gravity {
+            forces.movement -= vertical;
+            forces.gravity = Vec3::ZERO;
         }
+        // Slow down from the dash afterwards, rather than stopping short.
+        controller.last_goal_velocity = dash_velocity + frame.ground_velocity;
+        controller.dash_timer.tick(frame.dt);
     }
 }
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The rotation stage, see [`MovementSet::Upright`](crate::MovementSet::Upright). Keeps characters upright, and turns
+/// them to face the direction their [`FacingSettings`](crate::FacingSettings) ask for.
+#[allow(clippy::type_complexity)]
+pub fn apply_upright(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    mut facing_lock_events: EventWriter<FacingLockChangedEvent>,
+    mut turn_events: EventWriter<TurnInPlaceEvent>,
+) {
+    for (entity, mut controller, settings, input, frame, mut forces) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
+        let (dt, velocity) = (frame.dt, frame.velocity);
+        let forward = frame.rotation * Vec3::NEG_Z;
+
+        // Turning along with the ground means damping turning relative to the ground's instead.
+        let ground_yaw = if settings.facing.follow_ground_rotation
+            && (frame.grounded || controller.vehicle.is_some())
+        {
+            settings.up_vector * frame.ground_angular_velocity.dot(settings.up_vector)
+        } else {
+            Vec3::ZERO
+        };
+
+        // Calculate force to stay upright
+        forces.upright = {
+            let (to_goal_axis, to_goal_angle) = {
+                let current = frame.rotation * Vec3::Y;
+                (
+                    current.cross(settings.up_vector).normalize_or_zero(),
+                    current.angle_between(settings.up_vector),
+                )
+            };
+
+            // When facing is controlled, turning around the up vector is left for it to dampen.
+            let angvel = if settings.facing.mode == FacingMode::None {
+                velocity.angvel - ground_yaw
+            } else {
+                velocity.angvel - settings.up_vector * velocity.angvel.dot(settings.up_vector)
+            };
+
+            ((to_goal_axis * (to_goal_angle * settings.upright.spring_strength))
+                - (angvel * settings.upright.spring_damping))
+                * dt
+        };
+
+        // Calculate torque to turn towards the facing direction
+        forces.facing = {
+            let up = settings.up_vector;
+            let flatten = |v: Vec3| (v - up * v.dot(up)).normalize_or_zero();
+
+            // Moving too far behind the locked direction breaks the lock, so the character pivots to face where it is going.
+            let release_lock = settings.facing.mode == FacingMode::Locked
+                && settings.facing.release_lock_angle.is_some_and(|angle| {
+                    let (movement, facing) = (flatten(input.movement), flatten(input.facing));
+                    movement != Vec3::ZERO
+                        && facing != Vec3::ZERO
+                        && movement.angle_between(facing) > angle
+                });
+            if release_lock != controller.facing_lock_released {
+                controller.facing_lock_released = release_lock;
+                facing_lock_events.send(FacingLockChangedEvent {
+                    entity,
+                    released: release_lock,
+                });
+            }
+
+            let goal = match settings.facing.mode {
+                FacingMode::None => None,
+                FacingMode::Movement => Some(input.movement),
+                FacingMode::Locked if release_lock => Some(input.movement),
+                FacingMode::Locked => Some(input.facing),
+            };
+
+            match goal {
+                Some(goal) => {
+                    let mut goal = flatten(goal);
+                    if let Some(target) = input.look_at {
+                        goal += flatten(target - frame.position) * settings.facing.look_at_strength;
+                    }
+                    let angle = yaw_towards(up, forward, goal);
+
+                    if let Some(min_angle) = settings.facing.turn_in_place_angle {
+                        let speed = (velocity.linvel - frame.ground_velocity)
+                            .reject_from(up)
+                            .length();
+                        if !frame.grounded
+                            || speed > TURN_IN_PLACE_MAX_SPEED
+                            || angle.abs() < min_angle
+                        {
+                            controller.turning_in_place = false;
+                            controller.turn_in_place_timer.stop();
+                        } else if !controller.turning_in_place {
+                            controller.turning_in_place = true;
+                            controller
+                                .turn_in_place_timer
+                                .start(settings.facing.turn_in_place_delay);
+                            turn_events.send(TurnInPlaceEvent { entity, angle });
+                        } else {
+                            controller.turn_in_place_timer.tick(dt);
+                        }
+                    }
+                    // Hold still until the turning animation is ready to turn.
+                    let angle = if controller.turn_in_place_timer.is_running() {
+                        0.0
+                    } else {
+                        angle
+                    };
+
+                    (up * (angle * settings.facing.spring_strength)
+                        - up * ((velocity.angvel - ground_yaw).dot(up)
+                            * settings.facing.spring_damping))
+                        * dt
+                }
+                None => Vec3::ZERO,
+            }
+        };
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// The last movement stage, see [`MovementSet::ApplyForces`](crate::MovementSet::ApplyForces). Applies the sum of each
+/// character's [`ControllerForces`] to its body, along with [`ControllerInput::custom_impulse`] and
+/// [`ControllerInput::custom_torque`], and sends the events for it leaving or reaching the ground.
+#[allow(clippy::type_complexity)]
+pub fn apply_forces<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut B::Impulse,
+            &mut ControllerState,
+            &ControllerSettings,
+            &mut ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    mut rigid_bodies: Query<(&mut B::Body, Option<&B::Collider>)>,
+    ctx: Res<B::Context>,
+    mut ungrounded_events: EventWriter<UngroundedEvent>,
+    mut grounded_events: EventWriter<GroundedEvent>,
+    mut landed_events: EventWriter<LandedEvent>,
+) {
+    for (entity, mut transform, mut body, mut controller, settings, mut input, frame, mut forces) in
+        bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let dt = frame.dt;
+
+        forces.external_impulse = input.custom_impulse;
+        forces.external_torque = input.custom_torque;
+        input.custom_impulse = Vec3::ZERO;
+        input.custom_torque = Vec3::ZERO;
+
+        // A stage that worked with a NaN shouldn't pass it on to the body, where it would never go away.
+        let parts = &mut *forces;
+        for (source, force) in [
+            ("ControllerForces::movement", &mut parts.movement),
+            ("ControllerForces::jump", &mut parts.jump),
+            ("ControllerForces::float", &mut parts.float),
+            ("ControllerForces::gravity", &mut parts.gravity),
+            ("ControllerForces::upright", &mut parts.upright),
+            ("ControllerForces::facing", &mut parts.facing),
+            (
+                "ControllerForces::external_impulse",
+                &mut parts.external_impulse,
+            ),
+            (
+                "ControllerForces::external_torque",
+                &mut parts.external_torque,
+            ),
+        ] {
+            if !force.is_finite() {
+                report_non_finite(entity, source);
+                *force = Vec3::ZERO;
+            }
+        }
+        if !controller.last_goal_velocity.is_finite() {
+            report_non_finite(entity, "ControllerState::last_goal_velocity");
+            controller.last_goal_velocity = frame.velocity.linvel;
+        }
+
+        // Apply positional and rotational force to the rigidbody
+        let linear =
+            forces.movement + forces.jump + forces.float + forces.gravity + forces.external_impulse;
+        let angular = forces.upright + forces.facing + forces.external_torque;
+        // Only switch the rigidbody when the setting changes, leaving it to user code otherwise.
+        if controller.body != Some(settings.body) {
+            if let Ok((mut rigid_body, _)) = rigid_bodies.get_mut(entity) {
+                *rigid_body = B::body(settings.body);
+            }
+            controller.body = Some(settings.body);
+        }
+        match settings.body {
+            ControllerBody::Dynamic => {
+                B::apply_impulse(&mut body, linear, angular);
+            }
+            ControllerBody::Kinematic => {
+                // Move the body ourselves, sliding along whatever is in the way.
+                let mut kinematic_velocity = controller.kinematic_velocity;
+                kinematic_velocity.linvel += linear;
+                kinematic_velocity.angvel += angular;
+                if let Ok((_, shape)) = rigid_bodies.get(entity) {
+                    transform.translation += match shape.filter(|_| !controller.noclip) {
+                        Some(shape) => collide_and_slide::<B>(
+                            &ctx,
+                            entity,
+                            frame.position,
+                            frame.rotation,
+                            &B::collider_shape(shape),
+                            dt,
+                            &mut kinematic_velocity.linvel,
+                        ),
+                        None => kinematic_velocity.linvel * dt,
+                    };
+                }
+                transform.rotation =
+                    Quat::from_scaled_axis(kinematic_velocity.angvel * dt) * transform.rotation;
+                controller.kinematic_velocity = kinematic_velocity;
+            }
+        }
+
+        let now_grounded = frame.grounded && !frame.jumped;
+        if controller.is_grounded && !now_grounded {
+            let cause = if frame.jumped {
+                UngroundedCause::Jumped
+            } else if let Some(lost) = frame.lost {
+                UngroundedCause::GroundLost(lost)
+            } else {
+                UngroundedCause::WalkedOff
+            };
+            ungrounded_events.send(UngroundedEvent {
+                entity,
+                ground: frame.previous_ground,
+                cause,
+                contact: controller.last_ground_contact,
+            });
+        }
+        match (controller.is_grounded, now_grounded) {
+            (true, true) => {
+                controller.grounded_time += dt;
+                controller.fall_distance = 0.0;
+            }
+            (false, false) => {
+                controller.airborne_time += dt;
+                // Only count the way down, starting over at the apex of a jump.
+                let vertical_speed = frame.velocity.linvel.dot(settings.up_vector);
+                controller.fall_distance = if vertical_speed < 0.0 {
+                    controller.fall_distance - vertical_speed * dt
+                } else {
+                    0.0
+                };
+            }
+            (false, true) => {
+                controller.grounded_time = 0.0;
+                let ground = frame.ground.map(|hit| hit.entity);
+                grounded_events.send(GroundedEvent {
+                    entity,
+                    ground,
+                    contact: controller.last_ground_contact,
+                });
+                // A character spawned on the ground becomes grounded without ever being airborne.
+                if controller.airborne_time > 0.0 {
+                    let impact_speed = (frame.ground_velocity - frame.velocity.linvel)
+                        .dot(settings.up_vector)
+                        .max(0.0);
+                    landed_events.send(LandedEvent {
+                        entity,
+                        ground,
+                        impact_speed,
+                        contact: controller.last_ground_contact,
+                    });
+                }
+            }
+            (true, false) => {
+                controller.airborne_time = 0.0;
+                controller.fall_distance = 0.0;
+            }
+        }
+        // Remember where the character stood safely, for respawning it there.
+        let static_ground = frame.ground.is_some_and(|hit| {
+            B::body_entity(&ctx, hit.entity).is_none_or(|body| {
+                rigid_bodies
+                    .get(body)
+                    .map_or(true, |(body, _)| B::is_fixed(body))
+            })
+        });
+        if now_grounded
+            && static_ground
+            && !controller.sliding
+            && frame.steep_slope.is_none()
+            && controller.carry_parent.is_none()
+        {
+            let position = transform.translation;
+            let moved_on = controller
+                .safe_positions
+                .front()
+                .is_none_or(|last| last.distance(position) >= SAFE_POSITION_SPACING);
+            if moved_on {
+                controller.safe_positions.push_front(position);
+                controller.safe_positions.truncate(SAFE_POSITION_HISTORY);
+            }
+        }
+        controller.is_grounded = now_grounded;
+    }
+}
+
+/// How much further the ground can fall away from a grounded character and still be snapped to, see
+/// [`FloatSettings::downhill_snap`].
+fn downhill_snap(
+    controller: &ControllerState,
+    settings: &ControllerSettings,
+    velocity: Vec3,
+) -> f32 {
+    if controller.is_grounded {
+        settings.float.downhill_snap * velocity.reject_from(settings.up_vector).length()
+    } else {
+        0.0
+    }
+}
+
+/// The force of the float spring pulling a character towards the ground, given how much further from the ground than
+/// [`FloatSettings::distance`] it is, and how fast it moves towards the ground relative to the ground's own velocity.
+pub(crate) fn float_spring_force(float: &FloatSettings, offset: f32, relative_speed: f32) -> f32 {
+    offset * float.strength - relative_speed * float.dampen
+}
+
+/// The force pushing a character up while it holds a jump, `remaining` seconds before the jump ends.
+pub(crate) fn held_jump_force(jump: &JumpSettings, remaining: f32) -> f32 {
+    jump.force * (jump.decay_function)((jump.time - remaining) / jump.time)
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Reins in characters moving faster than [`RecoverySettings::max_speed`](crate::RecoverySettings::max_speed). Runs after
+/// [`MovementSet::FindGround`](crate::MovementSet::FindGround), so the later stages work with the recovered velocity.
+#[allow(clippy::type_complexity)]
+pub fn recover<B: PhysicsBackend>(
+    mut commands: Commands,
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &mut ControllerFrame,
+            Option<&mut B::Velocity>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    mut events: EventWriter<ControllerRecoveredEvent>,
+) {
+    for (entity, mut controller, settings, mut frame, velocity) in bodies.iter_mut() {
+        let max_speed = settings.recovery.max_speed;
+        let relative = frame.velocity.linvel - frame.ground_velocity;
+        if !frame.active || max_speed <= 0.0 || relative.length() <= max_speed {
+            continue;
+        }
+
+        let teleported_to = controller
+            .safe_position(0)
+            .filter(|_| settings.recovery.teleport);
+        let recovered = match teleported_to {
+            Some(position) => {
+                commands.add(TeleportController { entity, position });
+                Velocity::zero()
+            }
+            None => Velocity {
+                linvel: frame.ground_velocity + relative.clamp_length_max(max_speed),
+                angvel: frame.velocity.angvel,
+            },
+        };
+        frame.velocity = recovered;
+        controller.last_goal_velocity = recovered.linvel;
+        match settings.body {
+            ControllerBody::Dynamic => {
+                if let Some(mut velocity) = velocity {
+                    B::set_velocity(&mut velocity, recovered.linvel, recovered.angvel);
+                }
+            }
+            ControllerBody::Kinematic => controller.kinematic_velocity = recovered,
+        }
+        events.send(ControllerRecoveredEvent {
+            entity,
+            speed: relative.length(),
+            teleported_to,
+        });
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Carries out [`KnockbackEvent`]s. Runs before [`MovementSet::FindGround`](crate::MovementSet::FindGround), so the
+/// movement stages start from the new velocity.
+pub fn knockback<B: PhysicsBackend>(
+    mut bodies: Query<(
+        &mut ControllerState,
+        &ControllerSettings,
+        Option<&mut B::Velocity>,
+    )>,
+    mut events: EventReader<KnockbackEvent>,
+) {
+    for event in events.iter() {
+        let (mut controller, settings, velocity) = match bodies.get_mut(event.entity) {
+            Ok(body) => body,
+            Err(_) => continue,
+        };
+        match settings.body {
+            ControllerBody::Dynamic => {
+                if let Some(mut velocity) = velocity {
+                    let angular = B::angular_velocity(&velocity);
+                    B::set_velocity(&mut velocity, event.velocity, angular);
+                }
+            }
+            ControllerBody::Kinematic => controller.kinematic_velocity.linvel = event.velocity,
+        }
+        // Keep moving along with the knockback, rather than braking it back to the old goal.
+        controller.last_goal_velocity = event.velocity;
+        controller.knockback_lockout.start(event.lockout);
+        controller.jump_timer.stop();
+        controller.dash_timer.stop();
+        // Don't let the float spring pull a character launched upwards back down to the ground.
+        if event.velocity.dot(settings.up_vector) > 0.0 {
+            controller
+                .skip_ground_check_timer
+                .start(settings.jump.skip_ground_check_duration);
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Discards NaN and infinite values in [`ControllerInput`], and clamps absurdly long vectors, so that a single bad value
+/// from gameplay code can't spread into [`ControllerState`] and break the character for good. Each discarded value is
+/// reported with a warning, or a panic with the `strict-input` feature.
+pub fn sanitize_input(mut bodies: Query<(Entity, &mut ControllerInput)>) {
+    for (entity, mut input) in bodies.iter_mut() {
+        if let Some(movement) = sanitized(entity, "ControllerInput::movement", input.movement) {
+            input.movement = movement;
+        }
+        if let Some(facing) = sanitized(entity, "ControllerInput::facing", input.facing) {
+            input.facing = facing;
+        }
+        if let Some(impulse) = sanitized(
+            entity,
+            "ControllerInput::custom_impulse",
+            input.custom_impulse,
+        ) {
+            input.custom_impulse = impulse;
+        }
+        if let Some(torque) = sanitized(
+            entity,
+            "ControllerInput::custom_torque",
+            input.custom_torque,
+        ) {
+            input.custom_torque = torque;
+        }
+        if input.look_at.is_some_and(|look_at| !look_at.is_finite()) {
+            report_non_finite(entity, "ControllerInput::look_at");
+            input.look_at = None;
+        }
+    }
+}
+
+/// What to replace `value` with, if it isn't finite or is longer than [`INPUT_MAX_MAGNITUDE`].
+fn sanitized(entity: Entity, source: &str, value: Vec3) -> Option<Vec3> {
+    if !value.is_finite() {
+        report_non_finite(entity, source);
+        Some(Vec3::ZERO)
+    } else if value.length_squared() > INPUT_MAX_MAGNITUDE * INPUT_MAX_MAGNITUDE {
+        Some(value.clamp_length_max(INPUT_MAX_MAGNITUDE))
+    } else {
+        None
+    }
+}
+
+/// Reports a NaN or infinite value found in `source`, see [`sanitize_input`].
+fn report_non_finite(entity: Entity, source: &str) {
+    if cfg!(feature = "strict-input") {
+        panic!("{:?} has a non-finite value in {}", entity, source);
+    } else {
+        warn!(
+            "{:?} has a non-finite value in {}, which was discarded",
+            entity, source
+        );
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Carries out [`ControllerCommands`] by writing [`ControllerInput`].
+pub fn process_controller_commands(
+    mut bodies: Query<(
+        &GlobalTransform,
+        &mut ControllerCommands,
+        &mut ControllerInput,
+        &mut ControllerSettings,
+    )>,
+) {
+    for (tf, mut commands, mut input, mut settings) in bodies.iter_mut() {
+        if commands.releasing_jump {
+            input.jumping = false;
+            commands.releasing_jump = false;
+        }
+
+        while let Some(command) = commands.queue.front().cloned() {
+            match command {
+                ControllerCommand::MoveToward { target, tolerance } => {
+                    let up = settings.up_vector;
+                    let offset = target - tf.translation();
+                    let offset = offset - up * offset.dot(up);
+                    // Keep going into the next move rather than slowing down for this one.
+                    let continues = matches!(
+                        commands.queue.get(1),
+                        Some(ControllerCommand::MoveToward { .. })
+                    );
+                    let tolerance = if continues {
+                        tolerance.max(commands.corner_cutting)
+                    } else {
+                        tolerance
+                    };
+                    if offset.length() > tolerance {
+                        input.movement = if !continues && commands.slowdown_radius > 0.0 {
+                            (offset / commands.slowdown_radius).clamp_length_max(1.0)
+                        } else {
+                            offset.normalize_or_zero()
+                        };
+                        break;
+                    }
+                    input.movement = Vec3::ZERO;
+                }
+                ControllerCommand::JumpOnce => {
+                    input.jumping = true;
+                    commands.releasing_jump = true;
+                    commands.queue.pop_front();
+                    // Let the jump happen before moving on.
+                    break;
+                }
+                ControllerCommand::FaceDirection(direction) => input.facing = direction,
+                ControllerCommand::SetMode(mode) => settings.facing.mode = mode,
+                // Carried out by `jump_link`, which moves on to the next command once it's done.
+                ControllerCommand::JumpTo { .. } => {
+                    input.movement = Vec3::ZERO;
+                    break;
+                }
+            }
+            commands.queue.pop_front();
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Puts characters with a [`ControllerActivation`] to sleep when no [`LodViewer`] is near, and wakes them up again.
+#[allow(clippy::type_complexity)]
+pub fn update_activation<B: PhysicsBackend>(
+    mut commands: Commands,
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerActivation,
+        Option<&mut B::Sleeping>,
+        Option<&ControllerAsleep>,
+    )>,
+    viewers: Query<&GlobalTransform, With<LodViewer>>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+    mut wake_events: EventReader<WakeControllerEvent>,
+    mut activation_events: EventWriter<ActivationChangedEvent>,
+) {
+    for event in wake_events.iter() {
+        if let Ok((.., mut activation, _, _)) = bodies.get_mut(event.entity) {
+            let duration = activation.wake_duration;
+            activation.wake_timer.start(duration);
+        }
+    }
+
+    for (entity, tf, settings, mut activation, sleeping, asleep) in bodies.iter_mut() {
+        let dt = settings.clock.delta_seconds::<B>(&time, &config);
+        activation.wake_timer.tick(dt);
+
+        // Without any viewers there is nothing to measure from, so everyone stays awake.
+        let position = tf.translation();
+        let near = viewers.is_empty()
+            || viewers
+                .iter()
+                .any(|viewer| viewer.translation().distance(position) <= activation.radius);
+        let awake = near || activation.wake_timer.is_running();
+        if awake == asleep.is_none() {
+            continue;
+        }
+
+        if awake {
+            commands.entity(entity).remove::<ControllerAsleep>();
+        } else {
+            commands.entity(entity).insert(ControllerAsleep);
+        }
+        match sleeping {
+            Some(mut sleeping) => B::set_sleeping(&mut sleeping, !awake),
+            None => {
+                commands.entity(entity).insert(B::sleeping(!awake));
+            }
+        }
+        activation_events.send(ActivationChangedEvent { entity, awake });
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Picks how often each character with a [`ControllerLod`] is moved, by its distance to the nearest [`LodViewer`],
+/// and whether it is moved this frame.
+pub fn schedule_lod(
+    mut bodies: Query<(Entity, &GlobalTransform, &mut ControllerLod), Without<ControllerAsleep>>,
+    viewers: Query<&GlobalTransform, With<LodViewer>>,
+    mut frame: Local<u32>,
+) {
+    *frame = frame.wrapping_add(1);
+
+    for (entity, tf, mut lod) in bodies.iter_mut() {
+        let position = tf.translation();
+        let distance = viewers
+            .iter()
+            .map(|viewer| viewer.translation().distance(position))
+            .reduce(f32::min);
+
+        lod.interval = match distance {
+            Some(distance) if distance > lod.half_rate_distance * lod.importance => 4,
+            Some(distance) if distance > lod.full_rate_distance * lod.importance => 2,
+            _ => 1,
+        };
+        // Spread characters in the same group over different frames.
+        lod.due = (*frame).wrapping_add(entity.id()) % lod.interval == 0;
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves characters along their [`PathFollow`]s by writing [`ControllerInput`], and sends [`PathProgressEvent`]s.
+pub fn follow_paths<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerInput,
+        &mut PathFollow,
+    )>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+    mut samples: Local<Vec<(Vec3, f32, f32)>>,
+    mut events: EventWriter<PathProgressEvent>,
+) {
+    for (entity, tf, settings, mut input, mut path) in bodies.iter_mut() {
+        if path.waypoints.is_empty() || path.finished() {
+            input.movement = Vec3::ZERO;
+            continue;
+        }
+
+        let dt = settings.clock.delta_seconds::<B>(&time, &config);
+        let up = settings.up_vector;
+        let flatten = |v: Vec3| v - up * v.dot(up);
+        let position = tf.translation();
+
+        // Split the path into short straight pieces, noting how far along the path and how fast to go at each.
+        samples.clear();
+        let mut length = 0.0;
+        for index in 0..path.waypoints.len() {
+            let steps = if index + 1 < path.waypoints.len() {
+                PATH_SAMPLES
+            } else {
+                1
+            };
+            for step in 0..steps {
+                let t = step as f32 / PATH_SAMPLES as f32;
+                let point = path.point(index, t);
+                if let Some(&(prev, ..)) = samples.last() {
+                    length += flatten(point - prev).length();
+                }
+                let speed = path.speed(index) + (path.speed(index + 1) - path.speed(index)) * t;
+                samples.push((point, length, speed));
+            }
+        }
+        let sample_at = |distance: f32| {
+            let next = samples
+                .iter()
+                .position(|&(_, d, _)| d >= distance)
+                .unwrap_or(samples.len() - 1);
+            let (to, to_d, to_speed) = samples[next];
+            let (from, from_d, from_speed) = samples[next.saturating_sub(1)];
+            let t = if to_d > from_d {
+                (distance - from_d) / (to_d - from_d)
+            } else {
+                1.0
+            };
+            (from.lerp(to, t), from_speed + (to_speed - from_speed) * t)
+        };
+
+        // Only move the target along while the character keeps up with it. A character cutting corners passes wide of it.
+        let (target, speed) = sample_at(path.distance);
+        if flatten(target - position).length() < path.lookahead + path.corner_cutting {
+            path.distance = (path.distance + speed * dt).min(length);
+        }
+        let (target, speed) = sample_at(path.distance);
+
+        let offset = flatten(target - position);
+        let max_speed = settings.locomotion.max_speed.max(f32::EPSILON);
+        let speed = if path.slowdown_radius > 0.0 {
+            let remaining = length - path.distance + offset.length();
+            speed * (remaining / path.slowdown_radius).min(1.0)
+        } else {
+            speed
+        };
+        // Steer towards where the path goes next instead, so bends are taken in a curve.
+        let direction = if path.corner_cutting > 0.0 {
+            let (ahead, _) = sample_at(path.distance + path.corner_cutting);
+            flatten(ahead - position).normalize_or_zero()
+        } else {
+            offset.normalize_or_zero()
+        };
+        input.movement = direction * (speed / max_speed).min(offset.length());
+
+        // Waypoints count as passed once the target moves beyond them, except the last, which the character has to reach.
+        let last = path.waypoints.len() - 1;
+        while path.passed < last {
+            let waypoint_distance = samples[path.passed * PATH_SAMPLES].1;
+            if path.distance < waypoint_distance {
+                break;
+            }
+            events.send(PathProgressEvent {
+                entity,
+                waypoint: path.passed,
+                finished: false,
+            });
+            path.passed += 1;
+        }
+        if path.passed == last
+            && path.distance >= length
+            && flatten(path.waypoints[last] - position).length() < path.arrival_radius
+        {
+            events.send(PathProgressEvent {
+                entity,
+                waypoint: last,
+                finished: true,
+            });
+            path.passed += 1;
+            input.movement = Vec3::ZERO;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Walks characters along their [`PatrolRoute`]s by writing [`ControllerInput`], and sends [`PatrolWaypointEvent`]s.
+pub fn patrol<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerInput,
+        &mut PatrolRoute,
+    )>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+    mut events: EventWriter<PatrolWaypointEvent>,
+) {
+    for (entity, tf, settings, mut input, mut route) in bodies.iter_mut() {
+        input.movement = Vec3::ZERO;
+        if route.waypoints.is_empty() || route.finished {
+            continue;
+        }
+        if route.pause.is_running() {
+            let dt = settings.clock.delta_seconds::<B>(&time, &config);
+            route.pause.tick(dt);
+            continue;
+        }
+
+        let up = settings.up_vector;
+        let target = route.waypoints[route.target.min(route.waypoints.len() - 1)];
+        let offset = target - tf.translation();
+        let offset = offset - up * offset.dot(up);
+        if offset.length() < route.arrival_radius {
+            let waypoint = route.target;
+            let pause = route.pause_at(waypoint);
+            route.pause.start(pause);
+            route.advance();
+            events.send(PatrolWaypointEvent {
+                entity,
+                waypoint,
+                finished: route.finished,
+            });
+            continue;
+        }
+
+        let max_speed = settings.locomotion.max_speed.max(f32::EPSILON);
+        input.movement =
+            offset.normalize_or_zero() * (route.speed / max_speed).min(offset.length());
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Keeps characters in their [`FormationSlot`]s by writing [`ControllerInput`].
+#[allow(clippy::type_complexity)]
+pub fn follow_formation<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerInput,
+        &mut FormationSlot,
+    )>,
+    leaders: Query<(&GlobalTransform, Option<&B::Velocity>)>,
+    characters: Query<(), With<ControllerSettings>>,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, settings, mut input, mut formation) in bodies.iter_mut() {
+        let (leader_tf, leader_velocity) = match leaders.get(formation.leader) {
+            Ok(leader) if formation.leader != entity => leader,
+            _ => {
+                formation.slot = None;
+                input.movement = Vec3::ZERO;
+                continue;
+            }
+        };
+        let up = settings.up_vector;
+        let flatten = |v: Vec3| v - up * v.dot(up);
+        let leader_position = leader_tf.translation();
+
+        // Keep the last slot while the leader faces straight up or down, where there is no way to tell which way it faces.
+        let forward = flatten(leader_tf.forward()).normalize_or_zero();
+        if forward != Vec3::ZERO {
+            let rotation = Transform::identity().looking_at(forward, up).rotation;
+            let offset = rotation * formation.offset;
+            let distance = offset.length();
+            let direction = offset.normalize_or_zero();
+
+            // Pull the slot in towards the leader until there's room for the character in it. Other characters are left
+            // out, since they move out of the way by themselves.
+            let hit = B::cast_shape(
+                &ctx,
+                leader_position,
+                Quat::IDENTITY,
+                direction,
+                &Collider::ball(formation.clearance),
+                distance,
+                &|collider| {
+                    collider != entity
+                        && collider != formation.leader
+                        && !characters.contains(collider)
+                },
+            );
+            let distance = hit.map_or(distance, |hit| hit.distance);
+            formation.slot = Some(leader_position + direction * distance);
+        }
+        let slot = match formation.slot {
+            Some(slot) => slot,
+            None => {
+                input.movement = Vec3::ZERO;
+                continue;
+            }
+        };
+
+        // Match the leader's speed, and close in on the slot on top of that.
+        let to_slot = flatten(slot - tf.translation());
+        let correction = if to_slot.length() > formation.arrival_radius {
+            to_slot * formation.catch_up
+        } else {
+            Vec3::ZERO
+        };
+        let velocity = flatten(leader_velocity.map_or(Vec3::ZERO, B::linear_velocity));
+        let max_speed = settings.locomotion.max_speed.max(f32::EPSILON);
+        input.movement = ((velocity + correction) / max_speed).clamp_length_max(1.0);
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Keeps characters within their [`KeepDistance`] band of their targets by writing [`ControllerInput`].
+pub fn keep_distance(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerInput,
+        &KeepDistance,
+    )>,
+    targets: Query<&GlobalTransform>,
+) {
+    for (entity, tf, settings, mut input, keep) in bodies.iter_mut() {
+        let target = match targets.get(keep.target) {
+            Ok(target) if keep.target != entity => target.translation(),
+            _ => {
+                input.movement = Vec3::ZERO;
+                continue;
+            }
+        };
+        let up = settings.up_vector;
+        let offset = target - tf.translation();
+        let offset = offset - up * offset.dot(up);
+        let distance = offset.length();
+        let toward = offset.normalize_or_zero();
+
+        let velocity = if distance > keep.max_distance {
+            toward * keep.speed.min(distance - keep.max_distance)
+        } else if distance < keep.min_distance {
+            -toward * keep.speed.min(keep.min_distance - distance)
+        } else {
+            // Counterclockwise around the up vector.
+            up.cross(toward) * -keep.strafe_speed
+        };
+        let max_speed = settings.locomotion.max_speed.max(f32::EPSILON);
+        input.movement = (velocity / max_speed).clamp_length_max(1.0);
+        if toward != Vec3::ZERO {
+            input.facing = toward;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Sends an [`OutOfBoundsEvent`] when a character leaves its [`ControllerBounds`], and respawns it if requested.
+pub fn out_of_bounds(
+    mut commands: Commands,
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &ControllerSettings,
+        &mut ControllerBounds,
+    )>,
+    mut events: EventWriter<OutOfBoundsEvent>,
+) {
+    for (entity, tf, settings, mut bounds) in bodies.iter_mut() {
+        let position = tf.translation();
+        if bounds.contains(position, settings.up_vector) {
+            bounds.out = false;
+            continue;
+        }
+        if bounds.out {
+            continue;
+        }
+
+        events.send(OutOfBoundsEvent { entity, position });
+        match bounds.respawn {
+            Some(respawn) => {
+                commands.add(TeleportController {
+                    entity,
+                    position: respawn,
+                });
+            }
+            None => bounds.out = true,
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`VolumeOverlaps`] with the volumes each character's collider overlaps.
+#[allow(clippy::type_complexity)]
+pub fn track_volume_overlaps<B: PhysicsBackend>(
+    mut bodies: Query<(Entity, &B::Collider, &mut VolumeOverlaps), Without<ControllerAsleep>>,
+    fluids: Query<(), With<FluidVolume>>,
+    ladders: Query<(), With<LadderVolume>>,
+    zones: Query<(), With<ZoneVolume>>,
+    ctx: Res<B::Context>,
+    mut found: Local<Vec<(Entity, VolumeKind)>>,
+) {
+    let kind = |volume| {
+        if fluids.contains(volume) {
+            Some(VolumeKind::Fluid)
+        } else if ladders.contains(volume) {
+            Some(VolumeKind::Ladder)
+        } else if zones.contains(volume) {
+            Some(VolumeKind::Zone)
+        } else {
+            None
+        }
+    };
+
+    for (entity, collider, mut overlaps) in bodies.iter_mut() {
+        let (position, rotation) = match B::collider_pose(&ctx, entity) {
+            Some(pose) => pose,
+            None => continue,
+        };
+
+        found.clear();
+        B::intersections_with_shape(
+            &ctx,
+            position,
+            rotation,
+            &B::collider_shape(collider),
+            &|volume| kind(volume).is_some(),
+            &mut |volume| {
+                if let Some(kind) = kind(volume) {
+                    found.push((volume, kind));
+                }
+                true
+            },
+        );
+        // Keep the volumes the character was already in in the order it entered them, with the new ones after.
+        found.sort_by_key(|(volume, _)| *volume);
+        let mut volumes: Vec<_> = overlaps
+            .volumes
+            .iter()
+            .filter(|entry| found.contains(entry))
+            .copied()
+            .collect();
+        for entry in found.iter() {
+            if !volumes.contains(entry) {
+                volumes.push(*entry);
+            }
+        }
+
+        if volumes != overlaps.volumes {
+            overlaps.volumes = volumes;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`AimTransform`]s, removing the float spring's displacement from the eye position.
+pub fn update_aim(
+    mut bodies: Query<
+        (
+            &GlobalTransform,
+            &ControllerState,
+            &ControllerSettings,
+            &mut AimTransform,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (tf, controller, settings, mut aim) in bodies.iter_mut() {
+        let up = settings.up_vector;
+        let eye = tf.mul_vec3(settings.eye) - up * controller.float_offset;
+        let direction = match aim.direction.try_normalize() {
+            Some(direction) => direction,
+            None => tf.forward(),
+        };
+
+        aim.transform = if direction.cross(up) == Vec3::ZERO {
+            // Looking straight up or down, where there is no way to tell which way is right.
+            Transform::from_translation(eye).with_rotation(tf.to_scale_rotation_translation().1)
+        } else {
+            Transform::from_translation(eye).looking_at(eye + direction, up)
+        };
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`InteractionRay`]s by casting a ray from the eyes of each character along its look direction.
+#[allow(clippy::type_complexity)]
+pub fn cast_interaction_rays<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ControllerSettings,
+            &mut InteractionRay,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, settings, mut ray, filter) in bodies.iter_mut() {
+        let direction = match ray.direction.try_normalize() {
+            Some(direction) => direction,
+            None => tf.forward(),
+        };
+
+        let hit = B::cast_ray(
+            &ctx,
+            tf.mul_vec3(settings.eye),
+            direction,
+            ray.length,
+            true,
+            &|collider| collider != entity && filter.is_none_or(|filter| filter.allows(collider)),
+        );
+        ray.hovered = hit.map(|hit| hit.entity);
+        if let Some(hit) = hit {
+            ray.point = hit.point;
+            ray.normal = hit.normal;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`FootPlacement`]s by casting a ray down from each foot of grounded characters.
+pub fn place_feet<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ControllerState,
+            &ControllerSettings,
+            &mut FootPlacement,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, controller, settings, mut placement) in bodies.iter_mut() {
+        let placement = &mut *placement;
+        for foot in [&mut placement.left, &mut placement.right] {
+            foot.ground = None;
+            if !controller.is_grounded {
+                continue;
+            }
+
+            let hit = B::cast_ray(
+                &ctx,
+                tf.mul_vec3(foot.offset),
+                -settings.up_vector,
+                placement.length,
+                true,
+                &|collider| collider != entity,
+            );
+            if let Some(hit) = hit {
+                foot.ground = Some(hit.entity);
+                foot.position = hit.point;
+                foot.normal = hit.normal;
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`LandingPrediction`]s by casting the character's ground cast shape along its predicted fall.
+#[allow(clippy::type_complexity)]
+pub fn predict_landing<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            Option<&B::Velocity>,
+            &ControllerState,
+            &ControllerSettings,
+            &mut LandingPrediction,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, velocity, controller, settings, mut prediction, filter) in bodies.iter_mut() {
+        prediction.time_to_land = None;
+        prediction.ground = None;
+        if controller.is_grounded || prediction.segments == 0 {
+            continue;
+        }
+
+        let up = settings.up_vector;
+        let gravity = -up * settings.gravity;
+        // The character counts as landed once the ground is within floating distance.
+        let offset = tf.mul_vec3(settings.ground_cast.origin)
+            - up * settings.float_distance(controller.crouched)
+            - tf.translation();
+        let rotation = tf.to_scale_rotation_translation().1;
+        let linvel = velocity.map_or(Vec3::ZERO, B::linear_velocity);
+        let position_at = |t: f32| tf.translation() + linvel * t + gravity * t * t * 0.5;
+
+        let segment_time = prediction.horizon / prediction.segments as f32;
+        for i in 0..prediction.segments {
+            let start_time = segment_time * i as f32;
+            let start = position_at(start_time);
+            let end = position_at(start_time + segment_time);
+
+            let length = start.distance(end);
+            let hit = B::cast_shape(
+                &ctx,
+                start + offset,
+                rotation,
+                (end - start).normalize_or_zero(),
+                &settings.ground_cast.collider,
+                length,
+                &|collider| {
+                    collider != entity && filter.is_none_or(|filter| filter.allows(collider))
+                },
+            );
+            if let Some(hit) = hit {
+                // How far along the segment it was hit.
+                let t = if length > 0.0 {
+                    hit.distance / length
+                } else {
+                    0.0
+                };
+                prediction.time_to_land = Some(start_time + segment_time * t);
+                prediction.ground = Some(hit.entity);
+                prediction.position = start + (end - start) * t;
+                prediction.normal = hit.normal;
+                break;
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Updates [`ForwardObstruction`]s by casting spheres ahead of each character, just above its feet, at its step height and
+/// at its waist height. The highest probe that hits decides how high the obstacle is, and the nearest hit is reported.
+#[allow(clippy::type_complexity)]
+pub fn probe_forward_obstruction<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &mut ForwardObstruction,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, controller, settings, input, mut obstruction, filter) in bodies.iter_mut() {
+        let up = settings.up_vector;
+        let flatten = |v: Vec3| (v - up * v.dot(up)).normalize_or_zero();
+        let direction = match flatten(input.movement) {
+            Vec3::ZERO => flatten(tf.forward()),
+            direction => direction,
+        };
+        obstruction.direction = direction;
+        // Forget last frame's obstacle, in case the probes hit nothing this time.
+        obstruction.obstacle = None;
+        obstruction.height = None;
+        if direction == Vec3::ZERO {
+            continue;
+        }
+
+        let feet = character_feet(tf, controller, settings);
+        let radius = obstruction.probe_radius;
+        let probe = Collider::ball(radius);
+        let heights = [
+            (OBSTRUCTION_CLEARANCE, ObstructionHeight::Step),
+            (obstruction.step_height, ObstructionHeight::Waist),
+            (obstruction.waist_height, ObstructionHeight::Full),
+        ];
+
+        for (height, classification) in heights {
+            let hit = B::cast_shape(
+                &ctx,
+                feet + up * (height + radius),
+                Quat::IDENTITY,
+                direction,
+                &probe,
+                obstruction.range,
+                &|collider| {
+                    collider != entity && filter.is_none_or(|filter| filter.allows(collider))
+                },
+            );
+            if let Some(hit) = hit {
+                if obstruction.obstacle.is_none() || hit.distance < obstruction.distance {
+                    obstruction.obstacle = Some(hit.entity);
+                    obstruction.distance = hit.distance;
+                    obstruction.normal = hit.normal;
+                }
+                obstruction.height = Some(classification);
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Finds the nearest wall around characters with a [`WallContact`]. Runs after
+/// [`MovementSet::FindGround`](crate::MovementSet::FindGround), so the later stages can slide down and jump off of it.
+#[allow(clippy::type_complexity)]
+pub fn detect_walls<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ControllerSettings,
+            &mut WallContact,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, settings, mut contact, filter) in bodies.iter_mut() {
+        let up = settings.up_vector;
+        contact.wall = None;
+        let forward = (tf.forward() - up * tf.forward().dot(up)).normalize_or_zero();
+        if forward == Vec3::ZERO {
+            continue;
+        }
+
+        let radius = contact.probe_radius;
+        let probe = Collider::ball(radius);
+        for direction in 0..WALL_PROBE_DIRECTIONS {
+            let angle = std::f32::consts::TAU * direction as f32 / WALL_PROBE_DIRECTIONS as f32;
+            let hit = B::cast_shape(
+                &ctx,
+                tf.translation(),
+                Quat::IDENTITY,
+                Quat::from_axis_angle(up, angle) * forward,
+                &probe,
+                (contact.range - radius).max(0.0),
+                &|collider| {
+                    collider != entity && filter.is_none_or(|filter| filter.allows(collider))
+                },
+            );
+            let hit = match hit {
+                Some(hit) if hit.normal.dot(up).abs() <= WALL_MAX_SLOPE => hit,
+                _ => continue,
+            };
+            let distance = hit.distance + radius;
+            if contact.wall.is_none() || distance < contact.distance {
+                contact.wall = Some(hit.entity);
+                contact.normal = hit.normal;
+                contact.distance = distance;
+                contact.point = hit.point;
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Slows the fall of airborne characters pressing towards the wall their [`WallContact`] found, see
+/// [`WallSettings::slide_speed`](crate::WallSettings::slide_speed). Runs after [`MovementSet::Jump`](crate::MovementSet::Jump).
+#[allow(clippy::type_complexity)]
+pub fn wall_slide(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+            &WallContact,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    mut events: EventWriter<WallSlidingChangedEvent>,
+) {
+    for (entity, mut controller, settings, input, frame, mut forces, contact) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
+        let up = settings.up_vector;
+        let pressing = input.movement.reject_from(up).normalize_or_zero();
+
+        let slide_speed = settings.wall.slide_speed.filter(|_| {
+            contact.wall.is_some()
+                && !frame.grounded
+                && !frame.jumped
+                && pressing.dot(-contact.normal) > WALL_SLIDE_MIN_PRESS
+        });
+        if let Some(slide_speed) = slide_speed {
+            // How fast the character would be falling after this frame's forces.
+            let fall = -up.dot(frame.velocity.linvel + forces.gravity + forces.movement);
+            forces.gravity += up * (fall - slide_speed).max(0.0);
+        }
+
+        let sliding = slide_speed.is_some();
+        if sliding != controller.wall_sliding {
+            controller.wall_sliding = sliding;
+            events.send(WallSlidingChangedEvent {
+                entity,
+                wall: contact.wall,
+                sliding,
+                contact: ContactTransform {
+                    position: contact.point,
+                    normal: contact.normal,
+                },
+            });
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Slows the fall of airborne characters holding jump, and steers them with the glide's own speed, see
+/// [`GlideSettings`](crate::GlideSettings). Runs after [`MovementSet::Jump`](crate::MovementSet::Jump).
+#[allow(clippy::type_complexity)]
+pub fn glide(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+            Option<&Jetpack>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    mut events: EventWriter<GlidingChangedEvent>,
+) {
+    for (entity, mut controller, settings, input, frame, mut forces, jetpack) in bodies.iter_mut() {
+        if !frame.active {
+            continue;
+        }
+        let (up, dt) = (settings.up_vector, frame.dt);
+        let glide = &settings.glide;
+        let falling = frame.velocity.linvel.dot(up) < 0.0;
+
+        // Keep gliding through updrafts once started, rather than only while falling.
+        let gliding = glide.max_speed > 0.0
+            && input.jumping
+            && (falling || controller.gliding)
+            && !frame.grounded
+            && !frame.jumped
+            && !controller.jump_timer.is_running()
+            && !controller.coyote_timer.is_running()
+            && !controller.dash_timer.is_running()
+            && controller.swimming.is_none()
+            && controller.climbing.is_none()
+            && !controller.wall_sliding
+            && !settings.fly.enabled
+            && !jetpack.is_some_and(|jetpack| jetpack.thrusting);
+        if gliding {
+            // How fast the character would be falling after this frame's gravity.
+            let fall = -up.dot(frame.velocity.linvel + forces.gravity);
+            forces.gravity += up * (fall - glide.fall_speed).max(0.0);
+
+            let dir = input.movement.reject_from(up).clamp_length_max(1.0);
+            let goal_velocity = Vec3::lerp(
+                controller.last_goal_velocity.reject_from(up),
+                dir * glide.max_speed,
+                (glide.acceleration * dt).min(1.0),
+            );
+            forces.movement = goal_velocity - frame.velocity.linvel.reject_from(up);
+            controller.last_goal_velocity = goal_velocity;
+        }
+
+        if gliding != controller.gliding {
+            controller.gliding = gliding;
+            events.send(GlidingChangedEvent { entity, gliding });
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Keeps characters on their [`GrappleState`] ropes and reels them in or out. Runs after the other stages that work out
+/// movement, so the rope holds against all of their forces.
+#[allow(clippy::type_complexity)]
+pub fn grapple(
+    mut bodies: Query<
+        (
+            &GlobalTransform,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut ControllerForces,
+            &mut GrappleState,
+        ),
+        Without<ControllerAsleep>,
+    >,
+) {
+    for (tf, input, frame, mut forces, mut grapple) in bodies.iter_mut() {
+        let anchor = match grapple.anchor {
+            Some(anchor) if frame.active => anchor,
+            _ => continue,
+        };
+        let dt = frame.dt;
+        let max_length = grapple.max_length.max(grapple.min_length);
+        grapple.length = (grapple.length - grapple.reel.clamp(-1.0, 1.0) * grapple.reel_speed * dt)
+            .clamp(grapple.min_length, max_length);
+
+        let offset = tf.translation() - anchor;
+        let outwards = offset.normalize_or_zero();
+        let stretch = offset.length() - grapple.length;
+        // How fast the character would be moving away from the anchor after this frame's forces.
+        let velocity =
+            frame.velocity.linvel + forces.movement + forces.jump + forces.float + forces.gravity;
+        grapple.taut =
+            stretch >= 0.0 || offset.length() + velocity.dot(outwards) * dt > grapple.length;
+        if !grapple.taut {
+            continue;
+        }
+
+        // Push the swing along instead of braking it towards a goal velocity.
+        let push =
+            input.movement.clamp_length_max(1.0).reject_from(outwards) * grapple.swing_force * dt;
+        let velocity = velocity - forces.movement - forces.float + push;
+        forces.float = Vec3::ZERO;
+        // Take away only the velocity away from the anchor, and pull back in by however far the rope is stretched.
+        let away = velocity.dot(outwards).max(0.0) + stretch.max(0.0) / dt.max(f32::EPSILON);
+        forces.movement = push - outwards * away;
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Checks whether characters with a [`MovementBlockage`] are blocked from moving, and works out why.
+#[allow(clippy::type_complexity)]
+pub fn detect_blocked_movement<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ControllerFrame,
+            &mut MovementBlockage,
+            Option<&B::Collider>,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+    mut events: EventWriter<MovementBlockedEvent>,
+) {
+    for (entity, tf, controller, settings, input, frame, mut blockage, collider, filter) in
+        bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let up = settings.up_vector;
+        let movement = input.movement.reject_from(up).clamp_length_max(1.0);
+        let direction = movement.normalize_or_zero();
+        let filter =
+            |collider| collider != entity && filter.is_none_or(|filter| filter.allows(collider));
+
+        let reason = if frame.grounded && movement.length() >= BLOCKED_MIN_INPUT {
+            let wanted =
+                movement.length() * settings.max_speed(controller.crouched, controller.sprinting);
+            let progress = direction.dot(frame.velocity.linvel - frame.ground_velocity);
+
+            // Look down just past where the character is heading for the ground it would walk onto.
+            let feet = character_feet(tf, controller, settings);
+            let ahead = feet + direction * blockage.ledge_distance + up * BLOCKED_OBSTACLE_PROBE;
+            let ledge = B::cast_ray(
+                &ctx,
+                ahead,
+                -up,
+                blockage.ledge_depth + BLOCKED_OBSTACLE_PROBE,
+                true,
+                &filter,
+            )
+            .is_none();
+
+            if ledge {
+                Some(MovementBlockedReason::Ledge)
+            } else if progress >= wanted * blockage.min_progress {
+                None
+            } else if frame.steep_slope.is_some() {
+                Some(MovementBlockedReason::SteepSlope)
+            } else {
+                let obstacle = collider.and_then(|collider| {
+                    B::cast_shape(
+                        &ctx,
+                        frame.position,
+                        frame.rotation,
+                        direction,
+                        &B::collider_shape(collider),
+                        BLOCKED_OBSTACLE_PROBE,
+                        &filter,
+                    )
+                });
+                match obstacle {
+                    Some(hit)
+                        if hit.normal.dot(up) < settings.ground_cast.max_ground_angle.cos() =>
+                    {
+                        Some(MovementBlockedReason::Obstacle(hit.entity))
+                    }
+                    _ => Some(MovementBlockedReason::Stuck),
+                }
+            }
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => {
+                blockage.blocked_time = (blockage.blocked_time + frame.dt).min(blockage.duration);
+                // The character doesn't stop at ledges by itself, so they are reported without waiting.
+                let reported = blockage.blocked_time >= blockage.duration
+                    || reason == MovementBlockedReason::Ledge;
+                if reported && blockage.reason != Some(reason) {
+                    blockage.reason = Some(reason);
+                    events.send(MovementBlockedEvent {
+                        entity,
+                        reason,
+                        direction,
+                    });
+                }
+            }
+            // Wind back down rather than resetting, so a single frame of progress, such as bouncing off of a wall,
+            // doesn't count as getting unblocked.
+            None => {
+                blockage.blocked_time = (blockage.blocked_time - frame.dt).max(0.0);
+                if blockage.blocked_time == 0.0 {
+                    blockage.reason = None;
+                }
+            }
+        }
+    }
+}
+
+/// Where a character's feet are, as [`ForwardObstruction`] measures heights from.
+fn character_feet(
+    tf: &GlobalTransform,
+    controller: &ControllerState,
+    settings: &ControllerSettings,
+) -> Vec3 {
+    let up = settings.up_vector;
+    let origin = tf.translation();
+    if controller.is_grounded {
+        origin - up * up.dot(origin - controller.last_ground_contact.position)
+    } else {
+        tf.mul_vec3(settings.ground_cast.origin) - up * settings.float_distance(controller.crouched)
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Carries out [`ControllerCommand::JumpTo`]s. Runs after [`MovementSet::Jump`](crate::MovementSet::Jump), and while a
+/// character jumps replaces the forces the earlier stages worked out with the ones that keep it on the arc.
+#[allow(clippy::type_complexity)]
+pub fn jump_link<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut ControllerState,
+            &ControllerSettings,
+            &mut ControllerCommands,
+            &ControllerFrame,
+            &mut ControllerForces,
+            Option<&B::Collider>,
+            Option<&GroundCastFilter>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+    mut started_events: EventWriter<JumpLinkStartedEvent>,
+    mut finished_events: EventWriter<JumpLinkFinishedEvent>,
+    mut failed_events: EventWriter<JumpLinkFailedEvent>,
+) {
+    for (entity, tf, mut controller, settings, mut commands, frame, mut forces, collider, filter) in
+        bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let up = settings.up_vector;
+
+        if commands.jump_arc.is_none() && frame.grounded {
+            if let Some(&ControllerCommand::JumpTo { target, height }) = commands.current() {
+                // Land with the feet on the target, floating above it as usual.
+                let feet = character_feet(tf, &controller, settings);
+                let end = target + (frame.position - feet);
+                let arc = JumpArc::new(frame.position, end, height, settings.gravity, up);
+                let obstacle = arc.and_then(|arc| {
+                    let filter = |other: Entity| {
+                        other != entity && filter.is_none_or(|filter| filter.allows(other))
+                    };
+                    let collider = B::collider_shape(collider?);
+                    jump_arc_obstacle::<B>(&ctx, &arc, frame.rotation, &collider, &filter)
+                });
+
+                match arc {
+                    Some(arc) if obstacle.is_none() => {
+                        // The arc replaces any jump, and the ground is ignored until it's over.
+                        controller.jump_timer.stop();
+                        controller.skip_ground_check_timer.start(arc.duration);
+                        commands.jump_arc = Some(arc);
+                        started_events.send(JumpLinkStartedEvent {
+                            entity,
+                            velocity: arc.velocity,
+                            duration: arc.duration,
+                        });
+                    }
+                    _ => {
+                        commands.queue.pop_front();
+                        failed_events.send(JumpLinkFailedEvent {
+                            entity,
+                            target,
+                            obstacle,
+                        });
+                    }
+                }
+            }
+        }
+
+        let arc = match &mut commands.jump_arc {
+            Some(arc) => arc,
+            None => continue,
+        };
+        arc.elapsed = (arc.elapsed + frame.dt).min(arc.duration);
+        let finished = arc.elapsed >= arc.duration;
+        let goal_velocity = if finished {
+            // Land moving along the ground, rather than into it.
+            arc.velocity_at(arc.duration).reject_from(up)
+        } else {
+            // Aim for where the arc will be at the end of this frame, so errors don't build up.
+            (arc.at(arc.elapsed) - frame.position) / frame.dt
+        };
+
+        forces.movement = goal_velocity - frame.velocity.linvel;
+        forces.jump = Vec3::ZERO;
+        forces.float = Vec3::ZERO;
+        forces.gravity = Vec3::ZERO;
+
+        if finished {
+            commands.jump_arc = None;
+            commands.queue.pop_front();
+            controller.last_goal_velocity = goal_velocity;
+            finished_events.send(JumpLinkFinishedEvent { entity });
+        }
+    }
+}
+
+/// The first obstacle `collider` would hit following `arc`, if any.
+fn jump_arc_obstacle<B: PhysicsBackend>(
+    ctx: &B::Context,
+    arc: &JumpArc,
+    rotation: Quat,
+    collider: &Collider,
+    filter: &dyn Fn(Entity) -> bool,
+) -> Option<Entity> {
+    (0..JUMP_LINK_SEGMENTS).find_map(|segment| {
+        let time = |segment: usize| arc.duration * segment as f32 / JUMP_LINK_SEGMENTS as f32;
+        let from = arc.at(time(segment));
+        let offset = arc.at(time(segment + 1)) - from;
+        B::cast_shape(
+            ctx,
+            from,
+            rotation,
+            offset.normalize_or_zero(),
+            collider,
+            offset.length(),
+            filter,
+        )
+        .map(|hit| hit.entity)
+    })
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Starts and carries out [`Vault`]s. Runs after [`MovementSet::Jump`](crate::MovementSet::Jump), and while a character
+/// vaults replaces the forces the earlier stages worked out with the ones that keep it on the arc.
+#[allow(clippy::type_complexity)]
+pub fn vault<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &ForwardObstruction,
+            &mut Vault,
+            &mut ControllerFrame,
+            &mut ControllerForces,
+            Option<&mut ControllerIntents>,
+            Option<&GroundCastFilter>,
+            Option<&LedgeHang>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+    time: Res<Time>,
+    mut started_events: EventWriter<VaultStartedEvent>,
+    mut finished_events: EventWriter<VaultFinishedEvent>,
+) {
+    for (
+        entity,
+        tf,
+        mut controller,
+        settings,
+        input,
+        obstruction,
+        mut vault,
+        mut frame,
+        mut forces,
+        mut intents,
+        filter,
+        hang,
+    ) in bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let up = settings.up_vector;
+
+        let in_reach = obstruction.obstacle.is_some()
+            && obstruction.height == Some(ObstructionHeight::Waist)
+            && obstruction.distance <= vault.max_distance;
+        let now = time.seconds_since_startup();
+        let requested = intents.as_ref().is_some_and(|intents| {
+            let action = ControllerAction::Vault;
+            intents.pending(action, now, action.buffer_duration(settings))
+        });
+        let wants_to = frame.jumped
+            || requested
+            || (vault.automatic
+                && frame.grounded
+                && input.movement.dot(obstruction.direction) > 0.0);
+        let on_ledge = hang.is_some_and(|hang| hang.is_hanging() || hang.is_climbing());
+        if !vault.is_vaulting() && !on_ledge && in_reach && wants_to {
+            // Look down onto the obstacle from just past where it was hit to find its top.
+            let feet = character_feet(tf, &controller, settings);
+            let inside = obstruction.distance + obstruction.probe_radius * 2.0;
+            let origin = feet + up * obstruction.waist_height + obstruction.direction * inside;
+            let top = B::cast_ray(
+                &ctx,
+                origin,
+                -up,
+                obstruction.waist_height,
+                false,
+                &|collider| {
+                    collider != entity && filter.is_none_or(|filter| filter.allows(collider))
+                },
+            );
+
+            if let (Some(obstacle), Some(top)) = (obstruction.obstacle, top) {
+                if !claim_action(
+                    settings,
+                    &mut controller,
+                    &mut frame,
+                    &mut forces,
+                    ControllerAction::Vault,
+                ) {
+                    continue;
+                }
+                if let Some(intents) = intents.as_mut() {
+                    intents.consume(ControllerAction::Vault);
+                }
+                let height = obstruction.waist_height - top.distance;
+                let start = frame.position;
+                let end = start + obstruction.direction * vault.length;
+                vault.arc = Some(VaultArc {
+                    start,
+                    end,
+                    rise: height + vault.clearance,
+                    up,
+                    elapsed: 0.0,
+                    // A zero duration would put the character at infinity.
+                    duration: vault.duration.max(frame.dt),
+                });
+
+                // The vault replaces the jump, and the ground is ignored until it's over.
+                controller.jump_timer.stop();
+                controller.skip_ground_check_timer.start(vault.duration);
+                started_events.send(VaultStartedEvent {
+                    entity,
+                    obstacle,
+                    contact: ContactTransform {
+                        position: top.point,
+                        normal: top.normal,
+                    },
+                    end,
+                    duration: vault.duration,
+                });
+            }
+        }
+
+        let arc = match &mut vault.arc {
+            Some(arc) => arc,
+            None => continue,
+        };
+        arc.elapsed += frame.dt;
+        let t = (arc.elapsed / arc.duration).min(1.0);
+        let goal_velocity = if t < 1.0 {
+            // Aim for where the arc will be at the end of this frame, so errors don't build up.
+            (arc.at(t) - frame.position) / frame.dt
+        } else {
+            // Carry on along the ground at the vault's speed, but no faster than the character runs.
+            ((arc.end - arc.start) / arc.duration).clamp_length_max(settings.locomotion.max_speed)
+        };
+
+        forces.movement = goal_velocity - frame.velocity.linvel;
+        forces.jump = Vec3::ZERO;
+        forces.float = Vec3::ZERO;
+        forces.gravity = Vec3::ZERO;
+
+        if t >= 1.0 {
+            vault.arc = None;
+            finished_events.send(VaultFinishedEvent { entity });
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Grabs ledges, and moves characters hanging from them with [`LedgeHang`]. Runs after [`vault`], and like it replaces the
+/// forces the earlier stages worked out while a character hangs or climbs.
+#[allow(clippy::type_complexity)]
+pub fn ledge_hang<B: PhysicsBackend>(
+    mut bodies: Query<
+        (
+            Entity,
+            &mut ControllerState,
+            &ControllerSettings,
+            &ControllerInput,
+            &mut LedgeHang,
+            &mut ControllerFrame,
+            &mut ControllerForces,
+            Option<&mut ControllerIntents>,
+            Option<&Vault>,
+            Option<&GroundCastFilter>,
+            Option<&B::Collider>,
+        ),
+        Without<ControllerAsleep>,
+    >,
+    ctx: Res<B::Context>,
+    time: Res<Time>,
+    mut detected_events: EventWriter<LedgeDetectedEvent>,
+    mut grabbed_events: EventWriter<LedgeGrabbedEvent>,
+    mut released_events: EventWriter<LedgeReleasedEvent>,
+) {
+    for (
+        entity,
+        mut controller,
+        settings,
+        input,
+        mut hang,
+        mut frame,
+        mut forces,
+        mut intents,
+        vault,
+        filter,
+        collider,
+    ) in bodies.iter_mut()
+    {
+        if !frame.active {
+            continue;
+        }
+        let (dt, up) = (frame.dt, settings.up_vector);
+        let flatten = |v: Vec3| (v - up * v.dot(up)).normalize_or_zero();
+        let filter =
+            |collider| collider != entity && filter.is_none_or(|filter| filter.allows(collider));
+        let just_jumped = input.jumping && !hang.jump_held;
+        hang.jump_held = input.jumping;
+        let just_grabbed = input.grabbing && !hang.grab_held;
+        hang.grab_held = input.grabbing;
+        hang.regrab_timer.tick(dt);
+
+        // Buffer presses that can't do anything yet. A jump that was performed isn't also buffered as a mantle, unless the
+        // character was hanging already and it was cancelled by one.
+        if just_grabbed {
+            let window = ControllerAction::Grab.buffer_duration(settings);
+            hang.grab_buffer_timer.start(window);
+        } else {
+            hang.grab_buffer_timer.tick(dt);
+        }
+        if just_jumped && (hang.is_hanging() || !frame.jumped) {
+            let window = ControllerAction::Mantle.buffer_duration(settings);
+            hang.mantle_buffer_timer.start(window);
+        } else {
+            hang.mantle_buffer_timer.tick(dt);
+        }
+        let now = time.seconds_since_startup();
+        let requested = |action: ControllerAction| {
+            intents.as_ref().is_some_and(|intents| {
+                intents.pending(action, now, action.buffer_duration(settings))
+            })
+        };
+        let grabbing = input.grabbing
+            || hang.grab_buffer_timer.is_running()
+            || requested(ControllerAction::Grab);
+        let mantling = just_jumped
+            || hang.mantle_buffer_timer.is_running()
+            || requested(ControllerAction::Mantle);
+
+        let goal = match hang.state {
+            LedgeHangState::Free => {
+                let detected = hang.detected.take();
+                if hang.regrab_timer.is_running() || vault.is_some_and(|vault| vault.is_vaulting())
+                {
+                    continue;
+                }
+                let facing = match flatten(input.movement) {
+                    Vec3::ZERO => flatten(frame.rotation * -Vec3::Z),
+                    direction => direction,
+                };
+                let heading = flatten(frame.velocity.linvel);
+
+                let walked_off = controller.is_grounded && !frame.grounded && !frame.jumped;
+                let slow = frame.velocity.linvel.reject_from(up).length() <= hang.drop_down_speed;
+                let drop_down = (walked_off && (slow || grabbing)) || (frame.grounded && grabbing);
+                let falling = frame.velocity.linvel.dot(up) <= 0.0;
+
+                let ledge = if drop_down {
+                    // Look back from past the edge for the wall below it, at the height of the ground being left.
+                    let direction = if walked_off && heading != Vec3::ZERO {
+                        heading
+                    } else {
+                        facing
+                    };
+                    let position = frame.position;
+                    let top =
+                        position - up * up.dot(position - controller.last_ground_contact.position);
+                    find_ledge::<B>(
+                        &ctx,
+                        &filter,
+                        top + direction * hang.grab_distance,
+                        -direction,
+                        hang.grab_distance * 2.0,
+                        up,
+                        hang.reach,
+                        settings,
+                    )
+                    .filter(|ledge| ledge.normal.dot(direction) > 0.5)
+                } else if !frame.grounded && falling && facing != Vec3::ZERO {
+                    find_ledge::<B>(
+                        &ctx,
+                        &filter,
+                        frame.position + up * hang.hand_height,
+                        facing,
+                        hang.grab_distance,
+                        up,
+                        hang.reach,
+                        settings,
+                    )
+                } else {
+                    None
+                };
+                let ledge = match ledge {
+                    Some(ledge) => ledge,
+                    None => continue,
+                };
+                if detected.map(|detected| detected.entity) != Some(ledge.entity) {
+                    detected_events.send(LedgeDetectedEvent { entity, ledge });
+                }
+                if !drop_down && !hang.auto_grab && !grabbing
+                    || !claim_action(
+                        settings,
+                        &mut controller,
+                        &mut frame,
+                        &mut forces,
+                        ControllerAction::Grab,
+                    )
+                {
+                    hang.detected = Some(ledge);
+                    continue;
+                }
+                hang.grab_buffer_timer.stop();
+                if let Some(intents) = intents.as_mut() {
+                    intents.consume(ControllerAction::Grab);
+                }
+                let goal = hang_position(&hang, &ledge, up);
+                if drop_down {
+                    // Don't let the ground the character is climbing down from count until it hangs below it.
+                    let time = (goal - frame.position).length() / LEDGE_SNAP_SPEED;
+                    controller.skip_ground_check_timer.start(time + dt);
+                }
+                hang.state = LedgeHangState::Hanging(ledge);
+                // Grabbing a ledge is as good as landing for extra jumps, but it doesn't count as being grounded.
+                controller.remaining_jumps = settings.jump.extra_jumps;
+                grabbed_events.send(LedgeGrabbedEvent { entity, ledge });
+                goal
+            }
+            LedgeHangState::Hanging(mut ledge) => {
+                // Stand on the ledge as far past its edge as the character hung in front of it.
+                let feet = ledge.anchor - ledge.normal * hang.hang_offset;
+                let climb_end = feet + up * settings.float.distance
+                    - frame.rotation * settings.ground_cast.origin;
+                let release = if frame.grounded {
+                    Some(LedgeReleaseCause::Lost)
+                } else if mantling
+                    && can_climb::<B>(
+                        &ctx,
+                        &filter,
+                        &frame,
+                        climb_end,
+                        up,
+                        collider.map(B::collider_shape).as_ref(),
+                    )
+                    && claim_action(
+                        settings,
+                        &mut controller,
+                        &mut frame,
+                        &mut forces,
+                        ControllerAction::Mantle,
+                    )
+                {
+                    Some(LedgeReleaseCause::ClimbedUp)
+                } else if input.dropping {
+                    Some(LedgeReleaseCause::Dropped)
+                } else {
+                    let shimmy =
+                        input.movement.dot(ledge.along).clamp(-1.0, 1.0) * hang.shimmy_speed;
+                    let next =
+                        shimmy_along::<B>(&ctx, &filter, &ledge, shimmy * dt, &hang, up, settings);
+                    match next {
+                        Some(next) => {
+                            ledge = next;
+                            None
+                        }
+                        None => Some(LedgeReleaseCause::Lost),
+                    }
+                };
+
+                match release {
+                    None => {
+                        hang.state = LedgeHangState::Hanging(ledge);
+                        hang_position(&hang, &ledge, up)
+                    }
+                    Some(cause) => {
+                        released_events.send(LedgeReleasedEvent {
+                            entity,
+                            ledge,
+                            cause,
+                        });
+                        let regrab_delay = hang.regrab_delay;
+                        hang.regrab_timer.start(regrab_delay);
+                        if cause != LedgeReleaseCause::ClimbedUp {
+                            hang.mantle_buffer_timer.stop();
+                            hang.state = LedgeHangState::Free;
+                            continue;
+                        }
+
+                        hang.mantle_buffer_timer.stop();
+                        if let Some(intents) = intents.as_mut() {
+                            intents.consume(ControllerAction::Mantle);
+                        }
+                        hang.state = LedgeHangState::Climbing(ClimbPath {
+                            start: frame.position,
+                            end: climb_end,
+                            up,
+                            elapsed: 0.0,
+                            duration: hang.climb_duration,
+                        });
+                        controller
+                            .skip_ground_check_timer
+                            .start(hang.climb_duration);
+                        continue_climb(&mut hang, dt)
+                    }
+                }
+            }
+            LedgeHangState::Climbing(_) => continue_climb(&mut hang, dt),
+        };
+
+        // Head for the goal by the end of this frame, without lurching towards it too violently.
+        let goal_velocity = ((goal - frame.position) / dt).clamp_length_max(LEDGE_SNAP_SPEED);
+        forces.movement = goal_velocity - frame.velocity.linvel;
+        forces.jump = Vec3::ZERO;
+        forces.float = Vec3::ZERO;
+        forces.gravity = Vec3::ZERO;
+        controller.jump_timer.stop();
+    }
+}
+
+/// Adds `action` to the actions performed this frame if [`ActionSettings`](crate::ActionSettings) lets it be performed
+/// alongside the ones already performed, returning whether it does. Actions performed earlier this frame that `action`
+/// excludes with its higher priority are cancelled.
+fn claim_action(
+    settings: &ControllerSettings,
+    controller: &mut ControllerState,
+    frame: &mut ControllerFrame,
+    forces: &mut ControllerForces,
+    action: ControllerAction,
+) -> bool {
+    let mut actions = frame.actions.clone();
+    actions.push(action);
+    settings.actions.resolve(&mut actions);
+    if !actions.contains(&action) {
+        return false;
+    }
+
+    for cancelled in frame.actions.iter().filter(|kept| !actions.contains(kept)) {
+        match cancelled {
+            ControllerAction::Jump => {
+                controller.jump_timer.stop();
+                forces.jump = Vec3::ZERO;
+                frame.jumped = false;
+            }
+            ControllerAction::Dash => controller.dash_timer.stop(),
+            // Never claimed before another of these: ledges aren't grabbed during a vault, and vaults don't start from a
+            // ledge.
+            ControllerAction::Vault | ControllerAction::Grab | ControllerAction::Mantle => {}
+        }
+    }
+    frame.actions = actions;
+    true
+}
+
+/// Whether a character hanging at `frame.position` has room to climb up to `end`: to rise over the edge, and to stand on
+/// top of the ledge.
+fn can_climb<B: PhysicsBackend>(
+    ctx: &B::Context,
+    filter: &dyn Fn(Entity) -> bool,
+    frame: &ControllerFrame,
+    end: Vec3,
+    up: Vec3,
+    collider: Option<&Collider>,
+) -> bool {
+    let collider = match collider {
+        Some(collider) => collider,
+        None => return true,
+    };
+    let over_edge = frame.position + up * up.dot(end - frame.position);
+    [over_edge, end]
+        .into_iter()
+        .all(|position| !B::overlaps(ctx, position, frame.rotation, collider, filter))
+}
+
+/// Where a character hanging from `ledge` holds itself.
+fn hang_position(hang: &LedgeHang, ledge: &Ledge, up: Vec3) -> Vec3 {
+    ledge.anchor + ledge.normal * hang.hang_offset - up * hang.hand_height
+}
+
+/// Advances a climb up onto a ledge by `dt`, returning where the character should be at the end of the frame.
+fn continue_climb(hang: &mut LedgeHang, dt: f32) -> Vec3 {
+    let path = match &mut hang.state {
+        LedgeHangState::Climbing(path) => path,
+        _ => unreachable!("only called while climbing"),
+    };
+    path.elapsed += dt;
+    let t = (path.elapsed / path.duration).min(1.0);
+    let goal = path.at(t);
+    if t >= 1.0 {
+        hang.state = LedgeHangState::Free;
+    }
+    goal
+}
+
+/// Looks for a ledge by probing from `hands` along `direction` for up to `distance` for a wall, then down onto the wall for
+/// its top, which has to be within `reach` of `hands` and flat enough to stand on.
+#[allow(clippy::too_many_arguments)]
+fn find_ledge<B: PhysicsBackend>(
+    ctx: &B::Context,
+    filter: &dyn Fn(Entity) -> bool,
+    hands: Vec3,
+    direction: Vec3,
+    distance: f32,
+    up: Vec3,
+    reach: f32,
+    settings: &ControllerSettings,
+) -> Option<Ledge> {
+    // Probe low enough to find walls whose top is below the hands.
+    let wall = B::cast_ray(ctx, hands - up * reach, direction, distance, true, filter)?;
+    let normal = (wall.normal - up * wall.normal.dot(up)).normalize_or_zero();
+    if wall.distance == 0.0 || normal == Vec3::ZERO || wall.normal.dot(up).abs() > 0.5 {
+        return None;
+    }
+
+    let origin = wall.point - normal * LEDGE_INSET + up * reach * 2.0;
+    let top = B::cast_ray(ctx, origin, -up, reach * 2.0, true, filter)?;
+    // A distance of zero means the ray started inside the wall, so it goes on above the hands.
+    if top.distance == 0.0 || top.normal.angle_between(up) > settings.ground_cast.max_ground_angle {
+        return None;
+    }
+
+    // The edge is where the top meets the wall's plane.
+    let anchor = top.point + normal * normal.dot(wall.point - top.point);
+    let along = top.normal.cross(normal).normalize_or_zero();
+    Some(Ledge {
+        entity: wall.entity,
+        anchor,
+        normal,
+        along,
+    })
+}
+
+/// Moves a character's grip `step` along `ledge`, following it around inner and outer corners. Returns `None` if the
+/// ledge is lost.
+#[allow(clippy::too_many_arguments)]
+fn shimmy_along<B: PhysicsBackend>(
+    ctx: &B::Context,
+    filter: &dyn Fn(Entity) -> bool,
+    ledge: &Ledge,
+    step: f32,
+    hang: &LedgeHang,
+    up: Vec3,
+    settings: &ControllerSettings,
+) -> Option<Ledge> {
+    let hands = ledge.anchor + ledge.normal * hang.hang_offset;
+    let find = |hands, direction, distance| {
+        find_ledge::<B>(
+            ctx, filter, hands, direction, distance, up, hang.reach, settings,
+        )
+    };
+    let distance = hang.hang_offset + LEDGE_PROBE_MARGIN;
+    if step == 0.0 {
+        return find(hands, -ledge.normal, distance);
+    }
+    let sideways = ledge.along * step.signum();
+
+    // An inner corner: another wall blocks the way.
+    if let Some(corner) = find(hands, sideways, hang.hang_offset + step.abs()) {
+        if corner.normal.dot(sideways) < -0.5 {
+            return Some(corner);
+        }
+    }
+
+    let moved = hands + ledge.along * step;
+    if let Some(next) = find(moved, -ledge.normal, distance) {
+        return Some(next);
+    }
+
+    // An outer corner: look back around the end of the wall for its side.
+    let around = moved - ledge.normal * distance + sideways * distance;
+    if let Some(corner) = find(around, -sideways, distance * 2.0) {
+        return Some(corner);
+    }
+
+    // The end of the ledge, so stay put.
+    find(hands, -ledge.normal, distance)
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Pushes down on the dynamic bodies characters stand on, see [`FloatSettings::ground_reaction`]. The impulses of all the
+/// characters on a body are added to its [`PhysicsBackend::Impulse`], on top of any impulse other code gave it this frame.
+#[allow(clippy::type_complexity)]
+pub fn push_ground<B: PhysicsBackend>(
+    mut commands: Commands,
+    bodies: Query<(
+        Entity,
+        &ControllerSettings,
+        &ControllerFrame,
+        &ControllerForces,
+    )>,
+    mut grounds: Query<(&B::Body, Option<&mut B::Impulse>), Without<ControllerState>>,
+    ctx: Res<B::Context>,
+    mut impulses: Local<HashMap<Entity, (Vec3, Vec3)>>,
+) {
+    impulses.clear();
+    for (entity, settings, frame, forces) in bodies.iter() {
+        if !frame.active || !frame.grounded || settings.float.ground_reaction <= 0.0 {
+            continue;
+        }
+        let hit = match frame.ground {
+            Some(hit) => hit,
+            None => continue,
+        };
+        let ground = match B::body_entity(&ctx, hit.entity) {
+            Some(ground) => ground,
+            None => continue,
+        };
+        if !grounds
+            .get(ground)
+            .is_ok_and(|(body, _)| B::is_dynamic(body))
+        {
+            continue;
+        }
+
+        let mass = B::mass(&ctx, entity).unwrap_or(1.0);
+        let weight = -settings.up_vector * settings.gravity * mass * frame.dt;
+        let push = (weight - forces.float) * settings.float.ground_reaction;
+        let center = B::center_of_mass(&ctx, ground).unwrap_or(hit.point);
+
+        let (linear, angular) = impulses.entry(ground).or_default();
+        *linear += push;
+        *angular += (hit.point - center).cross(push);
+    }
+
+    for (ground, (linear, angular)) in impulses.drain() {
+        match grounds.get_mut(ground) {
+            // Rapier doesn't reset an impulse once applied, so only add to it if something else set it since last frame.
+            // Otherwise it still holds last frame's impulse, which was already applied.
+            Ok((_, Some(mut external))) if external.is_changed() => {
+                B::add_impulse(&mut external, linear, angular);
+            }
+            Ok((_, Some(mut external))) => B::apply_impulse(&mut external, linear, angular),
+            _ => {
+                commands.entity(ground).insert(B::impulse(linear, angular));
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Turns the up vector of characters moving in a [`ReferenceFrame`] along with its body, and converts their input from
+/// the body's local space to world space for the movement stages. See [`leave_reference_frame`].
+pub fn enter_reference_frame(
+    mut bodies: Query<(
+        &mut ReferenceFrame,
+        &mut ControllerSettings,
+        &mut ControllerInput,
+    )>,
+    transforms: Query<&GlobalTransform>,
+) {
+    for (mut reference_frame, mut settings, mut input) in bodies.iter_mut() {
+        let body_tf = match reference_frame
+            .body
+            .and_then(|body| transforms.get(body).ok())
+        {
+            Some(body_tf) => *body_tf,
+            None => continue,
+        };
+        let rotation = body_tf.to_scale_rotation_translation().1;
+        settings.up_vector = (rotation * reference_frame.up).normalize_or_zero();
+
+        if reference_frame.local_input {
+            reference_frame.local = Some((input.movement, input.facing, input.look_at));
+            input.movement = rotation * input.movement;
+            input.facing = rotation * input.facing;
+            input.look_at = input.look_at.map(|target| body_tf.mul_vec3(target));
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Turns the up vector of characters with a [`DynamicGravity`] towards the [`GravitySource`] they're in.
+#[allow(clippy::type_complexity)]
+pub fn follow_gravity_sources<B: PhysicsBackend>(
+    mut bodies: Query<(
+        &GlobalTransform,
+        &mut ControllerSettings,
+        &mut DynamicGravity,
+        Option<&ReferenceFrame>,
+    )>,
+    sources: Query<(Entity, &GravitySource, &GlobalTransform)>,
+    ctx: Res<B::Context>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+) {
+    for (tf, mut settings, mut gravity, reference_frame) in bodies.iter_mut() {
+        if reference_frame.is_some_and(|reference_frame| reference_frame.body.is_some()) {
+            continue;
+        }
+        let position = tf.translation();
+
+        // Volumes win over point sources, and the nearest point source over the others.
+        let mut volume = None;
+        B::intersections_with_point(
+            &ctx,
+            position,
+            &|collider| {
+                matches!(
+                    sources.get(collider),
+                    Ok((_, GravitySource::Volume { .. }, _))
+                )
+            },
+            &mut |collider| {
+                volume = Some(volume.map_or(collider, |volume: Entity| volume.min(collider)));
+                true
+            },
+        );
+        let source = volume.or_else(|| {
+            sources
+                .iter()
+                .filter_map(|(entity, source, source_tf)| match source {
+                    GravitySource::Point { radius } => {
+                        let distance = source_tf.translation().distance(position);
+                        (distance <= *radius).then_some((entity, distance))
+                    }
+                    GravitySource::Volume { .. } => None,
+                })
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(entity, _)| entity)
+        });
+        gravity.source = source;
+
+        let target = match source.and_then(|source| sources.get(source).ok()) {
+            Some((_, GravitySource::Point { .. }, source_tf)) => {
+                (position - source_tf.translation()).normalize_or_zero()
+            }
+            Some((_, GravitySource::Volume { up }, source_tf)) => {
+                (source_tf.to_scale_rotation_translation().1 * *up).normalize_or_zero()
+            }
+            None => gravity
+                .fallback
+                .map_or(Vec3::ZERO, |fallback| fallback.normalize_or_zero()),
+        };
+        let current = settings.up_vector.normalize_or_zero();
+        if target == Vec3::ZERO || target == current {
+            continue;
+        }
+        if current == Vec3::ZERO || gravity.turn_rate <= 0.0 {
+            settings.up_vector = target;
+            continue;
+        }
+
+        let dt = settings.clock.delta_seconds::<B>(&time, &config);
+        let turn = Quat::IDENTITY.slerp(
+            Quat::from_rotation_arc(current, target),
+            (gravity.turn_rate * dt).min(1.0),
+        );
+        settings.up_vector = (turn * current).normalize();
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Restores the input of characters moving in a [`ReferenceFrame`] to the body's local space once the movement stages
+/// are done with it, so it stays as the user set it. See [`enter_reference_frame`].
+pub fn leave_reference_frame(mut bodies: Query<(&mut ReferenceFrame, &mut ControllerInput)>) {
+    for (mut reference_frame, mut input) in bodies.iter_mut() {
+        if let Some((movement, facing, look_at)) = reference_frame.local.take() {
+            input.movement = movement;
+            input.facing = facing;
+            input.look_at = look_at;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Keeps [`WorldPosition`]s up to date, and recenters the [`WorldOrigin`] on the first [`LodViewer`] once it gets too far
+/// from it. Does nothing without a [`WorldOrigin`].
+#[allow(clippy::type_complexity)]
+pub fn recenter_world_origin<B: PhysicsBackend>(
+    mut commands: Commands,
+    origin: Option<ResMut<WorldOrigin>>,
+    mut positions: Query<(&GlobalTransform, &mut WorldPosition)>,
+    viewers: Query<&GlobalTransform, With<LodViewer>>,
+    mut roots: Query<
+        &mut Transform,
+        (
+            Without<Parent>,
+            Or<(
+                With<B::Body>,
+                With<B::Collider>,
+                With<LodViewer>,
+                With<ShiftWithOrigin>,
+            )>,
+        ),
+    >,
+) {
+    let mut origin = match origin {
+        Some(origin) => origin,
+        None => return,
+    };
+    for (tf, mut position) in positions.iter_mut() {
+        position.0 = origin.to_world(tf.translation());
+    }
+
+    let viewer = match viewers.iter().next() {
+        Some(viewer) => viewer.translation(),
+        None => return,
+    };
+    if viewer.length() <= origin.recenter_distance {
+        return;
+    }
+    let offset = -viewer;
+    for mut tf in roots.iter_mut() {
+        tf.translation += offset;
+    }
+    origin.position -= offset.as_dvec3();
+    commands.add(ShiftControllerOrigin { offset });
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Parents characters using [`PlatformCarry::Parent`] to the platform they are standing on, and unparents them
+/// once they jump or leave it.
+pub fn platform_carry<B: PhysicsBackend>(
+    mut commands: Commands,
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut Transform,
+        &mut ControllerState,
+        &ControllerSettings,
+    )>,
+    platforms: Query<(&GlobalTransform, &B::Body)>,
+    children: Query<&Children>,
+) {
+    for (entity, global, mut tf, mut controller, settings) in bodies.iter_mut() {
+        let target = match settings.locomotion.platform_carry {
+            PlatformCarry::Parent => controller.last_ground.filter(|ground| {
+                platforms
+                    .get(*ground)
+                    .map(|(_, body)| !B::is_fixed(body))
+                    .unwrap_or(false)
+            }),
+            PlatformCarry::Velocity => None,
+        };
+
+        if target == controller.carry_parent {
+            continue;
+        }
+
+        if let Some(old) = controller.carry_parent.take() {
+            // The platform may have been despawned without taking us with it.
+            if children.get(old).is_ok() {
+                commands.entity(old).remove_children(&[entity]);
+            } else {
+                commands.entity(entity).remove::<Parent>();
+            }
+            *tf = global.compute_transform();
+        }
+
+        if let Some(platform) = target {
+            if let Ok((platform_tf, _)) = platforms.get(platform) {
+                let local = platform_tf.affine().inverse() * global.affine();
+                *tf = Transform::from_matrix(local.into());
+                commands.entity(platform).add_child(entity);
+                controller.carry_parent = Some(platform);
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Applies stance switches requested through [`ControllerStances`] to [`ControllerSettings`], blending them over time if requested.
+/// Characters switching to a collider that doesn't fit where they are get pushed out, see
+/// [`ControllerStances::push_out_distance`].
+#[allow(clippy::type_complexity)]
+pub fn switch_stances<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut Transform,
+        &mut ControllerStances,
+        &mut ControllerSettings,
+        Option<&mut B::Collider>,
+    )>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+    ctx: Res<B::Context>,
+    mut events: EventWriter<StanceChangedEvent>,
+) {
+    for (entity, tf, mut transform, mut stances, mut settings, mut collider) in bodies.iter_mut() {
+        let stances = &mut *stances;
+        let dt = settings.clock.delta_seconds::<B>(&time, &config);
+
+        if let Some((to, blend_time)) = stances.pending.take() {
+            let target = match stances.stances.get(&to) {
+                Some(target) => target,
+                None => {
+                    warn!("{:?} has no stance named {:?}", entity, to);
+                    continue;
+                }
+            };
+
+            // Move out from under overhangs now, rather than having the solver shove the character out once the new
+            // collider has grown into them.
+            if let (true, Some(shape)) =
+                (stances.push_out_distance > 0.0, stances.colliders.get(&to))
+            {
+                let up = settings.up_vector;
+                let position =
+                    tf.translation() + up * (target.float.distance - settings.float.distance);
+                let rotation = tf.to_scale_rotation_translation().1;
+                if let Some(offset) = push_out_offset::<B>(
+                    &ctx,
+                    entity,
+                    position,
+                    rotation,
+                    up,
+                    shape,
+                    stances.push_out_distance,
+                ) {
+                    transform.translation += offset;
+                }
+            }
+
+            // Colliders that can be blended are grown or shrunk along with the settings, so the body doesn't pop out of the floor.
+            let mut from_collider = None;
+            if let (Some(collider), Some(target)) = (&mut collider, stances.colliders.get(&to)) {
+                let current = B::collider_shape(collider);
+                if blend_time > 0.0 && blend_collider(&current, target, 0.0).is_some() {
+                    from_collider = Some(current);
+                } else {
+                    B::set_collider_shape(collider, target.clone());
+                }
+            }
+
+            if blend_time > 0.0 {
+                stances.blend = Some(StanceBlend {
+                    from: settings.clone(),
+                    from_collider,
+                    elapsed: 0.0,
+                    duration: blend_time,
+                });
+            } else {
+                *settings = target.clone();
+                stances.blend = None;
+            }
+
+            events.send(StanceChangedEvent {
+                entity,
+                from: stances.current.replace(to.clone()),
+                to,
+                blend_time,
+            });
+        }
+
+        if let Some(blend) = &mut stances.blend {
+            let target = match stances
+                .current
+                .as_ref()
+                .and_then(|c| stances.stances.get(c))
+            {
+                Some(target) => target,
+                None => {
+                    stances.blend = None;
+                    continue;
+                }
+            };
+
+            blend.elapsed += dt;
+            let t = (blend.elapsed / blend.duration).min(1.0);
+
+            let mut blended = target.clone();
+            blend_reflect(&blend.from, &mut blended, t);
+            *settings = blended;
+
+            if let (Some(collider), Some(from), Some(to)) = (
+                &mut collider,
+                &blend.from_collider,
+                stances
+                    .current
+                    .as_ref()
+                    .and_then(|c| stances.colliders.get(c)),
+            ) {
+                let blended = blend_collider(from, to, t).unwrap_or_else(|| to.clone());
+                B::set_collider_shape(collider, blended);
+            }
+
+            if t >= 1.0 {
+                stances.blend = None;
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Crouches characters while [`ControllerInput::crouching`] is held, and stands them back up once it's released and there's
+/// room above them. See [`CrouchSettings`](crate::CrouchSettings).
+#[allow(clippy::type_complexity)]
+pub fn crouch<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut ControllerState,
+        &ControllerSettings,
+        &ControllerInput,
+        &mut B::Collider,
+    )>,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, mut controller, settings, input, mut collider) in bodies.iter_mut() {
+        if input.crouching && !controller.crouched {
+            let standing = B::collider_shape(&collider);
+            let crouched = match crouched_capsule(&standing, settings) {
+                Some(crouched) => crouched,
+                None => continue,
+            };
+            B::set_collider_shape(&mut collider, crouched);
+            controller.standing_collider = Some(standing);
+            controller.crouched = true;
+        } else if !input.crouching && controller.crouched {
+            let standing = match &controller.standing_collider {
+                Some(standing) => standing,
+                None => continue,
+            };
+            let (crouched_top, radius) = match capsule_top(&B::collider_shape(&collider)) {
+                Some(top) => top,
+                None => continue,
+            };
+            let standing_top = match capsule_top(standing) {
+                Some((top, _)) => top,
+                None => continue,
+            };
+
+            // Look for a ceiling above the crouched capsule, up to where the top of the standing one would be once the
+            // character floats back up. A slightly narrower probe doesn't catch walls the character is touching.
+            let up = settings.up_vector;
+            let rise = settings.float.distance - settings.crouch.float_distance;
+            let (_, rotation, position) = tf.to_scale_rotation_translation();
+            let start = position + rotation * crouched_top;
+            let distance = up.dot(rotation * (standing_top - crouched_top)) + rise;
+            let ceiling = distance > 0.0
+                && B::cast_shape(
+                    &ctx,
+                    start,
+                    rotation,
+                    up,
+                    &Collider::ball(radius * CROUCH_PROBE_SCALE),
+                    distance,
+                    &|collider| collider != entity,
+                )
+                .is_some();
+            if ceiling {
+                continue;
+            }
+
+            B::set_collider_shape(&mut collider, controller.standing_collider.take().unwrap());
+            controller.crouched = false;
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Makes characters sprint while [`ControllerInput::sprinting`] is held, and drains and refills their
+/// [`SprintStamina`](crate::SprintStamina). See [`SprintSettings`](crate::SprintSettings).
+pub fn sprint<B: PhysicsBackend>(
+    mut bodies: Query<(
+        &mut ControllerState,
+        &ControllerSettings,
+        &ControllerInput,
+        Option<&mut SprintStamina>,
+    )>,
+    time: Res<Time>,
+    config: Res<B::Config>,
+) {
+    for (mut controller, settings, input, stamina) in bodies.iter_mut() {
+        let dt = settings.clock.delta_seconds::<B>(&time, &config);
+        let mut sprinting = input.sprinting
+            && !controller.crouched
+            && settings.sprint.max_speed > 0.0
+            && input.movement != Vec3::ZERO;
+
+        if let Some(mut stamina) = stamina {
+            sprinting &= !stamina.exhausted;
+            if sprinting {
+                let current = stamina.current - stamina.drain * dt;
+                stamina.set_current(current);
+                let delay = stamina.regen_delay;
+                stamina.regen_timer.start(delay);
+            } else if stamina.regen_timer.is_running() {
+                stamina.regen_timer.tick(dt);
+            } else {
+                stamina.current = (stamina.current + stamina.regen * dt).min(stamina.max);
+                if stamina.current >= stamina.recover.min(stamina.max) {
+                    stamina.exhausted = false;
+                }
+            }
+        }
+
+        controller.sprinting = sprinting;
+    }
+}
+
+/// The capsule `collider` shortened to [`CrouchSettings::height`](crate::CrouchSettings::height), keeping its bottom at
+/// the same height above the ground once the character floats at the crouched distance. `None` if crouching is disabled
+/// or `collider` isn't a capsule.
+fn crouched_capsule(collider: &Collider, settings: &ControllerSettings) -> Option<Collider> {
+    let capsule = collider.as_capsule()?;
+    let (a, b, radius) = (
+        capsule.segment().a(),
+        capsule.segment().b(),
+        capsule.radius(),
+    );
+    let crouch = &settings.crouch;
+    if crouch.height <= 0.0 {
+        return None;
+    }
+    let (bottom, top) = if a.y <= b.y { (a, b) } else { (b, a) };
+    let axis = (top - bottom).try_normalize().unwrap_or(Vec3::Y);
+    let bottom = bottom + axis * (settings.float.distance - crouch.float_distance);
+    let length = (crouch.height - radius * 2.0).max(0.0);
+    Some(Collider::capsule(bottom, bottom + axis * length, radius))
+}
+
+/// The center of the upper cap of a capsule collider, and its radius.
+fn capsule_top(collider: &Collider) -> Option<(Vec3, f32)> {
+    let capsule = collider.as_capsule()?;
+    let (a, b) = (capsule.segment().a(), capsule.segment().b());
+    Some((if a.y >= b.y { a } else { b }, capsule.radius()))
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Switches characters with an [`AutoCrouch`] to crouch when there isn't room to stand where they are or are heading, and
+/// back to standing once there is.
+#[allow(clippy::type_complexity)]
+pub fn auto_crouch<B: PhysicsBackend>(
+    mut bodies: Query<(
+        Entity,
+        &GlobalTransform,
+        &B::Velocity,
+        &ControllerSettings,
+        &mut ControllerStances,
+        &mut AutoCrouch,
+    )>,
+    ctx: Res<B::Context>,
+) {
+    for (entity, tf, velocity, settings, mut stances, mut auto) in bodies.iter_mut() {
+        let (stand_settings, stand_collider) = match (
+            stances.stances.get(&auto.stand),
+            stances.colliders.get(&auto.stand),
+        ) {
+            (Some(settings), Some(collider)) => (settings, collider),
+            _ => continue,
+        };
+
+        let up = settings.up_vector;
+        // Where the character would float if it stood up right now.
+        let standing =
+            tf.translation() + up * (stand_settings.float.distance - settings.float.distance);
+        let linvel = B::linear_velocity(velocity);
+        let ahead = (linvel - up * linvel.dot(up)) * auto.lookahead;
+        let rotation = tf.to_scale_rotation_translation().1;
+        let fits = |position: Vec3| {
+            !B::overlaps(&ctx, position, rotation, stand_collider, &|collider| {
+                collider != entity
+            })
+        };
+        let room = fits(standing) && fits(standing + ahead);
+
+        let current = stances.current();
+        if !room && !auto.crouched && current != Some(auto.crouch.as_str()) {
+            auto.crouched = true;
+            let (crouch, blend_time) = (auto.crouch.clone(), auto.blend_time);
+            stances.switch_to(crouch, blend_time);
+        } else if room && auto.crouched {
+            auto.crouched = false;
+            // Don't stand up if something else switched stance in the meantime.
+            if current == Some(auto.crouch.as_str()) {
+                let (stand, blend_time) = (auto.stand.clone(), auto.blend_time);
+                stances.switch_to(stand, blend_time);
+            }
+        }
+    }
+}
+
+/// Moves `shape` along `velocity` for `dt` seconds, stopping at the colliders in its way and sliding along them instead.
+/// Returns how far it moved, and takes the part of `velocity` heading into the colliders it hit out of it.
+#[allow(clippy::too_many_arguments)]
+fn collide_and_slide<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    position: Vec3,
+    rotation: Quat,
+    shape: &Collider,
+    dt: f32,
+    velocity: &mut Vec3,
+) -> Vec3 {
+    let mut motion = *velocity * dt;
+    let mut moved = Vec3::ZERO;
+    let mut overlapped = Vec::new();
+    for _ in 0..SLIDE_ITERATIONS {
+        let distance = motion.length();
+        if distance <= f32::EPSILON {
+            break;
+        }
+        let direction = motion / distance;
+        let hit = loop {
+            let hit = B::cast_shape(
+                ctx,
+                position + moved,
+                rotation,
+                direction,
+                shape,
+                distance + SLIDE_SKIN_WIDTH,
+                &|collider| collider != entity && !overlapped.contains(&collider),
+            );
+            match hit {
+                // Let overlapping characters move apart rather than getting stuck in each other, but still stop at
+                // whatever is behind them.
+                Some(hit) if hit.penetrating || hit.normal.dot(direction) >= 0.0 => {
+                    overlapped.push(hit.entity)
+                }
+                hit => break hit,
+            }
+        };
+        let hit = match hit {
+            Some(hit) => hit,
+            None => {
+                moved += motion;
+                break;
+            }
+        };
+
+        let travel = (hit.distance - SLIDE_SKIN_WIDTH).clamp(0.0, distance);
+        moved += direction * travel;
+        motion -= direction * travel;
+        motion -= hit.normal * motion.dot(hit.normal).min(0.0);
+        *velocity -= hit.normal * velocity.dot(hit.normal).min(0.0);
+    }
+    moved
+}
+
+/// The shortest sideways offset, perpendicular to `up`, that moves `shape` at `position` out of everything it overlaps, if
+/// it overlaps anything and there is room within `max_distance`.
+fn push_out_offset<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    position: Vec3,
+    rotation: Quat,
+    up: Vec3,
+    shape: &Collider,
+    max_distance: f32,
+) -> Option<Vec3> {
+    let fits = |position: Vec3| {
+        !B::overlaps(ctx, position, rotation, shape, &|collider| {
+            collider != entity
+        })
+    };
+    if fits(position) {
+        return None;
+    }
+
+    let side = up.any_orthonormal_vector();
+    (1..=PUSH_OUT_STEPS)
+        .flat_map(|step| {
+            let distance = max_distance * step as f32 / PUSH_OUT_STEPS as f32;
+            (0..PUSH_OUT_DIRECTIONS).map(move |direction| {
+                let angle = std::f32::consts::TAU * direction as f32 / PUSH_OUT_DIRECTIONS as f32;
+                Quat::from_axis_angle(up, angle) * side * distance
+            })
+        })
+        .find(|&offset| fits(position + offset))
+}
+
+/// Interpolates between two colliders of the same kind of shape, or returns `None` for shapes that can't be interpolated.
+fn blend_collider(from: &Collider, to: &Collider, t: f32) -> Option<Collider> {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    if let (Some(from), Some(to)) = (from.as_capsule(), to.as_capsule()) {
+        let (from_segment, to_segment) = (from.segment(), to.segment());
+        Some(Collider::capsule(
+            from_segment.a().lerp(to_segment.a(), t),
+            from_segment.b().lerp(to_segment.b(), t),
+            lerp(from.radius(), to.radius()),
+        ))
+    } else if let (Some(from), Some(to)) = (from.as_ball(), to.as_ball()) {
+        Some(Collider::ball(lerp(from.radius(), to.radius())))
+    } else if let (Some(from), Some(to)) = (from.as_cuboid(), to.as_cuboid()) {
+        let half_extents = from.half_extents().lerp(to.half_extents(), t);
+        Some(Collider::cuboid(
+            half_extents.x,
+            half_extents.y,
+            half_extents.z,
+        ))
+    } else if let (Some(from), Some(to)) = (from.as_cylinder(), to.as_cylinder()) {
+        Some(Collider::cylinder(
+            lerp(from.half_height(), to.half_height()),
+            lerp(from.radius(), to.radius()),
+        ))
+    } else {
+        None
+    }
+}
+
+/// Interpolates every `f32` and `Vec3` reachable through reflection from `from` towards the values already in `to`.
+/// Everything else keeps the value in `to`.
+fn blend_reflect(from: &dyn Reflect, to: &mut dyn Reflect, t: f32) {
+    if let (Some(from), Some(to)) = (from.downcast_ref::<f32>(), to.downcast_mut::<f32>()) {
+        *to = *from + (*to - *from) * t;
+    } else if let (Some(from), Some(to)) = (from.downcast_ref::<Vec3>(), to.downcast_mut::<Vec3>())
+    {
+        *to = from.lerp(*to, t);
+    } else if let (ReflectRef::Struct(from), ReflectMut::Struct(to)) =
+        (from.reflect_ref(), to.reflect_mut())
+    {
+        for i in 0..to.field_len() {
+            if let (Some(from), Some(to)) = (from.field_at(i), to.field_at_mut(i)) {
+                blend_reflect(from, to, t);
+            }
+        }
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Moves children with [`RenderInterpolation`] between the poses of their body before and after the last physics step.
+/// Must run after the physics engine's writeback and before transform propagation.
+pub fn interpolate_render<B: PhysicsBackend>(
+    mut children: Query<(&Parent, &mut Transform, &mut RenderInterpolation)>,
+    bodies: Query<(&Transform, Option<&B::Interpolation>), Without<RenderInterpolation>>,
+    config: Res<B::Config>,
+    render_time: Res<B::RenderTime>,
+    time: Res<Time>,
+    mut last_diff: Local<f32>,
+) {
+    let physics_dt = B::interpolation_step(&config);
+    let diff = B::render_offset(&render_time);
+    // The frame time is added to `diff`, and a `dt` taken off of it for every step run.
+    let stepped = physics_dt
+        .map(|dt| *last_diff + time.delta_seconds() - diff >= dt * 0.5)
+        .unwrap_or(false);
+    *last_diff = diff;
+
+    for (parent, mut tf, mut render) in children.iter_mut() {
+        let (body, interpolation) = match bodies.get(parent.get()) {
+            Ok(body) => body,
+            Err(_) => continue,
+        };
+        let dt = match physics_dt {
+            Some(dt) if interpolation.is_none() => dt,
+            _ => {
+                render.steps = None;
+                *tf = render.offset;
+                continue;
+            }
+        };
+
+        render.steps = match render.steps {
+            Some((_, end)) if stepped => Some((end, *body)),
+            // The body was moved without a physics step, e.g. teleported, so there is nothing to interpolate from.
+            Some((_, end)) if end != *body => Some((*body, *body)),
+            None => Some((*body, *body)),
+            steps => steps,
+        };
+        let (start, end) = render.steps.unwrap();
+
+        let t = ((dt + diff) / dt).clamp(0.0, 1.0);
+        let interpolated = Transform {
+            translation: start.translation.lerp(end.translation, t),
+            rotation: start.rotation.slerp(end.rotation, t),
+            scale: start.scale.lerp(end.scale, t),
+        };
+        *tf = Transform::from_matrix(
+            body.compute_matrix().inverse()
+                * interpolated.compute_matrix()
+                * render.offset.compute_matrix(),
+        );
+    }
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.*
+///
+/// Brings the physics engine's queries up to date with the current collider positions, see [`PhysicsBackend::sync_queries`].
+/// Only added if [`WanderlustSchedule::update_query_pipeline`](crate::WanderlustSchedule::update_query_pipeline) is set.
+pub fn update_query_pipeline<B: PhysicsBackend>(mut ctx: ResMut<B::Context>) {
+    B::sync_queries(&mut ctx);
+}
+
+/// *Note: Most users will not need to use this directly. Use [`WanderlustPlugin`](crate::plugins::WanderlustPlugin) instead.
+/// Alternatively, if one only wants to disable the system, use [`WanderlustPhysicsTweaks`](WanderlustPhysicsTweaks).*
+///
+/// This system adds some tweaks to the physics settings that make the character controller behave better, see
+/// [`PhysicsBackend::tweak`].
+pub fn setup_physics_context<B: PhysicsBackend>(
+    mut ctx: ResMut<B::Context>,
+    should_change: Option<Res<WanderlustPhysicsTweaks>>,
+) {
+    if should_change.map(|s| s.should_do_tweaks()).unwrap_or(true) {
+        B::tweak(&mut ctx);
+    }
+}
+
+/// Finds two colliders that press into the character from opposite sides, at least one of which penetrates it
+/// deeper than `penetration`, if any.
+fn crushing_colliders<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    penetration: f32,
+) -> Option<(Entity, Entity)> {
+    let mut contacts = Vec::new();
+    B::contacts(ctx, entity, &mut contacts);
+    let mut pushes: Vec<(Entity, Vec3, f32)> = Vec::new();
+    for contact in contacts {
+        if contact.depth < 0.0 {
+            continue;
+        }
+
+        let (other, normal, depth) = (contact.entity, contact.normal, contact.depth);
+        if let Some((first, _, _)) = pushes
+            .iter()
+            .find(|(e, n, d)| *e != other && n.dot(normal) < -0.5 && d.max(depth) > penetration)
+        {
+            return Some((*first, other));
+        }
+        pushes.push((other, normal, depth));
+    }
+    None
+}
+
+/// If a ground cast `hit` too steep to stand on is the edge of a step no higher than
+/// [`GroundCastSettings::step_height`] above `feet`, returns the normal of the top of the step.
+fn step_tread<B: PhysicsBackend>(
+    ctx: &B::Context,
+    filter: &dyn Fn(Entity) -> bool,
+    hit: &ShapeHit,
+    feet: Vec3,
+    settings: &ControllerSettings,
+) -> Option<Vec3> {
+    let up = settings.up_vector;
+    let height = up.dot(hit.point - feet);
+    if settings.ground_cast.step_height <= 0.0
+        || height <= 0.0
+        || height > settings.ground_cast.step_height
+    {
+        return None;
+    }
+    // Which way the step faces, towards the lower side.
+    let outward = (hit.normal - up * hit.normal.dot(up)).normalize_or_zero();
+    if outward == Vec3::ZERO {
+        return None;
+    }
+
+    // The riser below the edge has to be near vertical. On a slope, this finds the slope itself.
+    let riser_origin = hit.point - up * STEP_PROBE_DEPTH + outward * STEP_PROBE_DEPTH;
+    let riser = B::cast_ray(
+        ctx,
+        riser_origin,
+        -outward,
+        STEP_PROBE_DEPTH * 2.0,
+        true,
+        filter,
+    )?;
+    if riser.distance == 0.0 || riser.normal.dot(up).abs() > STEP_RISER_MAX_SLOPE {
+        return None;
+    }
+
+    // And the tread above it has to be flat enough to stand on.
+    let tread_origin = hit.point - outward * STEP_PROBE_DEPTH + up * STEP_PROBE_DEPTH;
+    let tread = B::cast_ray(ctx, tread_origin, -up, STEP_PROBE_DEPTH * 2.0, true, filter)?;
+    if tread.distance == 0.0
+        || tread.normal.angle_between(up) > settings.ground_cast.max_ground_angle
+    {
+        return None;
+    }
+    Some(tread.normal)
+}
+
+/// Whether `vehicle` is still below an airborne character at `position`, within its
+/// [`VehicleInterior::max_height`](crate::VehicleInterior::max_height).
+fn vehicle_below<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    settings: &ControllerSettings,
+    position: Vec3,
+    vehicle: Entity,
+    interiors: &Query<&VehicleInterior>,
+) -> bool {
+    let max_height = match interiors.get(vehicle) {
+        Ok(interior) => interior.max_height,
+        Err(_) => return false,
+    };
+    B::cast_ray(
+        ctx,
+        position,
+        -settings.up_vector,
+        max_height,
+        true,
+        &|collider| collider != entity && B::body_entity(ctx, collider) == Some(vehicle),
+    )
+    .is_some()
+}
+
+/// Finds how far to move a character blocked by a ledge while moving along `movement` to get it on top of the ledge,
+/// if the top is within [`JumpSettings::ledge_forgiveness`](crate::JumpSettings::ledge_forgiveness) of where it is caught.
+fn ledge_forgiveness<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    settings: &ControllerSettings,
+    movement: Vec3,
+) -> Option<Vec3> {
+    let up = settings.up_vector;
+    let forgiveness = settings.jump.ledge_forgiveness;
+    let dir = (movement - up * movement.dot(up)).normalize_or_zero();
+    if dir == Vec3::ZERO {
+        return None;
+    }
+
+    let mut contacts = Vec::new();
+    B::contacts(ctx, entity, &mut contacts);
+    let mut lift: Option<f32> = None;
+    for contact in contacts {
+        // Only walls that block the character's movement.
+        if contact.normal.dot(up).abs() > 0.5 || contact.normal.dot(dir) > -0.5 {
+            continue;
+        }
+
+        let origin = contact.point + up * forgiveness + dir * LEDGE_FORGIVENESS_NUDGE;
+        let hit = B::cast_ray(ctx, origin, -up, forgiveness, true, &|collider| {
+            collider != entity
+        });
+        // A distance of zero means the ray started inside the obstacle, so it's a wall rather than a ledge.
+        if let Some(hit) = hit {
+            if hit.distance > 0.0
+                && hit.normal.angle_between(up) <= settings.ground_cast.max_ground_angle
+            {
+                let height = forgiveness - hit.distance;
+                lift = Some(lift.map_or(height, |lift| lift.max(height)));
+            }
+        }
+    }
+
+    lift.map(|lift| up * lift + dir * LEDGE_FORGIVENESS_NUDGE)
+}
+
+/// Finds the acceleration that pushes a character sideways around an obstacle that blocks its `movement` but ends
+/// close to the side. See [`LocomotionSettings::corner_nudge`](crate::LocomotionSettings::corner_nudge).
+fn corner_nudge<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    settings: &ControllerSettings,
+    movement: Vec3,
+    velocity: &Velocity,
+) -> Vec3 {
+    let up = settings.up_vector;
+    let dir = (movement - up * movement.dot(up)).normalize_or_zero();
+    // Only nudge when actually held back; sliding along a wall is fine as it is.
+    let desired_speed = movement.length().min(1.0) * settings.locomotion.max_speed;
+    if dir == Vec3::ZERO || velocity.linvel.dot(dir) >= desired_speed * 0.5 {
+        return Vec3::ZERO;
+    }
+
+    let right = dir.cross(up).normalize_or_zero();
+    // Whether there is nothing in the way `offset` to the side of `point`.
+    let open = |point: Vec3, offset: Vec3| {
+        B::cast_ray(
+            ctx,
+            point + offset - dir * CORNER_NUDGE_PROBE,
+            dir,
+            CORNER_NUDGE_PROBE * 2.0,
+            true,
+            &|collider| collider != entity,
+        )
+        .is_none()
+    };
+
+    let mut contacts = Vec::new();
+    B::contacts(ctx, entity, &mut contacts);
+    for contact in contacts {
+        // Only walls that block the character's movement.
+        if contact.normal.dot(up).abs() > 0.5 || contact.normal.dot(dir) > -0.5 {
+            continue;
+        }
+
+        // Look for the nearest edge, on either side.
+        for step in 1..=CORNER_NUDGE_STEPS {
+            let offset = right * settings.locomotion.corner_nudge_width * step as f32
+                / CORNER_NUDGE_STEPS as f32;
+            match (open(contact.point, offset), open(contact.point, -offset)) {
+                (true, false) => return right * settings.locomotion.corner_nudge,
+                (false, true) => return -right * settings.locomotion.corner_nudge,
+                // An obstacle thinner than the character, which can be passed either way.
+                (true, true) => {
+                    let side = if contact.normal.dot(right) >= 0.0 {
+                        right
+                    } else {
+                        -right
+                    };
+                    return side * settings.locomotion.corner_nudge;
+                }
+                (false, false) => {}
+            }
+        }
+    }
+
+    Vec3::ZERO
+}
+
+/// The signed angle to turn around `up` to face from `forward` towards `goal`, both flattened onto the plane of `up`.
+/// Zero if either direction is parallel to `up`.
+fn yaw_towards(up: Vec3, forward: Vec3, goal: Vec3) -> f32 {
+    let flatten = |v: Vec3| (v - up * v.dot(up)).normalize_or_zero();
+    let (forward, goal) = (flatten(forward), flatten(goal));
+    if forward == Vec3::ZERO || goal == Vec3::ZERO {
+        return 0.0;
+    }
+
+    forward.cross(goal).dot(up).atan2(forward.dot(goal))
+}
+
+/// The ground casts of all characters, issued together by [`find_ground`] before any character is moved, and then looked up
+/// by entity.
+pub struct GroundCastBatch<B: PhysicsBackend = RapierBackend> {
+    /// The hits of all casts, each cast's sorted by distance.
+    hits: Vec<ShapeHit>,
+    /// Which of the hits belong to each character.
+    ranges: HashMap<Entity, Range<usize>>,
+    /// The last cast of each character that caches it, see [`GroundCastSettings::cache_tolerance`].
+    cache: HashMap<Entity, CachedGroundCast>,
+    scratch: Vec<ShapeHit>,
+    contacts: Vec<Entity>,
+    backend: PhantomData<B>,
+}
+
+impl<B: PhysicsBackend> Default for GroundCastBatch<B> {
+    fn default() -> Self {
+        Self {
+            hits: default(),
+            ranges: default(),
+            cache: default(),
+            scratch: default(),
+            contacts: default(),
+            backend: PhantomData,
+        }
+    }
+}
+
+/// A character's ground cast, along with the poses of the colliders involved when it was cast.
+struct CachedGroundCast {
+    pose: (Vec3, Quat),
+    /// The cast itself: the shape, how far it was cast, and along which direction from which origin.
+    shape: Collider,
+    length: f32,
+    direction: Vec3,
+    origin: Vec3,
+    /// Each hit, with the pose of the collider it hit.
+    hits: Vec<(ShapeHit, Vec3, Quat)>,
+    /// The colliders the character was touching.
+    contacts: Vec<Entity>,
+}
+
+impl<B: PhysicsBackend> GroundCastBatch<B> {
+    fn clear(&mut self, entities: &Entities) {
+        self.hits.clear();
+        self.ranges.clear();
+        self.cache.retain(|entity, _| entities.contains(*entity));
+    }
+
+    fn invalidate(&mut self, entity: Entity) {
+        self.cache.remove(&entity);
+    }
+
+    /// Casts `shape` up to `length` for the ground of `entity`, keeping every collider it passes through. With a
+    /// [`cache_tolerance`](GroundCastSettings::cache_tolerance), the previous cast is reused instead if it is still valid.
+    #[allow(clippy::too_many_arguments)]
+    fn cast(
+        &mut self,
+        ctx: &B::Context,
+        entity: Entity,
+        shape_pos: Vec3,
+        shape_rot: Quat,
+        shape_vel: Vec3,
+        shape: &Collider,
+        length: f32,
+        settings: &GroundCastSettings,
+        filter: Option<&GroundCastFilter>,
+    ) {
+        let start = self.hits.len();
+        let pose = match (settings.cache_tolerance, B::collider_pose(ctx, entity)) {
+            (Some(tolerance), Some(pose)) => {
+                B::touching(ctx, entity, &mut self.contacts);
+                if let Some(cached) = self.cache.get(&entity) {
+                    let same_cast = same_shape(&cached.shape, shape)
+                        && cached.origin == settings.origin
+                        && (cached.length - length).abs() <= tolerance
+                        && cached.direction.angle_between(shape_vel) <= tolerance;
+                    if same_cast
+                        && cached.reuse::<B>(ctx, pose, &self.contacts, tolerance, &mut self.hits)
+                    {
+                        count(Counter::GroundCacheHits, 1);
+                        self.ranges.insert(entity, start..self.hits.len());
+                        return;
+                    }
+                    self.hits.truncate(start);
+                }
+                Some(pose)
+            }
+            _ => None,
+        };
+
+        count(Counter::GroundCasts, 1);
+        intersections_with_shape_cast::<B>(
+            ctx,
+            shape_pos,
+            shape_rot,
+            shape_vel,
+            shape,
+            length,
+            &|collider| collider != entity && filter.is_none_or(|filter| filter.allows(collider)),
+            &mut self.scratch,
+        );
+
+        if let Some(pose) = pose {
+            let hits = self
+                .scratch
+                .iter()
+                .filter_map(|&hit| {
+                    let (position, rotation) = B::collider_pose(ctx, hit.entity)?;
+                    Some((hit, position, rotation))
+                })
+                .collect();
+            self.cache.insert(
+                entity,
+                CachedGroundCast {
+                    pose,
+                    shape: shape.clone(),
+                    length,
+                    direction: shape_vel,
+                    origin: settings.origin,
+                    hits,
+                    contacts: std::mem::take(&mut self.contacts),
+                },
+            );
+        }
+
+        self.hits.append(&mut self.scratch);
+        self.ranges.insert(entity, start..self.hits.len());
+    }
+
+    /// The hits of the ground cast of `entity`, empty if it did not cast this frame.
+    fn hits(&self, entity: Entity) -> &[ShapeHit] {
+        match self.ranges.get(&entity) {
+            Some(range) => &self.hits[range.clone()],
+            None => &[],
+        }
+    }
+}
+
+impl CachedGroundCast {
+    /// Pushes the cached hits into `hits`, moved along with the colliders they hit, unless anything moved too far relative
+    /// to the character since they were cast, or the character touches different colliders. The cast itself has to be
+    /// checked to be the same beforehand. Returns whether the cache was
+    /// still valid; if not, `hits` may have been partially filled.
+    fn reuse<B: PhysicsBackend>(
+        &self,
+        ctx: &B::Context,
+        pose: (Vec3, Quat),
+        contacts: &[Entity],
+        tolerance: f32,
+        hits: &mut Vec<ShapeHit>,
+    ) -> bool {
+        // With nothing found to move along with, the character has to stay put.
+        if self.contacts != contacts
+            || self.pose.1.angle_between(pose.1) > tolerance
+            || (self.hits.is_empty() && pose.0.distance(self.pose.0) > tolerance)
+        {
+            return false;
+        }
+
+        for &(hit, position, rotation) in &self.hits {
+            let (new_position, new_rotation) = match B::collider_pose(ctx, hit.entity) {
+                Some(pose) => pose,
+                None => return false,
+            };
+            let moved = new_position - position;
+            if (moved - (pose.0 - self.pose.0)).length() > tolerance
+                || new_rotation.angle_between(rotation) > tolerance
+            {
+                return false;
+            }
+            hits.push(ShapeHit {
+                point: hit.point + moved,
+                ..hit
+            });
+        }
+        true
+    }
+}
+
+/// The pose of a character as of the last physics step, rather than the interpolated one it is rendered at.
+fn physics_transform<B: PhysicsBackend>(
+    ctx: &B::Context,
+    entity: Entity,
+    tf: &GlobalTransform,
+    interpolation: Option<&B::Interpolation>,
+    config: &B::Config,
+) -> GlobalTransform {
+    match (B::interpolation_step(config), interpolation) {
+        (Some(_), Some(_)) => match B::body_pose(ctx, entity) {
+            Some((translation, rotation)) => Transform {
+                translation,
+                rotation,
+                scale: tf.to_scale_rotation_translation().0,
+            }
+            .into(),
+            None => *tf,
+        },
+        _ => *tf,
+    }
+}
+
+/// Collects every collider hit by a shape cast into `collisions`, sorted by distance and then by entity index, so that
+/// identical worlds always produce identical orderings regardless of how the query pipeline returned them.
+#[allow(clippy::too_many_arguments)]
+fn intersections_with_shape_cast<B: PhysicsBackend>(
+    ctx: &B::Context,
+    shape_pos: Vec3,
+    shape_rot: Quat,
+    shape_vel: Vec3,
+    shape: &Collider,
+    max_distance: f32,
+    filter: &dyn Fn(Entity) -> bool,
+    collisions: &mut Vec<ShapeHit>,
+) {
+    collisions.clear();
+
+    loop {
+        let predicate =
+            |entity| !collisions.iter().any(|hit| hit.entity == entity) && filter(entity);
+
+        if let Some(hit) = B::cast_shape(
+            ctx,
+            shape_pos,
+            shape_rot,
+            shape_vel,
+            shape,
+            max_distance,
+            &predicate,
+        ) {
+            collisions.push(hit);
+        } else {
+            break;
+        }
+    }
+
+    collisions.sort_by(|a, b| {
+        a.distance
+            .total_cmp(&b.distance)
+            .then(a.entity.id().cmp(&b.entity.id()))
+            .then(a.entity.generation().cmp(&b.entity.generation()))
+    });
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_wanderlust::*;
+use bevy_rapier3d::prelude::*;
+use common::{app, step};
+
+/// Presses jump in front of a waist-high wall a character could vault, and returns whether it vaulted and whether it jumped.
+fn vault_or_jump(actions: ActionSettings) -> (bool, bool) {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -1.0, 0.0)))
+        .insert(Collider::cuboid(50.0, 1.0, 50.0));
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.4, -0.95)))
+        .insert(Collider::cuboid(2.0, 0.4, 0.25));
+    let character = app
+        .world
+        .spawn()
+        .insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(0.0, 3.0, 0.0),
+            ..default()
+        })
+        .id();
+    step(&mut app, 60);
+
+    app.world
+        .entity_mut(character)
+        .insert(ForwardObstruction::default())
+        .insert(Vault::default());
+    app.world
+        .get_mut::<ControllerSettings>(character)
+        .unwrap()
+        .actions = actions;
+    step(&mut app, 1);
+    app.world
+        .get_mut::<ControllerInput>(character)
+        .unwrap()
+        .jumping = true;
+    step(&mut app, 1);
+
+    let vaulted = app.world.get::<Vault>(character).unwrap().is_vaulting();
+    let frame = app.world.get::<ControllerFrame>(character).unwrap();
+    assert_eq!(vaulted, frame.actions.contains(&ControllerAction::Vault));
+    (vaulted, frame.jumped)
+}
+
+#[test]
+fn vault_and_jump_together() {
+    assert_eq!(vault_or_jump(ActionSettings::default()), (true, true));
+}
+
+#[test]
+fn vault_cancels_exclusive_jump() {
+    let actions = ActionSettings {
+        priority: vec![ControllerAction::Vault, ControllerAction::Jump],
+        exclusive: vec![(ControllerAction::Vault, ControllerAction::Jump)],
+        ..default()
+    };
+    assert_eq!(vault_or_jump(actions), (true, false));
+}
+
+#[test]
+fn exclusive_jump_blocks_vault() {
+    let actions = ActionSettings {
+        priority: vec![ControllerAction::Jump, ControllerAction::Vault],
+        exclusive: vec![(ControllerAction::Vault, ControllerAction::Jump)],
+        ..default()
+    };
+    assert_eq!(vault_or_jump(actions), (false, true));
+}
+
+/// Drops a character that only grabs ledges on request past a ledge, tapping grab for a frame while the ledge is still out
+/// of reach below its hands. Returns whether it ends up hanging.
+fn tap_grab_above_ledge(actions: ActionSettings) -> bool {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -1.0, 0.0)))
+        .insert(Collider::cuboid(50.0, 1.0, 50.0));
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, -1.5)))
+        .insert(Collider::cuboid(2.0, 1.5, 1.0));
+    let character = app
+        .world
+        .spawn()
+        .insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(0.0, 4.0, 0.1),
+            ..default()
+        })
+        .insert(LedgeHang::default())
+        .id();
+    app.world.get_mut::<LedgeHang>(character).unwrap().auto_grab = false;
+    app.world
+        .get_mut::<ControllerSettings>(character)
+        .unwrap()
+        .actions = actions;
+
+    while app.world.get::<Transform>(character).unwrap().translation.y > 2.6 {
+        step(&mut app, 1);
+    }
+    assert!(app
+        .world
+        .get::<LedgeHang>(character)
+        .unwrap()
+        .detected()
+        .is_none());
+    app.world
+        .get_mut::<ControllerInput>(character)
+        .unwrap()
+        .grabbing = true;
+    step(&mut app, 1);
+    app.world
+        .get_mut::<ControllerInput>(character)
+        .unwrap()
+        .grabbing = false;
+    step(&mut app, 30);
+
+    app.world.get::<LedgeHang>(character).unwrap().is_hanging()
+}
+
+#[test]
+fn grab_pressed_early_is_buffered() {
+    let mut actions = ActionSettings::default();
+    actions.buffer_durations.insert(ControllerAction::Grab, 0.5);
+    assert!(tap_grab_above_ledge(actions));
+}
+
+#[test]
+fn grab_pressed_early_without_buffer() {
+    assert!(!tap_grab_above_ledge(ActionSettings::default()));
+}
+
+/// Dashes, then presses dash again halfway through the cooldown. Returns how many dashes there were.
+fn dash_during_cooldown(actions: ActionSettings) -> usize {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -1.0, 0.0)))
+        .insert(Collider::cuboid(50.0, 1.0, 50.0));
+    let character = app
+        .world
+        .spawn()
+        .insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(0.0, 3.0, 0.0),
+            ..default()
+        })
+        .id();
+    app.world
+        .get_mut::<ControllerSettings>(character)
+        .unwrap()
+        .actions = actions;
+    step(&mut app, 60);
+
+    let mut dashes = 0;
+    let mut press = |app: &mut App, frames: usize| {
+        app.world
+            .get_mut::<ControllerInput>(character)
+            .unwrap()
+            .dashing = true;
+        step(app, 1);
+        app.world
+            .get_mut::<ControllerInput>(character)
+            .unwrap()
+            .dashing = false;
+        for _ in 0..frames {
+            dashes += app
+                .world
+                .resource::<Events<DashEvent>>()
+                .iter_current_update_events()
+                .count();
+            step(app, 1);
+        }
+    };
+    press(&mut app, 20);
+    assert!(app
+        .world
+        .get::<ControllerState>(character)
+        .unwrap()
+        .dash_cooldown()
+        .is_running());
+    press(&mut app, 60);
+    dashes
+}
+
+#[test]
+fn dash_pressed_during_cooldown_is_buffered() {
+    let mut actions = ActionSettings::default();
+    actions.buffer_durations.insert(ControllerAction::Dash, 0.5);
+    assert_eq!(dash_during_cooldown(actions), 2);
+}
+
+#[test]
+fn dash_pressed_during_cooldown_without_buffer() {
+    assert_eq!(dash_during_cooldown(ActionSettings::default()), 1);
+}
@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+use bevy::time::{create_time_channels, TimeSender};
+use bevy::utils::{Duration, Instant};
+use bevy_mod_wanderlust::*;
+use bevy_rapier3d::prelude::*;
+
+/// How much time passes in each [`step`].
+pub const FRAME: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Feeds [`Time`] a fixed [`FRAME`] per update, so tests don't depend on how fast they run.
+struct FrameClock {
+    sender: TimeSender,
+    now: Instant,
+}
+
+/// An app with the controller and Rapier, but nothing that needs a window.
+pub fn app() -> App {
+    let mut app = App::new();
+    let (sender, receiver) = create_time_channels();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(bevy::transform::TransformPlugin)
+        .add_plugin(bevy::hierarchy::HierarchyPlugin)
+        .add_plugin(bevy::asset::AssetPlugin)
+        .add_asset::<Mesh>()
+        .add_asset::<Scene>()
+        .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+        .add_plugin(WanderlustPlugin::default())
+        .insert_resource(receiver)
+        .insert_resource(FrameClock {
+            sender,
+            now: Instant::now(),
+        });
+    app
+}
+
+/// Runs `frames` updates of [`FRAME`] each.
+pub fn step(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        let mut clock = app.world.resource_mut::<FrameClock>();
+        clock.now += FRAME;
+        clock.sender.0.send(clock.now).unwrap();
+        app.update();
+    }
+}
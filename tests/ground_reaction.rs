@@ -0,0 +1,91 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_wanderlust::*;
+use bevy_rapier3d::prelude::*;
+use common::{app, step};
+
+/// The impulse gameplay code gives the raft, on the last frame only.
+#[derive(Default)]
+struct Shove(Vec3);
+
+/// The raft's impulse once every system has added to it.
+#[derive(Default)]
+struct Applied(ExternalImpulse);
+
+#[derive(Component)]
+struct Raft;
+
+fn shove(shove: Res<Shove>, mut rafts: Query<&mut ExternalImpulse, With<Raft>>) {
+    if shove.0 == Vec3::ZERO {
+        return;
+    }
+    for mut impulse in rafts.iter_mut() {
+        *impulse = ExternalImpulse {
+            impulse: shove.0,
+            ..default()
+        };
+    }
+}
+
+fn record(mut applied: ResMut<Applied>, rafts: Query<&ExternalImpulse, With<Raft>>) {
+    applied.0 = *rafts.single();
+}
+
+/// The impulse given to a raft with characters standing at `characters` along it, once they have settled.
+fn raft_impulse(characters: &[f32], shoved: Vec3) -> ExternalImpulse {
+    let mut app = app();
+    app.init_resource::<Shove>()
+        .init_resource::<Applied>()
+        .add_system_to_stage(
+            CoreStage::Update,
+            shove.before(push_ground::<RapierBackend>),
+        )
+        .add_system_to_stage(
+            CoreStage::Update,
+            record.after(push_ground::<RapierBackend>),
+        );
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0)))
+        .insert(RigidBody::Dynamic)
+        .insert(GravityScale(0.0))
+        .insert(Damping {
+            linear_damping: 1.0,
+            angular_damping: 1.0,
+        })
+        .insert(Collider::cuboid(3.0, 0.25, 3.0))
+        .insert(ExternalImpulse::default())
+        .insert(Raft);
+    for &x in characters {
+        let mut settings = ControllerSettings::character();
+        settings.float.ground_reaction = 1.0;
+        app.world.spawn().insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(x, 1.5, 0.0),
+            settings,
+            ..default()
+        });
+    }
+    step(&mut app, 60);
+    // Everything up to here is the same whatever the shove, so the last frame's reactions are too.
+    app.insert_resource(Shove(shoved));
+    step(&mut app, 1);
+    app.world.resource::<Applied>().0
+}
+
+#[test]
+fn ground_reaction_shared_body() {
+    let one = raft_impulse(&[0.0], Vec3::ZERO);
+    let two = raft_impulse(&[-2.0, 2.0], Vec3::ZERO);
+    assert!(one.impulse.y < 0.0);
+    // Both characters push on the raft.
+    assert!((two.impulse.y / one.impulse.y - 2.0).abs() < 0.1);
+    // Standing on opposite sides balances the raft out.
+    assert!(two.torque_impulse.length() < 0.1 * two.impulse.length());
+
+    // The impulse gameplay code gives the raft this frame is added to, not replaced.
+    let push = Vec3::X * 0.05;
+    let shoved = raft_impulse(&[-2.0, 2.0], push);
+    assert!((shoved.impulse - (two.impulse + push)).length() < 1e-5);
+    assert!((shoved.torque_impulse - two.torque_impulse).length() < 1e-5);
+}
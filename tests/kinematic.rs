@@ -0,0 +1,56 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_mod_wanderlust::*;
+use bevy_rapier3d::prelude::*;
+use common::{app, step};
+
+#[test]
+fn kinematic_overlap_does_not_tunnel() {
+    let mut app = app();
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+        .insert(Collider::cuboid(40.0, 0.5, 40.0));
+    app.world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(4.0, 1.0, 0.0)))
+        .insert(Collider::cuboid(0.5, 2.0, 40.0));
+    let character = app
+        .world
+        .spawn()
+        .insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(0.0, 1.5, 0.0),
+            settings: ControllerSettings {
+                body: ControllerBody::Kinematic,
+                ..ControllerSettings::character()
+            },
+            ..default()
+        })
+        .id();
+    step(&mut app, 60);
+
+    // A thin bar through the character's middle, reaching into the wall, that the character overlaps the whole way there.
+    let center = app.world.get::<Transform>(character).unwrap().translation;
+    let bar = app
+        .world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_translation(center)))
+        .insert(Collider::cuboid(10.0, 0.05, 0.05))
+        .id();
+    app.world
+        .entity_mut(character)
+        .insert(GroundCastFilter::ignoring([bar]));
+    app.world
+        .get_mut::<ControllerInput>(character)
+        .unwrap()
+        .movement = Vec3::X;
+    step(&mut app, 120);
+
+    let tf = app.world.get::<Transform>(character).unwrap();
+    assert!(
+        tf.translation.x > 2.5 && tf.translation.x < 3.5,
+        "stopped at {}",
+        tf.translation.x
+    );
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use bevy::math::DVec3;
+use bevy::prelude::*;
+use bevy_mod_wanderlust::*;
+use bevy_rapier3d::prelude::*;
+use common::{app, step};
+
+/// Checks that Rapier has `entity`'s body where its transform is.
+fn assert_in_sync(app: &App, entity: Entity) {
+    let tf = app.world.get::<Transform>(entity).unwrap();
+    let handle = app.world.get::<RapierRigidBodyHandle>(entity).unwrap();
+    let ctx = app.world.resource::<RapierContext>();
+    let body: Vec3 = (*ctx.bodies[handle.0].translation()).into();
+    assert!(
+        body.distance(tf.translation) < 0.01,
+        "{:?} is at {} in Rapier but at {}",
+        entity,
+        body,
+        tf.translation
+    );
+}
+
+#[test]
+fn recenter_keeps_character_on_ground() {
+    let mut app = app();
+    app.insert_resource(WorldOrigin {
+        position: DVec3::ZERO,
+        recenter_distance: 1000.0,
+    });
+    let ground = app
+        .world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(1500.0, 0.0, 0.0)))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(20.0, 0.5, 20.0))
+        .id();
+    let character = app
+        .world
+        .spawn()
+        .insert_bundle(CharacterControllerBundle {
+            transform: Transform::from_xyz(1500.0, 1.5, 0.0),
+            ..default()
+        })
+        .insert(LodViewer)
+        .insert(WorldPosition::default())
+        .id();
+    let scenery = app
+        .world
+        .spawn()
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(1500.0, 0.0, 0.0)))
+        .id();
+
+    step(&mut app, 1);
+    let origin = *app.world.resource::<WorldOrigin>();
+    assert!((origin.position.x - 1500.0).abs() < 0.01);
+    let ground_tf = *app.world.get::<Transform>(ground).unwrap();
+    assert!(ground_tf.translation.x.abs() < 0.01);
+    // Only physics entities, viewers and marked entities are moved.
+    let scenery_tf = app.world.get::<Transform>(scenery).unwrap();
+    assert_eq!(scenery_tf.translation.x, 1500.0);
+
+    // Rapier moves the bodies once, not once by hand and again when syncing with the moved transforms.
+    step(&mut app, 1);
+    assert_in_sync(&app, ground);
+    assert_in_sync(&app, character);
+
+    step(&mut app, 60);
+    assert_in_sync(&app, ground);
+    assert_in_sync(&app, character);
+    let ground_tf = *app.world.get::<Transform>(ground).unwrap();
+    assert!(ground_tf.translation.x.abs() < 0.01);
+    let tf = *app.world.get::<Transform>(character).unwrap();
+    let above = tf.translation - ground_tf.translation;
+    assert!(
+        above.y > 0.5 && above.y < 2.5,
+        "{} above the ground",
+        above.y
+    );
+    assert!(
+        app.world
+            .get::<ControllerState>(character)
+            .unwrap()
+            .is_grounded
+    );
+    let position = app.world.get::<WorldPosition>(character).unwrap();
+    assert!((position.0.x - 1500.0).abs() < 0.1);
+    // Nothing moved far enough to recenter again.
+    assert_eq!(
+        app.world.resource::<WorldOrigin>().position,
+        origin.position
+    );
+}